@@ -0,0 +1,14 @@
+//! Shared library exporting the C ABI of the `caps` crate.
+//!
+//! This crate only re-exports `caps::capi`, as a `cdylib` declared in
+//! `include/caps.h`:
+//!
+//! ```text
+//! cargo build --release -p caps-capi
+//! ```
+//!
+//! which produces `libcaps_capi.so`.
+
+extern crate caps;
+
+pub use caps::capi::*;
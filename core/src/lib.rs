@@ -0,0 +1,387 @@
+//! Allocation-free core of the `caps` crate.
+//!
+//! This crate holds the raw syscall and bitmask logic of `caps`, without
+//! depending on `std` or allocating: capability sets are plain `u64`
+//! bitmasks (bit `n` standing for capability index `n`), failures are
+//! reported as raw errno values, and syscalls are issued through the
+//! `Syscalls` shim. This makes it usable from `#![no_std]` programs, such
+//! as init systems and early-boot tools.
+//!
+//! With the default `libc` feature, `Libc` implements the shim on top of
+//! libc on Linux and Android. Programs without libc can provide their own
+//! implementation.
+//!
+//! All operations target the calling thread.
+//!
+//! ```rust
+//! use caps_core::{bit, Libc};
+//!
+//! let (effective, _, _) = caps_core::capget(&Libc).unwrap();
+//! let has_chown = (effective & bit(0)) != 0;
+//! ```
+
+#![no_std]
+
+#[cfg(feature = "libc")]
+extern crate libc;
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+pub mod nr;
+
+/// Raw errno value of a failed syscall.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Errno(pub i32);
+
+/// Result of core operations.
+pub type Result<T> = ::core::result::Result<T, Errno>;
+
+/// Version 1 of the capget/capset interface, for 32-bit capability sets.
+#[allow(clippy::unreadable_literal)]
+pub const CAPS_V1: u32 = 0x19980330;
+
+/// Version 2 of the capget/capset interface, for 64-bit capability sets
+/// (deprecated in favor of version 3).
+#[allow(clippy::unreadable_literal)]
+pub const CAPS_V2: u32 = 0x20071026;
+
+/// Version 3 of the capget/capset interface, for 64-bit capability sets.
+#[allow(clippy::unreadable_literal)]
+pub const CAPS_V3: u32 = 0x20080522;
+
+// EINVAL, whose value is the same on all Linux architectures.
+const EINVAL: i32 = 22;
+
+// Version of the capget/capset interface, negotiated down on kernels
+// rejecting newer ones.
+static VERSION: AtomicU32 = AtomicU32::new(CAPS_V3);
+
+/// Header of capget/capset calls (`struct __user_cap_header_struct`).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct CapUserHeader {
+    /// Linux capabilities version (runtime kernel support).
+    pub version: u32,
+    /// Process ID (thread), or 0 for the calling thread.
+    pub pid: i32,
+}
+
+/// One `struct __user_cap_data_struct`, holding 32 capabilities of each set.
+///
+/// Versions 2 and 3 of the capget/capset interface exchange an array of two
+/// units (capabilities 0 to 31, then 32 to 63), and version 1 a single one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct CapUserDataUnit {
+    /// Effective capabilities.
+    pub effective: u32,
+    /// Permitted capabilities.
+    pub permitted: u32,
+    /// Inheritable capabilities.
+    pub inheritable: u32,
+}
+
+/// Data of capget/capset calls, as a flat view over two `CapUserDataUnit`.
+///
+/// `_s0` fields hold capabilities 0 to 31, and `_s1` fields 32 to 63. This
+/// is layout-equivalent to `[CapUserDataUnit; 2]`, which is what syscalls
+/// are actually passed (see `units` and `from_units`).
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+pub struct CapUserData {
+    /// Effective capabilities 0 to 31.
+    pub effective_s0: u32,
+    /// Permitted capabilities 0 to 31.
+    pub permitted_s0: u32,
+    /// Inheritable capabilities 0 to 31.
+    pub inheritable_s0: u32,
+    /// Effective capabilities 32 to 63.
+    pub effective_s1: u32,
+    /// Permitted capabilities 32 to 63.
+    pub permitted_s1: u32,
+    /// Inheritable capabilities 32 to 63.
+    pub inheritable_s1: u32,
+}
+
+impl CapUserData {
+    /// Return the two data units of the kernel ABI.
+    pub fn units(&self) -> [CapUserDataUnit; 2] {
+        [
+            CapUserDataUnit {
+                effective: self.effective_s0,
+                permitted: self.permitted_s0,
+                inheritable: self.inheritable_s0,
+            },
+            CapUserDataUnit {
+                effective: self.effective_s1,
+                permitted: self.permitted_s1,
+                inheritable: self.inheritable_s1,
+            },
+        ]
+    }
+
+    /// Build data from the two data units of the kernel ABI.
+    pub fn from_units(units: &[CapUserDataUnit; 2]) -> CapUserData {
+        CapUserData {
+            effective_s0: units[0].effective,
+            permitted_s0: units[0].permitted,
+            inheritable_s0: units[0].inheritable,
+            effective_s1: units[1].effective,
+            permitted_s1: units[1].permitted,
+            inheritable_s1: units[1].inheritable,
+        }
+    }
+}
+
+/// Shim issuing the syscalls needed by this crate.
+pub trait Syscalls {
+    /// Issue `capget(2)`.
+    fn capget(&self, hdr: &mut CapUserHeader, data: &mut CapUserData) -> Result<()>;
+
+    /// Issue `capset(2)`.
+    fn capset(&self, hdr: &mut CapUserHeader, data: &CapUserData) -> Result<()>;
+
+    /// Issue `prctl(2)` with `option` and two arguments, other ones being 0.
+    fn prctl(&self, option: i32, arg2: u64, arg3: u64) -> Result<i32>;
+}
+
+/// Syscalls issued via libc (Linux and Android only).
+#[cfg(all(feature = "libc", any(target_os = "linux", target_os = "android")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Libc;
+
+#[cfg(all(feature = "libc", any(target_os = "linux", target_os = "android")))]
+impl Libc {
+    fn errno() -> Errno {
+        #[cfg(target_os = "android")]
+        let e = unsafe { *libc::__errno() };
+        #[cfg(not(target_os = "android"))]
+        let e = unsafe { *libc::__errno_location() };
+        Errno(e)
+    }
+}
+
+#[cfg(all(feature = "libc", any(target_os = "linux", target_os = "android")))]
+impl Syscalls for Libc {
+    fn capget(&self, hdr: &mut CapUserHeader, data: &mut CapUserData) -> Result<()> {
+        let mut units = data.units();
+        match unsafe { libc::syscall(nr::CAPGET, hdr, units.as_mut_ptr()) } {
+            0 => {
+                *data = CapUserData::from_units(&units);
+                Ok(())
+            }
+            _ => Err(Libc::errno()),
+        }
+    }
+
+    fn capset(&self, hdr: &mut CapUserHeader, data: &CapUserData) -> Result<()> {
+        let units = data.units();
+        match unsafe { libc::syscall(nr::CAPSET, hdr, units.as_ptr()) } {
+            0 => Ok(()),
+            _ => Err(Libc::errno()),
+        }
+    }
+
+    fn prctl(&self, option: i32, arg2: u64, arg3: u64) -> Result<i32> {
+        let arg2 = arg2 as libc::c_ulong;
+        let arg3 = arg3 as libc::c_ulong;
+        match unsafe { libc::prctl(option, arg2, arg3, 0 as libc::c_ulong, 0 as libc::c_ulong) } {
+            -1 => Err(Libc::errno()),
+            r => Ok(r),
+        }
+    }
+}
+
+/// Return the bitmask of capability `index`.
+pub const fn bit(index: u8) -> u64 {
+    1u64 << index
+}
+
+/// Iterate over capability indices set in `mask`, in increasing order.
+pub fn indices(mask: u64) -> impl Iterator<Item = u8> {
+    (0..64u8).filter(move |&i| (mask & bit(i)) != 0)
+}
+
+/// Return the capget/capset interface version preferred by the kernel.
+///
+/// The kernel reports its preferred version when called with an unknown
+/// one (here 0), so this works on any kernel.
+pub fn probe_version<S: Syscalls>(sys: &S) -> Result<u32> {
+    let mut hdr = CapUserHeader { version: 0, pid: 0 };
+    let mut data = CapUserData::default();
+    match sys.capget(&mut hdr, &mut data) {
+        Ok(()) | Err(Errno(EINVAL)) => Ok(hdr.version),
+        Err(e) => Err(e),
+    }
+}
+
+// Return the version suggested by the kernel after it rejected version
+// `sent` with `err`, if the call should be retried with it.
+fn negotiate(hdr: &CapUserHeader, sent: u32, err: Errno) -> Option<u32> {
+    let known = hdr.version == CAPS_V1 || hdr.version == CAPS_V2;
+    if err.0 != EINVAL || !known || hdr.version == sent {
+        return None;
+    }
+    Some(hdr.version)
+}
+
+// Clear capabilities 32 to 63, which are unknown to version 1.
+fn mask_upper(data: &mut CapUserData) {
+    data.effective_s1 = 0;
+    data.permitted_s1 = 0;
+    data.inheritable_s1 = 0;
+}
+
+/// Issue `capget(2)` for thread `pid` (0 for the calling thread).
+///
+/// Version 3 of the interface is tried first. Kernels rejecting it
+/// suggest an older version, which is then used by this and all later
+/// calls. Capabilities 32 to 63 are reported as unset with version 1.
+pub fn capget_data<S: Syscalls>(sys: &S, pid: i32) -> Result<CapUserData> {
+    let mut data = CapUserData::default();
+    loop {
+        let sent = VERSION.load(Ordering::Relaxed);
+        let mut hdr = CapUserHeader { version: sent, pid };
+        match sys.capget(&mut hdr, &mut data) {
+            Ok(()) => {
+                if sent == CAPS_V1 {
+                    mask_upper(&mut data);
+                }
+                return Ok(data);
+            }
+            Err(e) => match negotiate(&hdr, sent, e) {
+                Some(version) => VERSION.store(version, Ordering::Relaxed),
+                None => return Err(e),
+            },
+        }
+    }
+}
+
+/// Issue `capset(2)` for thread `pid` (0 for the calling thread).
+///
+/// The interface version is negotiated as in `capget_data`. Capabilities
+/// 32 to 63 are ignored with version 1.
+pub fn capset_data<S: Syscalls>(sys: &S, pid: i32, data: &CapUserData) -> Result<()> {
+    let mut data = *data;
+    loop {
+        let sent = VERSION.load(Ordering::Relaxed);
+        let mut hdr = CapUserHeader { version: sent, pid };
+        if sent == CAPS_V1 {
+            mask_upper(&mut data);
+        }
+        match sys.capset(&mut hdr, &data) {
+            Ok(()) => return Ok(()),
+            Err(e) => match negotiate(&hdr, sent, e) {
+                Some(version) => VERSION.store(version, Ordering::Relaxed),
+                None => return Err(e),
+            },
+        }
+    }
+}
+
+/// Read (effective, permitted, inheritable) masks.
+///
+/// The interface version is negotiated as in `capget_data`.
+pub fn capget<S: Syscalls>(sys: &S) -> Result<(u64, u64, u64)> {
+    let data = capget_data(sys, 0)?;
+    Ok((
+        u64::from(data.effective_s0) | (u64::from(data.effective_s1) << 32),
+        u64::from(data.permitted_s0) | (u64::from(data.permitted_s1) << 32),
+        u64::from(data.inheritable_s0) | (u64::from(data.inheritable_s1) << 32),
+    ))
+}
+
+/// Set effective, permitted and inheritable masks.
+///
+/// The interface version is negotiated as in `capget_data`.
+pub fn capset<S: Syscalls>(
+    sys: &S,
+    effective: u64,
+    permitted: u64,
+    inheritable: u64,
+) -> Result<()> {
+    let data = CapUserData {
+        effective_s0: effective as u32,
+        permitted_s0: permitted as u32,
+        inheritable_s0: inheritable as u32,
+        effective_s1: (effective >> 32) as u32,
+        permitted_s1: (permitted >> 32) as u32,
+        inheritable_s1: (inheritable >> 32) as u32,
+    };
+    capset_data(sys, 0, &data)
+}
+
+/// Check whether capability `index` is in the bounding set.
+///
+/// Unknown capabilities are reported as not held.
+pub fn bounding_read<S: Syscalls>(sys: &S, index: u8) -> bool {
+    sys.prctl(nr::PR_CAPBSET_READ, index.into(), 0) == Ok(1)
+}
+
+/// Drop capability `index` from the bounding set.
+pub fn bounding_drop<S: Syscalls>(sys: &S, index: u8) -> Result<()> {
+    sys.prctl(nr::PR_CAPBSET_DROP, index.into(), 0).map(|_| ())
+}
+
+/// Check whether capability `index` is in the ambient set.
+///
+/// Unknown capabilities are reported as not held.
+pub fn ambient_read<S: Syscalls>(sys: &S, index: u8) -> bool {
+    let is_set = nr::PR_CAP_AMBIENT_IS_SET as u64;
+    sys.prctl(nr::PR_CAP_AMBIENT, is_set, index.into()) == Ok(1)
+}
+
+/// Raise capability `index` in the ambient set.
+pub fn ambient_raise<S: Syscalls>(sys: &S, index: u8) -> Result<()> {
+    let raise = nr::PR_CAP_AMBIENT_RAISE as u64;
+    sys.prctl(nr::PR_CAP_AMBIENT, raise, index.into())
+        .map(|_| ())
+}
+
+/// Lower capability `index` in the ambient set.
+pub fn ambient_lower<S: Syscalls>(sys: &S, index: u8) -> Result<()> {
+    let lower = nr::PR_CAP_AMBIENT_LOWER as u64;
+    sys.prctl(nr::PR_CAP_AMBIENT, lower, index.into())
+        .map(|_| ())
+}
+
+/// Clear the ambient set.
+pub fn ambient_clear<S: Syscalls>(sys: &S) -> Result<()> {
+    let clear_all = nr::PR_CAP_AMBIENT_CLEAR_ALL as u64;
+    sys.prctl(nr::PR_CAP_AMBIENT, clear_all, 0).map(|_| ())
+}
+
+/// Read the bounding set as a mask.
+pub fn bounding_mask<S: Syscalls>(sys: &S) -> u64 {
+    (0..64)
+        .filter(|&i| bounding_read(sys, i))
+        .fold(0, |m, i| m | bit(i))
+}
+
+/// Read the ambient set as a mask.
+pub fn ambient_mask<S: Syscalls>(sys: &S) -> u64 {
+    (0..64)
+        .filter(|&i| ambient_read(sys, i))
+        .fold(0, |m, i| m | bit(i))
+}
+
+/// Read the securebits as a raw value.
+pub fn get_securebits<S: Syscalls>(sys: &S) -> Result<u32> {
+    sys.prctl(nr::PR_GET_SECUREBITS, 0, 0)
+        .map(|bits| bits as u32)
+}
+
+/// Set the securebits to the raw value `bits`.
+pub fn set_securebits<S: Syscalls>(sys: &S, bits: u32) -> Result<()> {
+    sys.prctl(nr::PR_SET_SECUREBITS, bits.into(), 0).map(|_| ())
+}
+
+/// Set the "keep capabilities" flag.
+pub fn set_keepcaps<S: Syscalls>(sys: &S, keep: bool) -> Result<()> {
+    sys.prctl(nr::PR_SET_KEEPCAPS, keep.into(), 0).map(|_| ())
+}
+
+/// Set the `no_new_privs` attribute.
+pub fn set_no_new_privs<S: Syscalls>(sys: &S) -> Result<()> {
+    sys.prctl(nr::PR_SET_NO_NEW_PRIVS, 1, 0).map(|_| ())
+}
@@ -0,0 +1,128 @@
+extern crate caps_core;
+extern crate libc;
+
+use caps_core::{bit, indices, CapUserData, CapUserHeader, Errno, Libc, Syscalls};
+use std::cell::RefCell;
+
+// Thread state emulated in memory.
+#[derive(Default)]
+struct Mock {
+    data: RefCell<CapUserData>,
+    bounding: RefCell<u64>,
+    ambient: RefCell<u64>,
+}
+
+impl Syscalls for Mock {
+    fn capget(&self, hdr: &mut CapUserHeader, data: &mut CapUserData) -> caps_core::Result<()> {
+        assert_eq!(hdr.version, caps_core::CAPS_V3);
+        *data = *self.data.borrow();
+        Ok(())
+    }
+
+    fn capset(&self, _hdr: &mut CapUserHeader, data: &CapUserData) -> caps_core::Result<()> {
+        *self.data.borrow_mut() = *data;
+        Ok(())
+    }
+
+    fn prctl(&self, option: i32, arg2: u64, arg3: u64) -> caps_core::Result<i32> {
+        use caps_core::nr::*;
+        match option {
+            PR_CAPBSET_READ => Ok((*self.bounding.borrow() & (1 << arg2) != 0) as i32),
+            PR_CAPBSET_DROP => {
+                *self.bounding.borrow_mut() &= !(1 << arg2);
+                Ok(0)
+            }
+            PR_CAP_AMBIENT => match arg2 as i32 {
+                PR_CAP_AMBIENT_IS_SET => Ok((*self.ambient.borrow() & (1 << arg3) != 0) as i32),
+                PR_CAP_AMBIENT_RAISE => {
+                    *self.ambient.borrow_mut() |= 1 << arg3;
+                    Ok(0)
+                }
+                PR_CAP_AMBIENT_CLEAR_ALL => {
+                    *self.ambient.borrow_mut() = 0;
+                    Ok(0)
+                }
+                _ => Err(Errno(22)),
+            },
+            _ => Err(Errno(22)),
+        }
+    }
+}
+
+#[test]
+fn test_core_masks() {
+    assert_eq!(bit(33), 1 << 33);
+    assert_eq!(indices(bit(1) | bit(40)).collect::<Vec<_>>(), vec![1, 40]);
+    let mock = Mock::default();
+    caps_core::capset(&mock, bit(0), bit(0) | bit(35), bit(35)).unwrap();
+    assert_eq!(mock.data.borrow().permitted_s1, 1 << 3);
+    assert_eq!(
+        caps_core::capget(&mock).unwrap(),
+        (bit(0), bit(0) | bit(35), bit(35))
+    );
+}
+
+#[test]
+fn test_core_prctls() {
+    let mock = Mock::default();
+    *mock.bounding.borrow_mut() = bit(0) | bit(5);
+    assert!(caps_core::bounding_read(&mock, 5));
+    caps_core::bounding_drop(&mock, 5).unwrap();
+    assert_eq!(caps_core::bounding_mask(&mock), bit(0));
+    caps_core::ambient_raise(&mock, 7).unwrap();
+    assert!(caps_core::ambient_read(&mock, 7));
+    caps_core::ambient_clear(&mock).unwrap();
+    assert_eq!(caps_core::ambient_mask(&mock), 0);
+    assert_eq!(caps_core::ambient_lower(&mock, 7), Err(Errno(22)));
+}
+
+#[test]
+fn test_core_libc() {
+    let (effective, permitted, _) = caps_core::capget(&Libc).unwrap();
+    assert_eq!(effective & !permitted, 0);
+    assert!(caps_core::bounding_mask(&Libc) != 0 || !caps_core::bounding_read(&Libc, 0));
+    assert!(caps_core::bounding_drop(&Libc, 63).is_err());
+}
+
+#[test]
+fn test_core_nr_matches_libc() {
+    assert_eq!(caps_core::nr::CAPGET, libc::SYS_capget);
+    assert_eq!(caps_core::nr::CAPSET, libc::SYS_capset);
+    assert_eq!(caps_core::nr::PR_CAPBSET_READ, libc::PR_CAPBSET_READ);
+    assert_eq!(caps_core::nr::PR_CAPBSET_DROP, libc::PR_CAPBSET_DROP);
+    assert_eq!(caps_core::nr::PR_SET_SECUREBITS, libc::PR_SET_SECUREBITS);
+    assert_eq!(caps_core::nr::PR_CAP_AMBIENT, libc::PR_CAP_AMBIENT);
+    assert_eq!(
+        caps_core::nr::PR_CAP_AMBIENT_RAISE,
+        libc::PR_CAP_AMBIENT_RAISE
+    );
+}
+
+#[test]
+fn test_core_probe_version() {
+    let version = caps_core::probe_version(&Libc).unwrap();
+    assert_eq!(version, caps_core::CAPS_V3);
+}
+
+#[test]
+fn test_core_data_units() {
+    use caps_core::CapUserDataUnit;
+    use std::mem::{align_of, size_of};
+    assert_eq!(size_of::<CapUserData>(), size_of::<[CapUserDataUnit; 2]>());
+    assert_eq!(
+        align_of::<CapUserData>(),
+        align_of::<[CapUserDataUnit; 2]>()
+    );
+    let data = CapUserData {
+        effective_s0: 1,
+        permitted_s0: 2,
+        inheritable_s0: 3,
+        effective_s1: 4,
+        permitted_s1: 5,
+        inheritable_s1: 6,
+    };
+    let units = data.units();
+    assert_eq!(units[1].permitted, 5);
+    let again = CapUserData::from_units(&units);
+    assert_eq!(again.units(), units);
+}
@@ -0,0 +1,60 @@
+extern crate caps_core;
+
+use caps_core::{bit, CapUserData, CapUserHeader, Errno, Syscalls, CAPS_V1};
+use std::cell::{Cell, RefCell};
+
+// Kernel only knowing version 1 of the interface, emulated in memory.
+#[derive(Default)]
+struct V1Only {
+    data: RefCell<CapUserData>,
+    rejected: Cell<u32>,
+}
+
+impl V1Only {
+    fn check(&self, hdr: &mut CapUserHeader) -> caps_core::Result<()> {
+        if hdr.version == CAPS_V1 {
+            return Ok(());
+        }
+        self.rejected.set(self.rejected.get() + 1);
+        hdr.version = CAPS_V1;
+        Err(Errno(22))
+    }
+}
+
+impl Syscalls for V1Only {
+    fn capget(&self, hdr: &mut CapUserHeader, data: &mut CapUserData) -> caps_core::Result<()> {
+        self.check(hdr)?;
+        // Version 1 only fills the first data unit.
+        data.effective_s0 = self.data.borrow().effective_s0;
+        data.permitted_s0 = self.data.borrow().permitted_s0;
+        data.inheritable_s0 = self.data.borrow().inheritable_s0;
+        Ok(())
+    }
+
+    fn capset(&self, hdr: &mut CapUserHeader, data: &CapUserData) -> caps_core::Result<()> {
+        self.check(hdr)?;
+        *self.data.borrow_mut() = *data;
+        Ok(())
+    }
+
+    fn prctl(&self, _option: i32, _arg2: u64, _arg3: u64) -> caps_core::Result<i32> {
+        Err(Errno(22))
+    }
+}
+
+// The negotiated version is process-wide, hence this separate test binary.
+#[test]
+fn test_core_negotiate() {
+    let mock = V1Only::default();
+    caps_core::capset(&mock, bit(0) | bit(40), bit(0) | bit(40), 0).unwrap();
+    assert_eq!(mock.data.borrow().permitted_s1, 0);
+    *mock.data.borrow_mut() = CapUserData {
+        effective_s1: !0,
+        ..Default::default()
+    };
+    assert_eq!(caps_core::capget(&mock).unwrap(), (0, 0, 0));
+    let data = caps_core::capget_data(&mock, 42).unwrap();
+    assert_eq!(data.effective_s1, 0);
+    // Version 1 is remembered after the first rejection.
+    assert_eq!(mock.rejected.get(), 1);
+}
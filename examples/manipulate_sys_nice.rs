@@ -25,7 +25,7 @@ fn main() {
     println!("Dropped CAP_SYS_NICE.");
     let has_sys_nice = caps::has_cap(None, CapSet::Effective, Capability::CAP_SYS_NICE);
     assert!(has_sys_nice.is_ok());
-    assert_eq!(has_sys_nice.unwrap(), false);
+    assert!(!has_sys_nice.unwrap());
     let r = renice(-20);
     assert_eq!(r, -1);
     println!("Unprivileged, unable to raise priority to -20.");
@@ -48,9 +48,10 @@ fn main() {
     println!("-> Current process priority is {}.", proc_nice());
 }
 
-#[cfg(target_env = "musl")]
+#[cfg(any(target_env = "musl", target_os = "android"))]
 const PRIO_PROCESS: i32 = libc::PRIO_PROCESS;
-#[cfg(not(target_env = "musl"))]
+#[cfg(not(any(target_env = "musl", target_os = "android")))]
+#[allow(clippy::unnecessary_cast)]
 const PRIO_PROCESS: u32 = libc::PRIO_PROCESS as u32;
 
 fn renice(prio: libc::c_int) -> libc::c_int {
@@ -60,7 +61,7 @@ fn renice(prio: libc::c_int) -> libc::c_int {
 
 fn proc_nice() -> libc::c_int {
     // This is not proper logic, as it does not special-case -1 nor drain errno.
-    let r = unsafe { libc::getpriority(PRIO_PROCESS as u32, 0) };
+    let r = unsafe { libc::getpriority(PRIO_PROCESS, 0) };
     if r == -1 {
         panic!("getpriority failed.");
     }
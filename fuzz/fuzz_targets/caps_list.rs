@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use caps::policy;
+use caps::Capability;
+
+fuzz_target!(|text: &str| {
+    let _ = text.parse::<Capability>();
+    let _ = policy::parse_list(text);
+});
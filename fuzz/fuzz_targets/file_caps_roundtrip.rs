@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use caps::file::FileCaps;
+
+fuzz_target!(|fcaps: FileCaps| {
+    assert_eq!(FileCaps::from_xattr(&fcaps.to_xattr()).unwrap(), fcaps);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use caps::file::FileCaps;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(fcaps) = FileCaps::from_xattr(data) {
+        // Any accepted value serializes back to an equivalent one.
+        let again = FileCaps::from_xattr(&fcaps.to_xattr()).unwrap();
+        assert_eq!(again, fcaps);
+    }
+});
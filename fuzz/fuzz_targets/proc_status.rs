@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use caps::CapState;
+
+fuzz_target!(|status: &str| {
+    let _ = CapState::from_proc_status(status);
+});
@@ -0,0 +1,44 @@
+/*
+ * C interface of the caps crate, built with the `capi` feature.
+ *
+ * Threads are identified by their TID (0 for the calling thread), and
+ * capabilities by their kernel index (as CAP_* in <linux/capability.h>).
+ * Functions return -1 and set errno on failure.
+ */
+
+#ifndef CAPS_H
+#define CAPS_H
+
+#include <stdint.h>
+#include <sys/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CAPS_AMBIENT 0
+#define CAPS_BOUNDING 1
+#define CAPS_EFFECTIVE 2
+#define CAPS_INHERITABLE 3
+#define CAPS_PERMITTED 4
+
+/* Read a set into a raw mask. */
+int caps_get(pid_t tid, int set, uint64_t *mask);
+/* Replace a set with a raw mask. */
+int caps_set(pid_t tid, int set, uint64_t mask);
+/* Return 1 if a capability is in a set, 0 otherwise. */
+int caps_has(pid_t tid, int set, int cap);
+/* Raise a capability in a set. */
+int caps_raise(pid_t tid, int set, int cap);
+/* Drop a capability from a set. */
+int caps_drop(pid_t tid, int set, int cap);
+/* Return the index of a capability name, with or without "cap_" prefix. */
+int caps_from_name(const char *name);
+/* Return the static lowercase name of a capability, or NULL. */
+const char *caps_to_name(int cap);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CAPS_H */
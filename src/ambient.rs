@@ -1,67 +1,62 @@
-use errno;
-use libc;
-
 use super::Capability;
 use errors::*;
 use nr;
+use std::os::raw::c_ulong;
+use {sys, trace};
 
 pub fn clear() -> Result<()> {
-    let ret = unsafe { libc::prctl(nr::PR_CAP_AMBIENT, nr::PR_CAP_AMBIENT_CLEAR_ALL, 0, 0, 0) };
+    let ret = sys::prctl(
+        nr::PR_CAP_AMBIENT,
+        nr::PR_CAP_AMBIENT_CLEAR_ALL as c_ulong,
+        0,
+    );
+    trace::syscall("PR_CAP_AMBIENT_CLEAR_ALL", ret.into());
     match ret {
         0 => Ok(()),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
             .chain_err(|| "PR_CAP_AMBIENT_CLEAR_ALL error")),
     }
 }
 
 pub fn drop(cap: Capability) -> Result<()> {
-    let ret = unsafe {
-        libc::prctl(
-            nr::PR_CAP_AMBIENT,
-            nr::PR_CAP_AMBIENT_LOWER,
-            libc::c_uint::from(cap.index()),
-            0,
-            0,
-        )
-    };
+    let ret = sys::prctl(
+        nr::PR_CAP_AMBIENT,
+        nr::PR_CAP_AMBIENT_LOWER as c_ulong,
+        c_ulong::from(cap.index()),
+    );
+    trace::syscall("PR_CAP_AMBIENT_LOWER", ret.into());
     match ret {
         0 => Ok(()),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
             .chain_err(|| "PR_CAP_AMBIENT_LOWER error")),
     }
 }
 
 pub fn has_cap(cap: Capability) -> Result<bool> {
-    let ret = unsafe {
-        libc::prctl(
-            nr::PR_CAP_AMBIENT,
-            nr::PR_CAP_AMBIENT_IS_SET,
-            libc::c_uint::from(cap.index()),
-            0,
-            0,
-        )
-    };
+    let ret = sys::prctl(
+        nr::PR_CAP_AMBIENT,
+        nr::PR_CAP_AMBIENT_IS_SET as c_ulong,
+        c_ulong::from(cap.index()),
+    );
+    trace::syscall("PR_CAP_AMBIENT_IS_SET", ret.into());
     match ret {
         0 => Ok(false),
         1 => Ok(true),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
             .chain_err(|| "PR_CAP_AMBIENT_IS_SET error")),
     }
 }
 
 pub fn raise(cap: Capability) -> Result<()> {
-    let ret = unsafe {
-        libc::prctl(
-            nr::PR_CAP_AMBIENT,
-            nr::PR_CAP_AMBIENT_RAISE,
-            libc::c_uint::from(cap.index()),
-            0,
-            0,
-        )
-    };
+    let ret = sys::prctl(
+        nr::PR_CAP_AMBIENT,
+        nr::PR_CAP_AMBIENT_RAISE as c_ulong,
+        c_ulong::from(cap.index()),
+    );
+    trace::syscall("PR_CAP_AMBIENT_RAISE", ret.into());
     match ret {
         0 => Ok(()),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
             .chain_err(|| "PR_CAP_AMBIENT_RAISE error")),
     }
 }
@@ -69,7 +64,7 @@ pub fn raise(cap: Capability) -> Result<()> {
 pub fn read() -> Result<super::CapsHashSet> {
     let mut res = super::CapsHashSet::new();
     for c in super::all() {
-        if try!(has_cap(c)) {
+        if has_cap(c)? {
             res.insert(c);
         }
     }
@@ -79,9 +74,9 @@ pub fn read() -> Result<super::CapsHashSet> {
 pub fn set(value: &super::CapsHashSet) -> Result<()> {
     for c in super::all() {
         if value.contains(&c) {
-            try!(raise(c));
+            raise(c)?;
         } else {
-            try!(drop(c));
+            drop(c)?;
         };
     }
     Ok(())
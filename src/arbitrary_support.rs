@@ -0,0 +1,72 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::{all, file::FileCaps, CapState, CapsHashSet};
+use super::{all_sorted, CapSet, Capability};
+
+// Build a set of capabilities from a raw mask, so that empty and full
+// sets are as likely as any other.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn caps_set(u: &mut Unstructured) -> Result<CapsHashSet> {
+    let mask = u64::arbitrary(u)?;
+    Ok(all()
+        .into_iter()
+        .filter(|c| (mask & c.bitmask()) != 0)
+        .collect())
+}
+
+impl<'a> Arbitrary<'a> for Capability {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&all_sorted()).cloned()
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(4))
+    }
+}
+
+impl<'a> Arbitrary<'a> for CapSet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[
+            CapSet::Ambient,
+            CapSet::Bounding,
+            CapSet::Effective,
+            CapSet::Inheritable,
+            CapSet::Permitted,
+        ])
+        .cloned()
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(4))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Arbitrary<'a> for CapState {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CapState {
+            ambient: caps_set(u)?,
+            bounding: caps_set(u)?,
+            effective: caps_set(u)?,
+            inheritable: caps_set(u)?,
+            permitted: caps_set(u)?,
+        })
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (40, Some(40))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Arbitrary<'a> for FileCaps {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(FileCaps {
+            effective: bool::arbitrary(u)?,
+            permitted: caps_set(u)?,
+            inheritable: caps_set(u)?,
+            rootid: Option::<u32>::arbitrary(u)?,
+        })
+    }
+}
@@ -0,0 +1,177 @@
+//! Audit the current thread against a hardening baseline.
+//!
+//! `report` compares capabilities, securebits, "no new privileges" and
+//! uids of the current thread against a built-in baseline, and produces a
+//! structured report (serializable with the `serde` feature) which fleet
+//! security agents can collect from every service.
+
+use libc;
+
+use super::{sorted, CapState, Capability, CapsHashSet, Risk};
+use errors::*;
+use securebits::{self, SecureBits};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+
+/// Severity of a finding.
+///
+/// Severities are ordered, from the least to the most severe one.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Severity {
+    /// Weakens defense in depth, without granting any privilege.
+    Low,
+    /// Eases escalation, e.g. by passing privileges to executed programs.
+    Medium,
+    /// Privileges bypassing significant security boundaries are held.
+    High,
+    /// Privileges escalating into full root access are held.
+    Critical,
+}
+
+/// Deviation from the hardening baseline.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Finding {
+    /// Identifier of the failed check (e.g. `"no-new-privs"`).
+    pub check: &'static str,
+    /// Severity of this finding.
+    pub severity: Severity,
+    /// Human-readable description.
+    pub message: String,
+    /// Capabilities involved, if any, sorted.
+    pub capabilities: Vec<Capability>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:?}] {}: {}", self.severity, self.check, self.message)
+    }
+}
+
+/// Audit report of a thread.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Report {
+    /// Real UID.
+    pub uid: u32,
+    /// Effective UID.
+    pub euid: u32,
+    /// Capability state.
+    pub state: CapState,
+    /// Securebits flags.
+    pub securebits: SecureBits,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+    /// Deviations from the baseline, most severe first.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Audit the given thread attributes against the baseline.
+    pub fn evaluate(
+        uid: u32,
+        euid: u32,
+        state: CapState,
+        securebits: SecureBits,
+        no_new_privs: bool,
+    ) -> Report {
+        let mut findings = vec![];
+        let mut finding = |check, severity, message: String, caps: Vec<Capability>| {
+            findings.push(Finding {
+                check,
+                severity,
+                message,
+                capabilities: caps,
+            })
+        };
+
+        let root_equivalent = filter(&state.permitted, |c| c.risk() == Risk::RootEquivalent);
+        if !root_equivalent.is_empty() {
+            let msg = "root-equivalent capabilities are permitted".to_string();
+            finding(
+                "root-equivalent-caps",
+                Severity::Critical,
+                msg,
+                root_equivalent,
+            );
+        }
+        let high = filter(&state.permitted, |c| c.risk() == Risk::High);
+        if !high.is_empty() {
+            let msg = "high-risk capabilities are permitted".to_string();
+            finding("high-risk-caps", Severity::High, msg, high);
+        }
+        if euid == 0 && !securebits.contains(SecureBits::NOROOT) {
+            let msg = "running as root without securebits NOROOT".to_string();
+            finding("root-uid", Severity::High, msg, vec![]);
+        }
+        if !state.ambient.is_empty() {
+            let msg = "ambient capabilities are inherited by executed programs".to_string();
+            finding(
+                "ambient-caps",
+                Severity::Medium,
+                msg,
+                sorted(&state.ambient),
+            );
+        }
+        if !no_new_privs {
+            let msg = "no_new_privs is not set".to_string();
+            finding("no-new-privs", Severity::Medium, msg, vec![]);
+        }
+        let bounding = filter(&state.bounding, |c| c.risk() == Risk::RootEquivalent);
+        if !bounding.is_empty() {
+            let msg = "bounding set allows regaining root-equivalent capabilities".to_string();
+            finding("bounding-set", Severity::Medium, msg, bounding);
+        }
+        if !state.inheritable.is_empty() {
+            let msg = "inheritable capabilities are set".to_string();
+            finding(
+                "inheritable-caps",
+                Severity::Low,
+                msg,
+                sorted(&state.inheritable),
+            );
+        }
+        if !securebits.contains(SecureBits::NOROOT_LOCKED) {
+            let msg = "securebits are not locked".to_string();
+            finding("securebits-unlocked", Severity::Low, msg, vec![]);
+        }
+        findings.sort_by_key(|f| ::std::cmp::Reverse(f.severity));
+
+        Report {
+            uid,
+            euid,
+            state,
+            securebits,
+            no_new_privs,
+            findings,
+        }
+    }
+
+    /// Return the highest severity among findings, if any.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    /// Check whether no finding is at least as severe as `threshold`.
+    pub fn passes(&self, threshold: Severity) -> bool {
+        self.findings.iter().all(|f| f.severity < threshold)
+    }
+}
+
+fn filter<F: Fn(&Capability) -> bool>(caps: &CapsHashSet, f: F) -> Vec<Capability> {
+    sorted(caps.iter().filter(|c| f(c)))
+}
+
+/// Audit the current thread against the hardening baseline.
+pub fn report() -> Result<Report> {
+    let state = CapState::current()?;
+    Ok(Report::evaluate(
+        unsafe { libc::getuid() },
+        unsafe { libc::geteuid() },
+        state,
+        securebits::get_securebits()?,
+        securebits::has_no_new_privs()?,
+    ))
+}
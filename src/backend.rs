@@ -0,0 +1,320 @@
+//! Cross-platform privilege backends.
+//!
+//! Each platform has its own flavour of privileges: capabilities on Linux,
+//! privilege sets on illumos, capability mode on FreeBSD. The
+//! `PrivilegeBackend` trait abstracts over them, so that
+//! privilege-minimization code can be written once:
+//!
+//! ```rust,no_run
+//! use caps::backend::{self, PrivilegeBackend};
+//!
+//! fn minimize(backend: &dyn PrivilegeBackend, keep: &[&str]) -> caps::errors::Result<()> {
+//!     println!("{} privileges: {:?}", backend.name(), backend.effective()?);
+//!     backend.restrict(keep)
+//! }
+//!
+//! let backend = backend::native().unwrap();
+//! minimize(&*backend, &["cap_net_bind_service"]).unwrap();
+//! ```
+//!
+//! Privileges are identified by their platform-specific names, such as
+//! `CAP_NET_RAW` (case-insensitive, with an optional `CAP_` prefix) on
+//! Linux or `net_rawaccess` on illumos.
+
+use errors::*;
+
+/// A platform privilege model.
+///
+/// Operations target the calling thread where privileges are per-thread
+/// (Linux), or the whole process otherwise.
+pub trait PrivilegeBackend {
+    /// Short name of this backend (e.g. `"linux"`).
+    fn name(&self) -> &'static str;
+
+    /// Return the names of privileges currently in effect.
+    fn effective(&self) -> Result<Vec<String>>;
+
+    /// Return the names of privileges which can be put in effect.
+    fn permitted(&self) -> Result<Vec<String>>;
+
+    /// Put permitted privilege `name` in effect.
+    fn raise(&self, name: &str) -> Result<()>;
+
+    /// Take privilege `name` out of effect, keeping it permitted.
+    fn lower(&self, name: &str) -> Result<()>;
+
+    /// Permanently drop privilege `name`.
+    fn drop(&self, name: &str) -> Result<()>;
+
+    /// Check whether privilege `name` is in effect.
+    fn has(&self, name: &str) -> Result<bool> {
+        Ok(self
+            .effective()?
+            .iter()
+            .any(|p| p.eq_ignore_ascii_case(name)))
+    }
+
+    /// Permanently drop all privileges except the ones in `keep`.
+    fn restrict(&self, keep: &[&str]) -> Result<()> {
+        for name in self.permitted()? {
+            if !keep.iter().any(|k| k.eq_ignore_ascii_case(&name)) {
+                self.drop(&name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the backend of the current platform.
+///
+/// On Linux, capabilities are read from procfs if `capget(2)` is denied
+/// (e.g. by a seccomp filter). On platforms without privileges support,
+/// this fails with `ErrorKind::Unsupported`.
+pub fn native() -> Result<Box<dyn PrivilegeBackend>> {
+    native_impl()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn native_impl() -> Result<Box<dyn PrivilegeBackend>> {
+    match ::base::read(0, ::CapSet::Effective) {
+        Err(ref e) if ::fallback::is_denied(e) => Ok(Box::new(Procfs)),
+        _ => Ok(Box::new(Linux)),
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+fn native_impl() -> Result<Box<dyn PrivilegeBackend>> {
+    Ok(Box::new(Capsicum))
+}
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+fn native_impl() -> Result<Box<dyn PrivilegeBackend>> {
+    Ok(Box::new(Illumos))
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "illumos",
+    target_os = "solaris"
+)))]
+fn native_impl() -> Result<Box<dyn PrivilegeBackend>> {
+    bail!(ErrorKind::Unsupported)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::linux::{Linux, Procfs};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod linux {
+    use super::PrivilegeBackend;
+    use errors::*;
+    use {fallback, sorted, CapSet, Capability, CapsHashSet};
+
+    /// Linux capabilities of the calling thread, via `capget(2)` and
+    /// `capset(2)`.
+    ///
+    /// Privileges are capabilities, and `restrict` also shrinks the
+    /// Bounding and Ambient sets (see `drop_all_except`).
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Linux;
+
+    /// Read-only Linux capabilities of the calling thread, via procfs.
+    ///
+    /// This keeps introspection working where `capget(2)` is denied,
+    /// while all changes fail with `ErrorKind::Unsupported`.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Procfs;
+
+    impl PrivilegeBackend for Linux {
+        fn name(&self) -> &'static str {
+            "linux"
+        }
+
+        fn effective(&self) -> Result<Vec<String>> {
+            ::read(None, CapSet::Effective).map(|s| names(&s))
+        }
+
+        fn permitted(&self) -> Result<Vec<String>> {
+            ::read(None, CapSet::Permitted).map(|s| names(&s))
+        }
+
+        fn raise(&self, name: &str) -> Result<()> {
+            ::raise(None, CapSet::Effective, parse(name)?)
+        }
+
+        fn lower(&self, name: &str) -> Result<()> {
+            ::drop(None, CapSet::Effective, parse(name)?)
+        }
+
+        fn drop(&self, name: &str) -> Result<()> {
+            let cap = parse(name)?;
+            ::drop(None, CapSet::Ambient, cap)?;
+            ::drop(None, CapSet::Effective, cap)?;
+            ::drop(None, CapSet::Permitted, cap)?;
+            ::drop(None, CapSet::Inheritable, cap)
+        }
+
+        fn has(&self, name: &str) -> Result<bool> {
+            ::has_cap(None, CapSet::Effective, parse(name)?)
+        }
+
+        fn restrict(&self, keep: &[&str]) -> Result<()> {
+            let keep = keep
+                .iter()
+                .map(|k| parse(k))
+                .collect::<Result<CapsHashSet>>()?;
+            ::drop_all_except(&keep)
+        }
+    }
+
+    impl PrivilegeBackend for Procfs {
+        fn name(&self) -> &'static str {
+            "procfs"
+        }
+
+        fn effective(&self) -> Result<Vec<String>> {
+            Ok(names(&fallback::proc_state(0)?.effective))
+        }
+
+        fn permitted(&self) -> Result<Vec<String>> {
+            Ok(names(&fallback::proc_state(0)?.permitted))
+        }
+
+        fn raise(&self, _name: &str) -> Result<()> {
+            bail!(ErrorKind::Unsupported)
+        }
+
+        fn lower(&self, _name: &str) -> Result<()> {
+            bail!(ErrorKind::Unsupported)
+        }
+
+        fn drop(&self, _name: &str) -> Result<()> {
+            bail!(ErrorKind::Unsupported)
+        }
+    }
+
+    fn parse(name: &str) -> Result<Capability> {
+        ::to_canonical(name).parse()
+    }
+
+    // Sorted names of capabilities in `set`.
+    fn names(set: &CapsHashSet) -> Vec<String> {
+        sorted(set).iter().map(|c| c.to_string()).collect()
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+pub use self::freebsd::{Capsicum, AMBIENT_AUTHORITY};
+
+#[cfg(target_os = "freebsd")]
+mod freebsd {
+    use super::PrivilegeBackend;
+    use capsicum;
+    use errors::*;
+
+    /// Name of the single privilege of `Capsicum`.
+    pub const AMBIENT_AUTHORITY: &str = "ambient_authority";
+
+    /// FreeBSD Capsicum capability mode of the process.
+    ///
+    /// Outside of capability mode, the process holds a single privilege,
+    /// `"ambient_authority"` (access to global namespaces), which is
+    /// dropped by entering capability mode. It cannot be lowered
+    /// temporarily.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Capsicum;
+
+    impl PrivilegeBackend for Capsicum {
+        fn name(&self) -> &'static str {
+            "capsicum"
+        }
+
+        fn effective(&self) -> Result<Vec<String>> {
+            match capsicum::in_capability_mode()? {
+                true => Ok(vec![]),
+                false => Ok(vec![AMBIENT_AUTHORITY.to_string()]),
+            }
+        }
+
+        fn permitted(&self) -> Result<Vec<String>> {
+            self.effective()
+        }
+
+        fn raise(&self, name: &str) -> Result<()> {
+            match self.has(name)? {
+                true => Ok(()),
+                false => bail!(ErrorKind::Unsupported),
+            }
+        }
+
+        fn lower(&self, _name: &str) -> Result<()> {
+            bail!(ErrorKind::Unsupported)
+        }
+
+        fn drop(&self, name: &str) -> Result<()> {
+            if !name.eq_ignore_ascii_case(AMBIENT_AUTHORITY) {
+                bail!("unknown Capsicum privilege '{}'", name);
+            }
+            capsicum::enter()
+        }
+    }
+}
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub use self::illumos::Illumos;
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+mod illumos {
+    use super::PrivilegeBackend;
+    use errors::*;
+    use privileges::{self, PrivSet, Privileges};
+
+    /// illumos and Solaris privilege sets of the process.
+    ///
+    /// `drop` and `restrict` remove privileges from the Permitted,
+    /// Inheritable and Limit sets.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Illumos;
+
+    impl PrivilegeBackend for Illumos {
+        fn name(&self) -> &'static str {
+            "illumos"
+        }
+
+        fn effective(&self) -> Result<Vec<String>> {
+            Ok(privileges::get(PrivSet::Effective)?.names())
+        }
+
+        fn permitted(&self) -> Result<Vec<String>> {
+            Ok(privileges::get(PrivSet::Permitted)?.names())
+        }
+
+        fn raise(&self, name: &str) -> Result<()> {
+            privileges::raise(PrivSet::Effective, &single(name)?)
+        }
+
+        fn lower(&self, name: &str) -> Result<()> {
+            privileges::drop(PrivSet::Effective, &single(name)?)
+        }
+
+        fn drop(&self, name: &str) -> Result<()> {
+            let privs = single(name)?;
+            for set in &[PrivSet::Inheritable, PrivSet::Permitted, PrivSet::Limit] {
+                privileges::drop(*set, &privs)?;
+            }
+            Ok(())
+        }
+
+        fn has(&self, name: &str) -> Result<bool> {
+            privileges::has_priv(PrivSet::Effective, name)
+        }
+    }
+
+    fn single(name: &str) -> Result<Privileges> {
+        let mut privs = Privileges::empty()?;
+        privs.add(name)?;
+        Ok(privs)
+    }
+}
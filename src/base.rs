@@ -1,36 +1,36 @@
-use errno;
-use libc;
-
 use super::{CapSet, Capability};
 use errors::*;
-use nr;
+use {sys, trace};
 
-#[allow(unknown_lints, unreadable_literal)]
-const CAPS_V3: u32 = 0x20080522;
+#[cfg(all(not(feature = "rustix"), target_os = "android"))]
+pub(crate) use caps_core::CapUserDataUnit;
+pub(crate) use caps_core::{CapUserData, CapUserHeader};
 
-fn capget(hdr: &mut CapUserHeader, data: &mut CapUserData) -> Result<()> {
-    let r = unsafe { libc::syscall(nr::CAPGET, hdr, data) };
-    match r {
-        0 => Ok(()),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "capget error")),
-    }
+// The capget/capset interface version is negotiated by `caps_core`.
+fn capget(tid: i32) -> Result<CapUserData> {
+    let res = caps_core::capget_data(&sys::Core, tid);
+    trace::syscall("capget", if res.is_ok() { 0 } else { -1 });
+    res.map_err(|e| Error::from_kind(ErrorKind::Sys(Errno(e.0))).chain_err(|| "capget error"))
 }
 
-fn capset(hdr: &mut CapUserHeader, data: &CapUserData) -> Result<()> {
-    let r = unsafe { libc::syscall(nr::CAPSET, hdr, data) };
-    match r {
-        0 => Ok(()),
-        _ => Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "capset error")),
+fn capset(tid: i32, data: &CapUserData) -> Result<()> {
+    let res = caps_core::capset_data(&sys::Core, tid, data);
+    trace::syscall("capset", if res.is_ok() { 0 } else { -1 });
+    let e = match res {
+        Ok(()) => return Ok(()),
+        Err(e) => Errno(e.0),
+    };
+    let err = Error::from_kind(ErrorKind::Sys(e));
+    // Missing capabilities are reported as EPERM, while LSM hooks
+    // (e.g. SELinux `process:setcap`) deny with EACCES.
+    if e.0 == sys::EACCES {
+        return Err(err.chain_err(|| ErrorKind::PolicyDenied("capset".to_string())));
     }
+    Err(err.chain_err(|| "capset error"))
 }
 
 pub fn has_cap(tid: i32, cset: CapSet, cap: Capability) -> Result<bool> {
-    let mut hdr = CapUserHeader {
-        version: CAPS_V3,
-        pid: tid,
-    };
-    let mut data: CapUserData = Default::default();
-    try!(capget(&mut hdr, &mut data));
+    let data = capget(tid)?;
     let caps: u64 = match cset {
         CapSet::Effective => (u64::from(data.effective_s1) << 32) + u64::from(data.effective_s0),
         CapSet::Inheritable => {
@@ -44,12 +44,7 @@ pub fn has_cap(tid: i32, cset: CapSet, cap: Capability) -> Result<bool> {
 }
 
 pub fn clear(tid: i32, cset: CapSet) -> Result<()> {
-    let mut hdr = CapUserHeader {
-        version: CAPS_V3,
-        pid: tid,
-    };
-    let mut data: CapUserData = Default::default();
-    try!(capget(&mut hdr, &mut data));
+    let mut data = capget(tid)?;
     match cset {
         CapSet::Effective => {
             data.effective_s0 = 0;
@@ -67,16 +62,11 @@ pub fn clear(tid: i32, cset: CapSet) -> Result<()> {
         }
         CapSet::Bounding | CapSet::Ambient => bail!("not a base set"),
     }
-    capset(&mut hdr, &data)
+    capset(tid, &data)
 }
 
 pub fn read(tid: i32, cset: CapSet) -> Result<super::CapsHashSet> {
-    let mut hdr = CapUserHeader {
-        version: CAPS_V3,
-        pid: tid,
-    };
-    let mut data: CapUserData = Default::default();
-    try!(capget(&mut hdr, &mut data));
+    let data = capget(tid)?;
     let caps: u64 = match cset {
         CapSet::Effective => (u64::from(data.effective_s1) << 32) + u64::from(data.effective_s0),
         CapSet::Inheritable => {
@@ -95,12 +85,7 @@ pub fn read(tid: i32, cset: CapSet) -> Result<super::CapsHashSet> {
 }
 
 pub fn set(tid: i32, cset: CapSet, value: super::CapsHashSet) -> Result<()> {
-    let mut hdr = CapUserHeader {
-        version: CAPS_V3,
-        pid: tid,
-    };
-    let mut data: CapUserData = Default::default();
-    try!(capget(&mut hdr, &mut data));
+    let mut data = capget(tid)?;
     {
         let (s1, s0) = match cset {
             CapSet::Effective => (&mut data.effective_s1, &mut data.effective_s0),
@@ -112,52 +97,32 @@ pub fn set(tid: i32, cset: CapSet, value: super::CapsHashSet) -> Result<()> {
         *s0 = 0;
         for c in value {
             match c.index() {
-                0...31 => {
+                0..=31 => {
                     *s0 |= c.bitmask() as u32;
                 }
-                32...63 => {
+                32..=63 => {
                     *s1 |= (c.bitmask() >> 32) as u32;
                 }
                 _ => bail!("overlarge cap index {}", c.index()),
             }
         }
     }
-    try!(capset(&mut hdr, &data));
+    capset(tid, &data)?;
     Ok(())
 }
 
 pub fn drop(tid: i32, cset: CapSet, cap: Capability) -> Result<()> {
-    let mut caps = try!(read(tid, cset));
+    let mut caps = read(tid, cset)?;
     if caps.remove(&cap) {
-        try!(set(tid, cset, caps));
+        set(tid, cset, caps)?;
     };
     Ok(())
 }
 
 pub fn raise(tid: i32, cset: CapSet, cap: Capability) -> Result<()> {
-    let mut caps = try!(read(tid, cset));
+    let mut caps = read(tid, cset)?;
     if caps.insert(cap) {
-        try!(set(tid, cset, caps));
+        set(tid, cset, caps)?;
     };
     Ok(())
 }
-
-#[derive(Debug)]
-#[repr(C)]
-struct CapUserHeader {
-    // Linux capabilities version (runtime kernel support)
-    version: u32,
-    // Process ID (thread)
-    pid: i32,
-}
-
-#[derive(Debug, Default, Clone)]
-#[repr(C)]
-struct CapUserData {
-    effective_s0: u32,
-    permitted_s0: u32,
-    inheritable_s0: u32,
-    effective_s1: u32,
-    permitted_s1: u32,
-    inheritable_s1: u32,
-}
@@ -0,0 +1,292 @@
+//! `caps` command-line tool, built on the `caps` library.
+//!
+//! This is a getpcaps/capsh replacement, available with the `cli` feature.
+
+extern crate caps;
+extern crate libc;
+extern crate serde_json;
+
+use caps::compare::Report;
+use caps::launch::Launcher;
+use caps::observe;
+use caps::tool::{self, Change, Outcome};
+use caps::{policy, CapSet, CapState, Capability, CapsHashSet};
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "usage: caps <command> [options]
+
+commands:
+  inspect [--json] <pid>    print the capability state of a process
+  diff <pid|snapshot> <pid|snapshot>
+                            compare capabilities of processes or JSON
+                            snapshots (from `inspect --json`)
+  watch [--json] [--interval <ms>] <pid>
+                            stream capability changes of a process
+  predict [--json] <program>
+                            predict the capabilities of a program after exec
+  run [--keep <caps>] [--ambient <caps>] [--user <user>] [--group <group>]
+      [--chdir <dir>] -- <program> [args...]
+                            run a program with limited capabilities";
+
+// Sets, in display order.
+const SETS: [CapSet; 5] = [
+    CapSet::Ambient,
+    CapSet::Bounding,
+    CapSet::Effective,
+    CapSet::Inheritable,
+    CapSet::Permitted,
+];
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let res = match args.first().map(|a| a.as_str()) {
+        Some("inspect") => inspect(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("predict") => predict(&args[1..]),
+        Some("run") => run(&args[1..]),
+        Some("watch") => watch(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(e) = res {
+        eprintln!("caps: {}", e);
+        process::exit(1);
+    }
+}
+
+// Split `args` into flags (starting with `--`) and positional arguments.
+fn split_args(args: &[String]) -> (Vec<&str>, Vec<&str>) {
+    args.iter()
+        .map(|a| a.as_str())
+        .partition(|a| a.starts_with("--"))
+}
+
+fn parse_pid(arg: Option<&&str>) -> Result<libc::pid_t, String> {
+    match arg.and_then(|a| a.parse().ok()) {
+        Some(pid) if pid > 0 => Ok(pid),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+// Format an error along with its causes.
+fn chain(e: caps::errors::Error) -> String {
+    e.iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+fn names(caps: &[Capability]) -> String {
+    if caps.len() == caps::all().len() {
+        return "(all)".to_string();
+    }
+    caps.iter()
+        .map(|c| c.to_string().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn inspect(args: &[String]) -> Result<(), String> {
+    let (flags, pos) = split_args(args);
+    let json = flags.contains(&"--json");
+    if flags.iter().any(|f| *f != "--json") || pos.len() != 1 {
+        return Err(USAGE.to_string());
+    }
+    let pid = parse_pid(pos.first())?;
+    let i = tool::inspect(pid).map_err(chain)?;
+    if json {
+        println!("{}", serde_json::to_string(&i).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    println!("Process {}", i.pid);
+    for cset in &SETS {
+        let label = format!("{:?}:", cset);
+        println!("  {:<14}{}", label, names(i.sets.get(*cset)));
+    }
+    match i.securebits {
+        Some(bits) => println!("  Securebits:   {:#x}", bits),
+        None => println!("  Securebits:   unknown"),
+    }
+    match i.no_new_privs {
+        Some(nnp) => println!("  NoNewPrivs:   {}", nnp),
+        None => println!("  NoNewPrivs:   unknown"),
+    }
+    Ok(())
+}
+
+fn diff(args: &[String]) -> Result<(), String> {
+    if args.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+    let (a, b) = (load_state(&args[0])?, load_state(&args[1])?);
+    let report = Report::between(&a, &b);
+    println!("{}", report);
+    if report.is_empty() {
+        return Ok(());
+    }
+    // Like diff(1), exit with 1 when inputs differ.
+    process::exit(1);
+}
+
+// Load a state from a pid, or from a JSON snapshot file.
+fn load_state(arg: &str) -> Result<CapState, String> {
+    if let Ok(pid) = arg.parse() {
+        return CapState::from_proc(pid, pid).map_err(chain);
+    }
+    let json = fs::read_to_string(arg).map_err(|e| format!("{}: {}", arg, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("{}: {}", arg, e))
+}
+
+fn watch(args: &[String]) -> Result<(), String> {
+    let (mut json, mut interval, mut pid) = (false, Duration::from_millis(100), None);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--interval" => {
+                let ms = iter.next().and_then(|v| v.parse().ok());
+                interval = Duration::from_millis(ms.ok_or_else(|| USAGE.to_string())?);
+            }
+            a if pid.is_none() => pid = Some(parse_pid(Some(&a))?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let pid = pid.ok_or_else(|| USAGE.to_string())?;
+    let changes = observe::watch(pid, interval).map_err(chain)?;
+    // Changes made from now on are reported.
+    eprintln!("caps: watching process {}", pid);
+    for diff in changes {
+        let change = Change::now(pid, &diff);
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string(&change).map_err(|e| e.to_string())?
+            );
+            continue;
+        }
+        let mut line = format!("{:.3} {:?}:", change.timestamp, change.set);
+        for c in &change.added {
+            line += &format!(" +{}", c.to_string().to_lowercase());
+        }
+        for c in &change.removed {
+            line += &format!(" -{}", c.to_string().to_lowercase());
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn predict(args: &[String]) -> Result<(), String> {
+    let (flags, pos) = split_args(args);
+    let json = flags.contains(&"--json");
+    if flags.iter().any(|f| *f != "--json") || pos.len() != 1 {
+        return Err(USAGE.to_string());
+    }
+    let outcome = tool::predict(pos[0]).map_err(chain)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string(&outcome).map_err(|e| e.to_string())?
+        );
+    } else {
+        print_outcome(&outcome);
+    }
+    if outcome.state.is_none() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_outcome(o: &Outcome) {
+    println!("Program {}", o.program.display());
+    match (o.file_effective, &o.file_permitted, &o.file_inheritable) {
+        (Some(eff), Some(prm), Some(inh)) => {
+            println!("  File effective:    {}", eff);
+            println!("  File permitted:    {}", names(prm));
+            println!("  File inheritable:  {}", names(inh));
+            if let Some(rootid) = o.file_rootid {
+                println!("  File rootid:       {}", rootid);
+            }
+        }
+        _ => println!("  File capabilities: none"),
+    }
+    if let Some(owner) = o.setuid_owner {
+        println!("  Set-user-ID:       {}", owner);
+    }
+    println!("  Caller uid/euid:   {}/{}", o.uid, o.euid);
+    println!("  Caller securebits: {:#x}", o.securebits);
+    println!("  Caller NoNewPrivs: {}", o.no_new_privs);
+    let state = match o.state {
+        Some(ref s) => s,
+        None => {
+            println!(
+                "Exec would fail: file-permitted capabilities can not be obtained: {}",
+                names(&o.missing)
+            );
+            return;
+        }
+    };
+    println!("After exec:");
+    for cset in &SETS {
+        let label = format!("{:?}:", cset);
+        println!("  {:<14}{}", label, names(state.get(*cset)));
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut launcher: Option<Launcher> = None;
+    let (mut keep, mut ambient) = (None, CapsHashSet::new());
+    let (mut user, mut group, mut chdir) = (None, None, None);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or_else(|| USAGE.to_string());
+        match arg.as_str() {
+            "--keep" => keep = Some(parse_caps(&value()?)?),
+            "--ambient" => ambient = parse_caps(&value()?)?,
+            "--user" => user = Some(value()?),
+            "--group" => group = Some(value()?),
+            "--chdir" => chdir = Some(value()?),
+            "--" => {
+                let program = iter.next().ok_or_else(|| USAGE.to_string())?;
+                launcher = Some(
+                    Launcher::new(tool::find_program(program).map_err(chain)?).args(iter.by_ref()),
+                );
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let mut launcher = launcher.ok_or_else(|| USAGE.to_string())?.ambient(&ambient);
+    if let Some(ref keep) = keep {
+        launcher = launcher.bounding(keep);
+    }
+    if let Some(ref user) = user {
+        let (uid, gid) = tool::lookup_user(user).map_err(chain)?;
+        launcher = launcher.user(uid).group(gid).groups(&[gid]);
+    }
+    if let Some(ref group) = group {
+        let gid = tool::lookup_group(group).map_err(chain)?;
+        launcher = launcher.group(gid).groups(&[gid]);
+    }
+    if let Some(ref dir) = chdir {
+        launcher = launcher.chdir(dir);
+    }
+    let pid = launcher.launch().map_err(chain)?;
+    let mut status = 0;
+    if unsafe { libc::waitpid(pid, &mut status, 0) } != pid {
+        return Err("failed to wait for child".to_string());
+    }
+    if libc::WIFEXITED(status) {
+        process::exit(libc::WEXITSTATUS(status));
+    }
+    process::exit(128 + libc::WTERMSIG(status));
+}
+
+fn parse_caps(list: &str) -> Result<CapsHashSet, String> {
+    policy::parse_list(list).map_err(chain)
+}
@@ -1,36 +1,37 @@
-use errno;
-use libc;
-
 use super::Capability;
 use errors::*;
 use nr;
+use std::os::raw::c_ulong;
+use {sys, trace};
 
 pub fn clear() -> Result<()> {
     for c in super::all() {
-        if try!(has_cap(c)) {
-            try!(drop(c));
+        if has_cap(c)? {
+            drop(c)?;
         }
     }
     Ok(())
 }
 
 pub fn drop(cap: Capability) -> Result<()> {
-    let ret = unsafe { libc::prctl(nr::PR_CAPBSET_DROP, libc::c_uint::from(cap.index()), 0, 0) };
+    let ret = sys::prctl(nr::PR_CAPBSET_DROP, c_ulong::from(cap.index()), 0);
+    trace::syscall("PR_CAPBSET_DROP", ret.into());
     match ret {
         0 => Ok(()),
         _ => Err(
-            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "PR_CAPBSET_DROP error")
+            Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "PR_CAPBSET_DROP error")
         ),
     }
 }
 
 pub fn has_cap(cap: Capability) -> Result<bool> {
-    let ret = unsafe { libc::prctl(nr::PR_CAPBSET_READ, libc::c_uint::from(cap.index()), 0, 0) };
+    let ret = sys::prctl(nr::PR_CAPBSET_READ, c_ulong::from(cap.index()), 0);
+    trace::syscall("PR_CAPBSET_READ", ret.into());
     match ret {
         0 => Ok(false),
         1 => Ok(true),
         _ => Err(
-            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "PR_CAPBSET_READ error")
+            Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "PR_CAPBSET_READ error")
         ),
     }
 }
@@ -38,7 +39,7 @@ pub fn has_cap(cap: Capability) -> Result<bool> {
 pub fn read() -> Result<super::CapsHashSet> {
     let mut res = super::CapsHashSet::new();
     for c in super::all() {
-        if try!(has_cap(c)) {
+        if has_cap(c)? {
             res.insert(c);
         }
     }
@@ -0,0 +1,66 @@
+//! Cached capability state for hot paths.
+//!
+//! Each `has_cap` call issues a syscall, which adds up when checking
+//! permissions on every request. `CachedCaps` snapshots the capability
+//! state of the current thread once, and answers queries from memory
+//! until explicitly refreshed:
+//!
+//! ```rust
+//! use caps::cache::CachedCaps;
+//! use caps::{CapSet, Capability};
+//!
+//! let mut cached = CachedCaps::for_current_thread().unwrap();
+//! let can_bind = cached.has_cap(CapSet::Effective, Capability::CAP_NET_BIND_SERVICE);
+//! // After changing capabilities:
+//! cached.refresh().unwrap();
+//! ```
+//!
+//! The snapshot is not updated by later changes to the thread state, so
+//! callers must `refresh` after changing capabilities. As capabilities are
+//! per-thread, a `CachedCaps` cannot be sent to other threads.
+
+use super::{CapSet, CapState, Capability};
+use errors::*;
+use lowlevel::Masks;
+use std::marker::PhantomData;
+
+/// Snapshot of the capability state of the current thread.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CachedCaps {
+    masks: Masks,
+    // Capabilities are per-thread, so the snapshot is bound to its thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl CachedCaps {
+    /// Snapshot the capability state of the current thread.
+    pub fn for_current_thread() -> Result<CachedCaps> {
+        Ok(CachedCaps {
+            masks: Masks::from(&CapState::current()?),
+            _thread: PhantomData,
+        })
+    }
+
+    /// Check whether `cap` was in set `cset` when last refreshed.
+    pub fn has_cap(&self, cset: CapSet, cap: Capability) -> bool {
+        let mask = match cset {
+            CapSet::Ambient => self.masks.ambient,
+            CapSet::Bounding => self.masks.bounding,
+            CapSet::Effective => self.masks.effective,
+            CapSet::Inheritable => self.masks.inheritable,
+            CapSet::Permitted => self.masks.permitted,
+        };
+        (mask & cap.bitmask()) != 0
+    }
+
+    /// Snapshot the capability state of the current thread again.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.masks = Masks::from(&CapState::current()?);
+        Ok(())
+    }
+
+    /// Return the cached state, as raw masks.
+    pub fn masks(&self) -> &Masks {
+        &self.masks
+    }
+}
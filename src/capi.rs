@@ -0,0 +1,173 @@
+//! C ABI over the core API.
+//!
+//! With the `capi` feature, this crate exports a small set of C functions
+//! (declared in `include/caps.h`), so that it can replace libcap beneath
+//! non-Rust components. A shared library is built by the `caps-capi`
+//! crate of this workspace:
+//!
+//! ```text
+//! cargo build --release -p caps-capi
+//! ```
+//!
+//! Sets are identified by the `CAPS_*` constants of this module,
+//! capabilities by their kernel index, and threads by their TID (0 for the
+//! calling thread). Capability sets are passed as raw 64-bit masks.
+//!
+//! Functions return -1 and set `errno` on failure: the syscall error if
+//! any, or `EINVAL` otherwise.
+
+use errno;
+use libc::{c_char, c_int, pid_t};
+
+use super::{all, from_bitmask, to_bitmask, CapSet, Capability, Target};
+use errors::*;
+use std::ffi::{CStr, CString};
+use std::sync::OnceLock;
+
+/// Ambient set.
+pub const CAPS_AMBIENT: c_int = 0;
+/// Bounding set.
+pub const CAPS_BOUNDING: c_int = 1;
+/// Effective set.
+pub const CAPS_EFFECTIVE: c_int = 2;
+/// Inheritable set.
+pub const CAPS_INHERITABLE: c_int = 3;
+/// Permitted set.
+pub const CAPS_PERMITTED: c_int = 4;
+
+/// Read set `set` of thread `tid` into `mask`.
+///
+/// # Safety
+///
+/// `mask` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn caps_get(tid: pid_t, set: c_int, mask: *mut u64) -> c_int {
+    if mask.is_null() {
+        return fail(None);
+    }
+    report(
+        cset(set)
+            .and_then(|cset| super::read(target(tid), cset))
+            .map(|caps| {
+                *mask = to_bitmask(&caps);
+            }),
+    )
+}
+
+/// Replace set `set` of thread `tid` with `mask`.
+///
+/// Unknown capabilities in `mask` are rejected.
+#[no_mangle]
+pub extern "C" fn caps_set(tid: pid_t, set: c_int, mask: u64) -> c_int {
+    let caps = from_bitmask(mask);
+    if to_bitmask(&caps) != mask {
+        return fail(None);
+    }
+    report(cset(set).and_then(|cset| super::set(target(tid), cset, caps)))
+}
+
+/// Check whether capability `cap` is in set `set` of thread `tid`.
+///
+/// Return 1 if present, 0 if absent.
+#[no_mangle]
+pub extern "C" fn caps_has(tid: pid_t, set: c_int, cap: c_int) -> c_int {
+    let res = cset(set).and_then(|cset| super::has_cap(target(tid), cset, capability(cap)?));
+    match res {
+        Ok(has) => has as c_int,
+        Err(e) => fail(Some(e)),
+    }
+}
+
+/// Raise capability `cap` in set `set` of thread `tid`.
+#[no_mangle]
+pub extern "C" fn caps_raise(tid: pid_t, set: c_int, cap: c_int) -> c_int {
+    report(cset(set).and_then(|cset| super::raise(target(tid), cset, capability(cap)?)))
+}
+
+/// Drop capability `cap` from set `set` of thread `tid`.
+#[no_mangle]
+pub extern "C" fn caps_drop(tid: pid_t, set: c_int, cap: c_int) -> c_int {
+    report(cset(set).and_then(|cset| super::drop(target(tid), cset, capability(cap)?)))
+}
+
+/// Return the index of capability `name` (e.g. `"net_raw"` or `"CAP_NET_RAW"`).
+///
+/// # Safety
+///
+/// `name` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn caps_from_name(name: *const c_char) -> c_int {
+    if name.is_null() {
+        return fail(None);
+    }
+    let res = CStr::from_ptr(name)
+        .to_str()
+        .chain_err(|| "invalid capability name")
+        .and_then(|n| super::to_canonical(n).parse::<Capability>());
+    match res {
+        Ok(cap) => cap.index().into(),
+        Err(e) => fail(Some(e)),
+    }
+}
+
+/// Return the lowercase name of capability `cap` (e.g. `"cap_net_raw"`).
+///
+/// The returned string is static. NULL is returned for unknown capabilities.
+#[no_mangle]
+pub extern "C" fn caps_to_name(cap: c_int) -> *const c_char {
+    static NAMES: OnceLock<Vec<(Capability, CString)>> = OnceLock::new();
+    let names = NAMES.get_or_init(|| {
+        all()
+            .into_iter()
+            .map(|c| (c, CString::new(c.to_string().to_lowercase()).unwrap()))
+            .collect()
+    });
+    match capability(cap) {
+        Ok(cap) => names
+            .iter()
+            .find(|&&(c, _)| c == cap)
+            .map_or(::std::ptr::null(), |(_, n)| n.as_ptr()),
+        Err(_) => {
+            fail(None);
+            ::std::ptr::null()
+        }
+    }
+}
+
+fn target(tid: pid_t) -> Target {
+    Target::from(Some(tid))
+}
+
+fn cset(set: c_int) -> Result<CapSet> {
+    match set {
+        CAPS_AMBIENT => Ok(CapSet::Ambient),
+        CAPS_BOUNDING => Ok(CapSet::Bounding),
+        CAPS_EFFECTIVE => Ok(CapSet::Effective),
+        CAPS_INHERITABLE => Ok(CapSet::Inheritable),
+        CAPS_PERMITTED => Ok(CapSet::Permitted),
+        _ => bail!("invalid capability set {}", set),
+    }
+}
+
+fn capability(cap: c_int) -> Result<Capability> {
+    match all().into_iter().find(|c| c_int::from(c.index()) == cap) {
+        Some(c) => Ok(c),
+        None => bail!("invalid capability {}", cap),
+    }
+}
+
+fn report(res: Result<()>) -> c_int {
+    match res {
+        Ok(()) => 0,
+        Err(e) => fail(Some(e)),
+    }
+}
+
+// Set errno from `err`, falling back to EINVAL, and return -1.
+fn fail(err: Option<Error>) -> c_int {
+    let errno = err
+        .and_then(|e| e.errno())
+        .unwrap_or(errno::Errno(::libc::EINVAL));
+    errno::set_errno(errno);
+    -1
+}
@@ -0,0 +1,239 @@
+//! FreeBSD Capsicum capability mode.
+//!
+//! Capsicum is FreeBSD's take on capability-based sandboxing: once a
+//! process enters capability mode with `enter`, it loses access to global
+//! namespaces (filesystem paths, PIDs, sockets addresses...) and can only
+//! operate on file descriptors it already holds. The operations allowed on
+//! each descriptor can be further narrowed with `Rights`:
+//!
+//! ```rust,no_run
+//! use caps::capsicum::{self, Right, Rights};
+//!
+//! let file = std::fs::File::open("/etc/passwd").unwrap();
+//! Rights::new()
+//!     .allow(Right::READ)
+//!     .allow(Right::FSTAT)
+//!     .limit(&file)
+//!     .unwrap();
+//! capsicum::enter().unwrap();
+//! ```
+//!
+//! This module is only available on FreeBSD.
+
+use errno;
+use libc;
+
+use errors::*;
+use std::fmt;
+use std::os::unix::io::AsRawFd;
+
+/// Enter capability mode, for the whole process.
+///
+/// This cannot be undone, and is inherited by children.
+pub fn enter() -> Result<()> {
+    match unsafe { libc::cap_enter() } {
+        0 => Ok(()),
+        _ => Err(sys("cap_enter error")),
+    }
+}
+
+/// Check whether the process is in capability mode.
+pub fn in_capability_mode() -> Result<bool> {
+    let mut mode: libc::c_uint = 0;
+    match unsafe { libc::cap_getmode(&mut mode) } {
+        0 => Ok(mode != 0),
+        _ => Err(sys("cap_getmode error")),
+    }
+}
+
+/// Right to perform an operation on a file descriptor.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Right(u64);
+
+impl Right {
+    /// `read(2)` and similar.
+    pub const READ: Right = Right(libc::CAP_READ);
+    /// `write(2)` and similar.
+    pub const WRITE: Right = Right(libc::CAP_WRITE);
+    /// `lseek(2)`.
+    pub const SEEK: Right = Right(libc::CAP_SEEK);
+    /// `pread(2)`.
+    pub const PREAD: Right = Right(libc::CAP_PREAD);
+    /// `pwrite(2)`.
+    pub const PWRITE: Right = Right(libc::CAP_PWRITE);
+    /// `mmap(2)` with `PROT_NONE`.
+    pub const MMAP: Right = Right(libc::CAP_MMAP);
+    /// `mmap(2)` with `PROT_READ`.
+    pub const MMAP_R: Right = Right(libc::CAP_MMAP_R);
+    /// `mmap(2)` with `PROT_WRITE`.
+    pub const MMAP_W: Right = Right(libc::CAP_MMAP_W);
+    /// `mmap(2)` with `PROT_EXEC`.
+    pub const MMAP_X: Right = Right(libc::CAP_MMAP_X);
+    /// `openat(2)` with `O_CREAT`.
+    pub const CREATE: Right = Right(libc::CAP_CREATE);
+    /// `fexecve(2)`.
+    pub const FEXECVE: Right = Right(libc::CAP_FEXECVE);
+    /// `fsync(2)`.
+    pub const FSYNC: Right = Right(libc::CAP_FSYNC);
+    /// `ftruncate(2)`.
+    pub const FTRUNCATE: Right = Right(libc::CAP_FTRUNCATE);
+    /// Lookups relative to a directory descriptor (`openat(2)` and similar).
+    pub const LOOKUP: Right = Right(libc::CAP_LOOKUP);
+    /// `fchdir(2)`.
+    pub const FCHDIR: Right = Right(libc::CAP_FCHDIR);
+    /// `fchmod(2)`.
+    pub const FCHMOD: Right = Right(libc::CAP_FCHMOD);
+    /// `fchown(2)`.
+    pub const FCHOWN: Right = Right(libc::CAP_FCHOWN);
+    /// `fcntl(2)`.
+    pub const FCNTL: Right = Right(libc::CAP_FCNTL);
+    /// `flock(2)`.
+    pub const FLOCK: Right = Right(libc::CAP_FLOCK);
+    /// `fstat(2)`.
+    pub const FSTAT: Right = Right(libc::CAP_FSTAT);
+    /// `fstatat(2)`.
+    pub const FSTATAT: Right = Right(libc::CAP_FSTATAT);
+    /// `fstatfs(2)`.
+    pub const FSTATFS: Right = Right(libc::CAP_FSTATFS);
+    /// `futimes(2)`.
+    pub const FUTIMES: Right = Right(libc::CAP_FUTIMES);
+    /// `mkdirat(2)`.
+    pub const MKDIRAT: Right = Right(libc::CAP_MKDIRAT);
+    /// `unlinkat(2)`.
+    pub const UNLINKAT: Right = Right(libc::CAP_UNLINKAT);
+    /// `accept(2)`.
+    pub const ACCEPT: Right = Right(libc::CAP_ACCEPT);
+    /// `bind(2)`.
+    pub const BIND: Right = Right(libc::CAP_BIND);
+    /// `connect(2)`.
+    pub const CONNECT: Right = Right(libc::CAP_CONNECT);
+    /// `listen(2)`.
+    pub const LISTEN: Right = Right(libc::CAP_LISTEN);
+    /// `getpeername(2)`.
+    pub const GETPEERNAME: Right = Right(libc::CAP_GETPEERNAME);
+    /// `getsockname(2)`.
+    pub const GETSOCKNAME: Right = Right(libc::CAP_GETSOCKNAME);
+    /// `getsockopt(2)`.
+    pub const GETSOCKOPT: Right = Right(libc::CAP_GETSOCKOPT);
+    /// `setsockopt(2)`.
+    pub const SETSOCKOPT: Right = Right(libc::CAP_SETSOCKOPT);
+    /// `shutdown(2)`.
+    pub const SHUTDOWN: Right = Right(libc::CAP_SHUTDOWN);
+    /// `recv(2)` and similar.
+    pub const RECV: Right = Right(libc::CAP_RECV);
+    /// `send(2)` and similar.
+    pub const SEND: Right = Right(libc::CAP_SEND);
+    /// All rights of a socket client.
+    pub const SOCK_CLIENT: Right = Right(libc::CAP_SOCK_CLIENT);
+    /// All rights of a socket server.
+    pub const SOCK_SERVER: Right = Right(libc::CAP_SOCK_SERVER);
+    /// `select(2)`, `poll(2)` and `kevent(2)` on the descriptor.
+    pub const EVENT: Right = Right(libc::CAP_EVENT);
+    /// `kevent(2)` on a kqueue descriptor.
+    pub const KQUEUE_EVENT: Right = Right(libc::CAP_KQUEUE_EVENT);
+    /// `ioctl(2)`.
+    pub const IOCTL: Right = Right(libc::CAP_IOCTL);
+    /// `pdgetpid(2)`.
+    pub const PDGETPID: Right = Right(libc::CAP_PDGETPID);
+    /// `pdwait4(2)`.
+    pub const PDWAIT: Right = Right(libc::CAP_PDWAIT);
+    /// `pdkill(2)`.
+    pub const PDKILL: Right = Right(libc::CAP_PDKILL);
+
+    /// Build a right from its raw `CAP_*` value of `<sys/capsicum.h>`.
+    pub fn from_raw(raw: u64) -> Right {
+        Right(raw)
+    }
+
+    /// Return the raw `CAP_*` value of this right.
+    pub fn as_raw(self) -> u64 {
+        self.0
+    }
+}
+
+/// Set of rights on a file descriptor, as `cap_rights_t`.
+#[derive(Clone, Copy)]
+pub struct Rights {
+    raw: libc::cap_rights_t,
+}
+
+impl Rights {
+    /// Return an empty set of rights.
+    pub fn new() -> Rights {
+        let mut raw: libc::cap_rights_t = unsafe { ::std::mem::zeroed() };
+        unsafe { libc::__cap_rights_init(libc::CAP_RIGHTS_VERSION, &mut raw, 0u64) };
+        Rights { raw }
+    }
+
+    /// Return the rights currently granted on `fd`.
+    pub fn of<F: AsRawFd>(fd: &F) -> Result<Rights> {
+        let mut rights = Rights::new();
+        let ret = unsafe {
+            libc::__cap_rights_get(libc::CAP_RIGHTS_VERSION, fd.as_raw_fd(), &mut rights.raw)
+        };
+        match ret {
+            0 => Ok(rights),
+            _ => Err(sys("cap_rights_get error")),
+        }
+    }
+
+    /// Allow `right`.
+    pub fn allow(mut self, right: Right) -> Rights {
+        unsafe { libc::__cap_rights_set(&mut self.raw, right.0, 0u64) };
+        self
+    }
+
+    /// Remove `right`.
+    pub fn remove(mut self, right: Right) -> Rights {
+        unsafe { libc::__cap_rights_clear(&mut self.raw, right.0, 0u64) };
+        self
+    }
+
+    /// Check whether `right` is allowed.
+    pub fn contains(&self, right: Right) -> bool {
+        unsafe { libc::__cap_rights_is_set(&self.raw, right.0, 0u64) }
+    }
+
+    /// Restrict `fd` to these rights.
+    ///
+    /// Rights can only be reduced: a descriptor cannot regain rights it
+    /// lost, and trying to do so fails with `ENOTCAPABLE`.
+    pub fn limit<F: AsRawFd>(&self, fd: &F) -> Result<()> {
+        match unsafe { libc::cap_rights_limit(fd.as_raw_fd(), &self.raw) } {
+            0 => Ok(()),
+            _ => Err(sys("cap_rights_limit error")),
+        }
+    }
+
+    /// Return the underlying `cap_rights_t`.
+    pub fn as_raw(&self) -> &libc::cap_rights_t {
+        &self.raw
+    }
+}
+
+impl Default for Rights {
+    fn default() -> Rights {
+        Rights::new()
+    }
+}
+
+impl PartialEq for Rights {
+    fn eq(&self, other: &Rights) -> bool {
+        unsafe {
+            libc::cap_rights_contains(&self.raw, &other.raw)
+                && libc::cap_rights_contains(&other.raw, &self.raw)
+        }
+    }
+}
+
+impl Eq for Rights {}
+
+impl fmt::Debug for Rights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Rights").finish_non_exhaustive()
+    }
+}
+
+fn sys(context: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| context)
+}
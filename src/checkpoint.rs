@@ -0,0 +1,108 @@
+//! Checkpoint and restore of per-thread capability state.
+//!
+//! Capabilities are a per-thread attribute, which checkpointing tools
+//! (such as CRIU) must preserve along with the rest of a process. A
+//! `Checkpoint` captures the capability state of every thread of a
+//! process (serializable with the `serde` feature), and can re-apply it
+//! thread-by-thread from within the restored process.
+//!
+//! States are kept as raw masks (see `lowlevel::Masks`), so that
+//! capabilities unknown to this crate survive a checkpoint too.
+//!
+//! Threads are matched by TID, which checkpointing tools restore as-is.
+
+use libc;
+
+use errors::*;
+use lowlevel::Masks;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use state::proc_status_masks;
+use std::fs;
+use {psx, this_thread};
+
+/// Capability state of a single thread.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThreadState {
+    /// Thread ID.
+    pub tid: libc::pid_t,
+    /// Capability state of the thread, as raw masks.
+    pub masks: Masks,
+}
+
+/// Capability state of all threads of a process.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint {
+    /// Process ID.
+    pub pid: libc::pid_t,
+    /// Threads, sorted by TID.
+    pub threads: Vec<ThreadState>,
+}
+
+impl Checkpoint {
+    /// Capture the capability state of all threads of process `pid`.
+    ///
+    /// Threads which exit while being captured are skipped.
+    pub fn capture(pid: libc::pid_t) -> Result<Checkpoint> {
+        let dir = format!("/proc/{}/task", pid);
+        let mut threads = vec![];
+        for entry in fs::read_dir(&dir).chain_err(|| format!("failed to list {}", dir))? {
+            let entry = entry.chain_err(|| format!("failed to list {}", dir))?;
+            let tid = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                Some(tid) => tid,
+                None => continue,
+            };
+            let path = entry.path().join("status");
+            match fs::read_to_string(&path) {
+                Ok(status) => threads.push(ThreadState {
+                    tid,
+                    masks: proc_status_masks(&status)?,
+                }),
+                Err(_) if !entry.path().exists() => continue,
+                Err(e) => {
+                    return Err(e).chain_err(|| format!("failed to read {}", path.display()))
+                }
+            }
+        }
+        threads.sort_by_key(|t| t.tid);
+        Ok(Checkpoint { pid, threads })
+    }
+
+    /// Capture the capability state of all threads of the current process.
+    pub fn capture_self() -> Result<Checkpoint> {
+        Checkpoint::capture(unsafe { libc::getpid() })
+    }
+
+    /// Return the captured state of thread `tid`, if any.
+    pub fn get(&self, tid: libc::pid_t) -> Option<&Masks> {
+        self.threads.iter().find(|t| t.tid == tid).map(|t| &t.masks)
+    }
+
+    /// Re-apply the captured state of the calling thread.
+    ///
+    /// This is meant to be called by each thread of the restored process.
+    pub fn restore_current_thread(&self) -> Result<()> {
+        let tid = this_thread::gettid();
+        match self.get(tid) {
+            Some(masks) => masks.apply().map_err(|e| {
+                Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "failed to apply capabilities")
+            }),
+            None => bail!("thread {} not found in checkpoint", tid),
+        }
+    }
+
+    /// Re-apply the captured state of every thread of the current process.
+    ///
+    /// Each thread is signalled in turn (see `psx::set_thread_masks`) to
+    /// apply its own state. All captured threads must exist; threads which were
+    /// not captured are left untouched.
+    pub fn restore(&self) -> Result<()> {
+        for t in &self.threads {
+            psx::set_thread_masks(t.tid, &t.masks)
+                .chain_err(|| format!("failed to restore thread {}", t.tid))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use clap::builder::{PossibleValue, TypedValueParser, ValueParserFactory};
+use clap::error::ErrorKind;
+use clap::{Arg, Command, Error, ValueEnum};
+use std::ffi::OsStr;
+
+use super::{all_sorted, policy, to_canonical, CapSet, Capability, CapsHashSet};
+
+impl ValueEnum for CapSet {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            CapSet::Ambient,
+            CapSet::Bounding,
+            CapSet::Effective,
+            CapSet::Inheritable,
+            CapSet::Permitted,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let name = match *self {
+            CapSet::Ambient => "ambient",
+            CapSet::Bounding => "bounding",
+            CapSet::Effective => "effective",
+            CapSet::Inheritable => "inheritable",
+            CapSet::Permitted => "permitted",
+        };
+        Some(PossibleValue::new(name))
+    }
+}
+
+impl ValueParserFactory for Capability {
+    type Parser = CapabilityParser;
+
+    fn value_parser() -> CapabilityParser {
+        CapabilityParser
+    }
+}
+
+/// `clap` parser for a capability name.
+///
+/// Names are accepted in any case and with an optional `CAP_` prefix
+/// (e.g. `net_raw`). This is the default parser for `Capability`
+/// arguments (`value_parser!(Capability)`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CapabilityParser;
+
+impl TypedValueParser for CapabilityParser {
+    type Value = Capability;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> ::std::result::Result<Capability, Error> {
+        let s = to_str(cmd, value)?;
+        to_canonical(s)
+            .parse()
+            .map_err(|e| invalid_value(cmd, arg, s, e))
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(possible_values()))
+    }
+}
+
+/// `clap` parser for a comma-separated list of capability names.
+///
+/// Names are parsed as by `policy::parse_list` (e.g. `chown,cap_net_raw`),
+/// and an empty list is accepted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CapsListParser;
+
+impl TypedValueParser for CapsListParser {
+    type Value = CapsHashSet;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> ::std::result::Result<CapsHashSet, Error> {
+        let s = to_str(cmd, value)?;
+        policy::parse_list(s).map_err(|e| invalid_value(cmd, arg, s, e))
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(possible_values()))
+    }
+}
+
+fn to_str<'a>(cmd: &Command, value: &'a OsStr) -> ::std::result::Result<&'a str, Error> {
+    value
+        .to_str()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))
+}
+
+fn invalid_value<E: ::std::fmt::Display>(
+    cmd: &Command,
+    arg: Option<&Arg>,
+    value: &str,
+    err: E,
+) -> Error {
+    let arg = arg.map_or_else(|| "...".to_string(), |a| a.to_string());
+    Error::raw(
+        ErrorKind::InvalidValue,
+        format!("invalid value '{}' for '{}': {}\n", value, arg, err),
+    )
+    .with_cmd(cmd)
+}
+
+// Lowercase capability names, sorted by index.
+fn possible_values() -> impl Iterator<Item = PossibleValue> {
+    all_sorted()
+        .into_iter()
+        .map(|c| PossibleValue::new(c.to_string().to_lowercase()))
+}
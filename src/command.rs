@@ -0,0 +1,72 @@
+//! Capabilities of child processes spawned via `std::process::Command`.
+//!
+//! Settings are applied in the child between `fork` and `exec`, through
+//! `pre_exec` hooks built on the `lowlevel` module. They are applied in
+//! the order they were configured.
+
+use super::{to_bitmask, CapsHashSet};
+use errno::Errno;
+use lowlevel;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+/// Extension trait configuring capabilities of a `Command` child.
+pub trait CommandCapsExt {
+    /// Raise capabilities in the ambient set of the child.
+    ///
+    /// Capabilities are added to the inheritable set first, and must be
+    /// in the permitted set of the parent.
+    fn ambient_caps(&mut self, set: &CapsHashSet) -> &mut Command;
+
+    /// Drop capabilities from the bounding set of the child.
+    fn drop_bounding(&mut self, set: &CapsHashSet) -> &mut Command;
+
+    /// Clear the inheritable set (and thus the ambient set) of the child.
+    fn clear_inheritable(&mut self) -> &mut Command;
+}
+
+impl CommandCapsExt for Command {
+    fn ambient_caps(&mut self, set: &CapsHashSet) -> &mut Command {
+        let mask = to_bitmask(set);
+        unsafe {
+            self.pre_exec(move || {
+                let (e, p, i) = lowlevel::capget().map_err(from_errno)?;
+                lowlevel::capset(e, p, i | mask).map_err(from_errno)?;
+                for index in 0..64 {
+                    if (mask & (1u64 << index)) != 0 {
+                        lowlevel::ambient_raise(index).map_err(from_errno)?;
+                    }
+                }
+                Ok(())
+            })
+        }
+    }
+
+    fn drop_bounding(&mut self, set: &CapsHashSet) -> &mut Command {
+        let mask = to_bitmask(set);
+        unsafe {
+            self.pre_exec(move || {
+                for index in 0..64 {
+                    if (mask & (1u64 << index)) != 0 && lowlevel::bounding_read(index) {
+                        lowlevel::bounding_drop(index).map_err(from_errno)?;
+                    }
+                }
+                Ok(())
+            })
+        }
+    }
+
+    fn clear_inheritable(&mut self) -> &mut Command {
+        unsafe {
+            self.pre_exec(|| {
+                let (e, p, _) = lowlevel::capget().map_err(from_errno)?;
+                lowlevel::capset(e, p, 0).map_err(from_errno)
+            })
+        }
+    }
+}
+
+fn from_errno(e: Errno) -> io::Error {
+    io::Error::from_raw_os_error(e.0)
+}
@@ -0,0 +1,131 @@
+//! Structured comparison of capability states.
+//!
+//! A `Report` lists what changed from one capability state to another:
+//! capabilities added to and removed from each set, and optionally
+//! securebits and `no_new_privs` differences when those were captured.
+//! It backs `CapState::diff` (and thus `verify_state`) as well as the
+//! `caps diff` tool, and makes for readable test assertions:
+//!
+//! ```rust
+//! use caps::compare::Report;
+//! use caps::CapState;
+//!
+//! let before = CapState::current().unwrap();
+//! let after = CapState {
+//!     effective: Default::default(),
+//!     ..before.clone()
+//! };
+//! let report = Report::between(&before, &after);
+//! assert_eq!(report.is_empty(), before.effective.is_empty());
+//! println!("{}", report);
+//! ```
+
+use super::{sorted, CapSet, CapState, Capability};
+use securebits::SecureBits;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Sets, in reporting order.
+const SETS: [CapSet; 5] = [
+    CapSet::Ambient,
+    CapSet::Bounding,
+    CapSet::Effective,
+    CapSet::Inheritable,
+    CapSet::Permitted,
+];
+
+/// Changes of a single capability set.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetChange {
+    /// Changed set.
+    pub set: CapSet,
+    /// Capabilities only in the second state, sorted.
+    pub added: Vec<Capability>,
+    /// Capabilities only in the first state, sorted.
+    pub removed: Vec<Capability>,
+}
+
+/// Differences from a first capability state to a second one.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    /// Changed sets, in Ambient, Bounding, Effective, Inheritable, Permitted
+    /// order.
+    pub sets: Vec<SetChange>,
+    /// First and second securebits, if captured and different.
+    pub securebits: Option<(SecureBits, SecureBits)>,
+    /// First and second `no_new_privs` flags, if captured and different.
+    pub no_new_privs: Option<(bool, bool)>,
+}
+
+impl Report {
+    /// Compare capability sets of states `a` and `b`.
+    pub fn between(a: &CapState, b: &CapState) -> Report {
+        let sets = SETS
+            .iter()
+            .filter(|cset| a.get(**cset) != b.get(**cset))
+            .map(|cset| {
+                let (a, b) = (a.get(*cset), b.get(*cset));
+                SetChange {
+                    set: *cset,
+                    added: sorted(b.difference(a)),
+                    removed: sorted(a.difference(b)),
+                }
+            })
+            .collect();
+        Report {
+            sets,
+            ..Default::default()
+        }
+    }
+
+    /// Also compare securebits `a` and `b`.
+    pub fn securebits(mut self, a: SecureBits, b: SecureBits) -> Report {
+        self.securebits = if a != b { Some((a, b)) } else { None };
+        self
+    }
+
+    /// Also compare `no_new_privs` flags `a` and `b`.
+    pub fn no_new_privs(mut self, a: bool, b: bool) -> Report {
+        self.no_new_privs = if a != b { Some((a, b)) } else { None };
+        self
+    }
+
+    /// Whether both states are the same.
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty() && self.securebits.is_none() && self.no_new_privs.is_none()
+    }
+
+    /// Return the changes of set `cset`, if any.
+    pub fn set(&self, cset: CapSet) -> Option<&SetChange> {
+        self.sets.iter().find(|s| s.set == cset)
+    }
+}
+
+impl fmt::Display for Report {
+    /// Format one line per difference, as `caps diff` does.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No differences");
+        }
+        let mut lines = vec![];
+        for s in &self.sets {
+            lines.push(format!("{:?}:", s.set));
+            for c in &s.removed {
+                lines.push(format!("  - {}", c.to_string().to_lowercase()));
+            }
+            for c in &s.added {
+                lines.push(format!("  + {}", c.to_string().to_lowercase()));
+            }
+        }
+        if let Some((a, b)) = self.securebits {
+            lines.push(format!("Securebits: {:#x} -> {:#x}", a.bits(), b.bits()));
+        }
+        if let Some((a, b)) = self.no_new_privs {
+            lines.push(format!("NoNewPrivs: {} -> {}", a, b));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
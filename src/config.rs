@@ -0,0 +1,91 @@
+//! Config-file-driven privilege policy.
+//!
+//! Operators can tune privileges of a daemon without recompiling it, via
+//! a TOML or YAML file such as:
+//!
+//! ```toml
+//! keep = ["net_bind_service"]
+//! deny = ["sys_admin"]
+//! no_new_privs = true
+//! lock_securebits = true
+//! ```
+//!
+//! `Policy` and `CapState` can also be loaded directly with any serde
+//! format, as they implement `Deserialize`.
+
+use std::fs;
+use std::path::Path;
+
+use super::CapsHashSet;
+use errors::*;
+use policy::Policy;
+use securebits::{self, SecureBits};
+use serde::{Deserialize, Serialize};
+
+/// Privilege configuration.
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Capabilities to keep; all others are removed. `None` keeps all
+    /// capabilities but denied ones.
+    pub keep: Option<CapsHashSet>,
+    /// Capabilities which must be removed.
+    pub deny: CapsHashSet,
+    /// Whether to set "no new privileges".
+    pub no_new_privs: bool,
+    /// Whether to set and lock `SecureBits::LOCKDOWN`.
+    pub lock_securebits: bool,
+}
+
+impl Config {
+    /// Parse a TOML configuration.
+    pub fn from_toml(s: &str) -> Result<Config> {
+        ::toml::from_str(s).chain_err(|| "invalid TOML configuration")
+    }
+
+    /// Parse a YAML configuration.
+    pub fn from_yaml(s: &str) -> Result<Config> {
+        ::serde_yaml::from_str(s).chain_err(|| "invalid YAML configuration")
+    }
+
+    /// Load a configuration file, as YAML for `.yaml`/`.yml` files and as
+    /// TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .chain_err(|| format!("failed to read configuration {}", path.display()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Config::from_yaml(&content),
+            _ => Config::from_toml(&content),
+        }
+    }
+
+    /// Policy enforced by this configuration.
+    pub fn policy(&self) -> Policy {
+        Policy {
+            allowed: self.keep.clone(),
+            denied: self.deny.clone(),
+            ..Policy::default()
+        }
+    }
+
+    /// Apply this configuration to the current thread.
+    ///
+    /// Securebits are locked first, then the policy is enforced and
+    /// finally "no new privileges" is set.
+    pub fn apply(&self) -> Result<()> {
+        if self.lock_securebits {
+            securebits::set_securebits(SecureBits::LOCKDOWN)?;
+        }
+        self.policy().enforce()?;
+        if self.no_new_privs {
+            securebits::set_no_new_privs()?;
+        }
+        Ok(())
+    }
+}
+
+/// Load configuration file `path` and apply it to the current thread.
+pub fn apply_from_config<P: AsRef<Path>>(path: P) -> Result<()> {
+    Config::load(path)?.apply()
+}
@@ -0,0 +1,207 @@
+//! Diagnose the capability state of the current thread.
+//!
+//! This module exposes helpers to inspect where a capability is
+//! currently held and how it would be affected by an `execve(2)`.
+//! With the `diagnostics` feature, a panic hook can also report the
+//! capability state of panicking threads.
+
+use super::{CapSet, CapState, Capability, Risk};
+use errors::*;
+use exec::{self, ExecParams};
+use libc;
+use lowlevel::Masks;
+use securebits::{self, SecureBits};
+use std::fmt;
+#[cfg(feature = "diagnostics")]
+use std::panic;
+use this_thread;
+
+/// Where a capability lives, and what happens to it on exec.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Explanation {
+    /// Explained capability.
+    pub capability: Capability,
+    /// Sets which currently contain the capability.
+    pub sets: Vec<CapSet>,
+    /// Real UID of the current thread.
+    pub uid: u32,
+    /// Effective UID of the current thread.
+    pub euid: u32,
+    /// Securebits flags of the current thread.
+    pub securebits: SecureBits,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+    /// Whether the capability stays permitted after executing a
+    /// binary without file capabilities nor set-user-ID bit.
+    pub survives_exec: bool,
+    /// Human-readable reason for the `survives_exec` outcome.
+    pub exec_reason: &'static str,
+}
+
+impl Explanation {
+    /// Check whether set `cset` contains the explained capability.
+    pub fn in_set(&self, cset: CapSet) -> bool {
+        self.sets.contains(&cset)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.capability)?;
+        for cset in &ALL_SETS {
+            let mark = if self.in_set(*cset) { "yes" } else { "no" };
+            writeln!(f, "  {:?}: {}", cset, mark)?;
+        }
+        writeln!(
+            f,
+            "  uid={} euid={} securebits={:#x} no_new_privs={}",
+            self.uid,
+            self.euid,
+            self.securebits.bits(),
+            self.no_new_privs
+        )?;
+        let outcome = if self.survives_exec { "kept" } else { "lost" };
+        write!(f, "  on exec: {} ({})", outcome, self.exec_reason)
+    }
+}
+
+const ALL_SETS: [CapSet; 5] = [
+    CapSet::Ambient,
+    CapSet::Bounding,
+    CapSet::Effective,
+    CapSet::Inheritable,
+    CapSet::Permitted,
+];
+
+/// Explain where capability `cap` lives for the current thread.
+///
+/// This reports which sets contain `cap` and whether it would still be
+/// permitted after executing an unprivileged binary (i.e. without file
+/// capabilities nor set-user-ID bit), under the current securebits
+/// configuration.
+pub fn explain(cap: Capability) -> Result<Explanation> {
+    let state = CapState::current()?;
+    let params = ExecParams::current()?;
+    let (uid, euid, securebits) = (params.uid, params.euid, params.securebits);
+
+    let sets = ALL_SETS
+        .iter()
+        .filter(|cset| state.get(**cset).contains(&cap))
+        .cloned()
+        .collect();
+    let survives_exec = exec::transform(&state, None, &params)?
+        .permitted
+        .contains(&cap);
+    let root = !securebits.contains(SecureBits::NOROOT) && (uid == 0 || euid == 0);
+    let exec_reason = if state.ambient.contains(&cap) {
+        "in ambient set"
+    } else if root && state.bounding.contains(&cap) {
+        "root without NOROOT securebit, in bounding set"
+    } else if root && state.inheritable.contains(&cap) {
+        "root without NOROOT securebit, in inheritable set"
+    } else if root {
+        "root without NOROOT securebit, but not in bounding nor inheritable set"
+    } else {
+        "not in ambient set"
+    };
+
+    Ok(Explanation {
+        capability: cap,
+        sets,
+        uid,
+        euid,
+        securebits,
+        no_new_privs: params.no_new_privs,
+        survives_exec,
+        exec_reason,
+    })
+}
+
+/// Check whether the current thread is effectively privileged.
+///
+/// This is the case if it runs as root (effective UID 0) while holding
+/// any permitted capability, or if it holds root-equivalent capabilities
+/// (see `Risk::RootEquivalent`) in its Permitted set, now or after
+/// executing an unprivileged binary (e.g. as root without the `NOROOT`
+/// securebit). Unlike checking `geteuid() == 0` alone, this accounts for
+/// root without capabilities in containers, and for non-root daemons
+/// holding dangerous capabilities.
+pub fn am_i_privileged() -> Result<bool> {
+    let state = CapState::current()?;
+    let params = ExecParams::current()?;
+    if params.euid == 0 && !state.permitted.is_empty() {
+        return Ok(true);
+    }
+    let after_exec = exec::transform(&state, None, &params)?;
+    Ok(state
+        .permitted
+        .iter()
+        .chain(after_exec.permitted.iter())
+        .any(|c| c.risk() == Risk::RootEquivalent))
+}
+
+/// Snapshot of the whole capability-related state of the current thread.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Snapshot {
+    /// Thread identifier.
+    pub tid: i32,
+    /// Real UID.
+    pub uid: u32,
+    /// Effective UID.
+    pub euid: u32,
+    /// Capability sets, including capabilities unknown to this crate.
+    pub masks: Masks,
+    /// Securebits flags.
+    pub securebits: SecureBits,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the current thread.
+    pub fn current() -> Result<Snapshot> {
+        Ok(Snapshot {
+            tid: this_thread::gettid(),
+            uid: unsafe { libc::getuid() },
+            euid: unsafe { libc::geteuid() },
+            masks: Masks::current()
+                .map_err(|e| Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "capget error"))?,
+            securebits: securebits::get_securebits()?,
+            no_new_privs: securebits::has_no_new_privs()?,
+        })
+    }
+}
+
+impl fmt::Display for Snapshot {
+    /// Format as `/proc/<pid>/status` does for capability sets.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "tid={} uid={} euid={}", self.tid, self.uid, self.euid)?;
+        writeln!(f, "CapInh: {:016x}", self.masks.inheritable)?;
+        writeln!(f, "CapPrm: {:016x}", self.masks.permitted)?;
+        writeln!(f, "CapEff: {:016x}", self.masks.effective)?;
+        writeln!(f, "CapBnd: {:016x}", self.masks.bounding)?;
+        writeln!(f, "CapAmb: {:016x}", self.masks.ambient)?;
+        write!(
+            f,
+            "securebits={:#x} no_new_privs={}",
+            self.securebits.bits(),
+            self.no_new_privs
+        )
+    }
+}
+
+/// Install a panic hook appending the capability state of the panicking
+/// thread to panic output.
+///
+/// The previously installed hook (e.g. the default one) runs first.
+#[cfg(feature = "diagnostics")]
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        match Snapshot::current() {
+            Ok(snapshot) => eprintln!("capability state at panic:\n{}", snapshot),
+            Err(e) => eprintln!("capability state at panic unavailable: {}", e),
+        }
+    }));
+}
@@ -0,0 +1,53 @@
+//! Docker capability flags.
+//!
+//! Containers started by Docker get the `sets::DOCKER_DEFAULT`
+//! capabilities, adjusted with `--cap-add` and `--cap-drop` flags. `flags`
+//! translates a desired capability set (e.g. as computed by an analysis)
+//! into the shortest list of such flags:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::docker;
+//!
+//! # fn main() {
+//! let flags = docker::flags(&caps![CAP_NET_BIND_SERVICE]);
+//! assert_eq!(flags, ["--cap-drop=ALL", "--cap-add=NET_BIND_SERVICE"]);
+//! # }
+//! ```
+
+use super::{sorted, Capability, CapsHashSet};
+use sets::DOCKER_DEFAULT;
+
+/// Return the shortest list of flags turning Docker's default capabilities
+/// into `desired`.
+///
+/// Flags either adjust the default set, or start from `--cap-drop=ALL`
+/// (preferred when equally short). `--cap-add=ALL` is never used, as it
+/// would also grant capabilities unknown to this crate, which cannot be
+/// dropped by name. Capability names are given without their `CAP_`
+/// prefix, sorted by index.
+pub fn flags(desired: &CapsHashSet) -> Vec<String> {
+    let default = DOCKER_DEFAULT.to_set();
+    let mut from_none = vec!["--cap-drop=ALL".to_string()];
+    from_none.extend(sorted(desired).iter().map(|c| flag("add", c)));
+    let mut relative: Vec<_> = sorted(default.difference(desired))
+        .iter()
+        .map(|c| flag("drop", c))
+        .collect();
+    relative.extend(
+        sorted(desired.difference(&default))
+            .iter()
+            .map(|c| flag("add", c)),
+    );
+    if relative.len() < from_none.len() {
+        relative
+    } else {
+        from_none
+    }
+}
+
+fn flag(action: &str, cap: &Capability) -> String {
+    let name = cap.to_string();
+    format!("--cap-{}={}", action, name.trim_start_matches("CAP_"))
+}
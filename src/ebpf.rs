@@ -0,0 +1,284 @@
+//! Tracing of capability checks with eBPF.
+//!
+//! With the `ebpf` feature, a `Tracer` attaches a small eBPF program to
+//! the `capability:cap_capable` tracepoint (from Linux 6.13), which fires
+//! on every capability check in the kernel. The program records each
+//! distinct (process, capability, result) triple in a map, which the
+//! tracer polls. This yields a deduplicated snapshot rather than a stream
+//! of events: how often and in which order checks happened is not
+//! recorded. Unlike `usage`, this requires no audit configuration, so
+//! a service can be watched under load to learn its real capability
+//! footprint:
+//!
+//! ```rust,no_run
+//! use caps::ebpf;
+//! use std::process::Command;
+//!
+//! let discovery = ebpf::record(Command::new("ping").args(&["-c1", "::1"])).unwrap();
+//! println!("exercised: {:?}", discovery.used);
+//! ```
+//!
+//! Loading eBPF programs requires `CAP_BPF` and `CAP_PERFMON` (or
+//! `CAP_SYS_ADMIN`). Checks are reported per thread group, and at most
+//! `MAX_ENTRIES` distinct triples are recorded.
+
+use errno;
+use libc;
+
+use super::{from_bitmask, CapsHashSet};
+use errors::*;
+use std::collections::HashSet;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::process::Command;
+use std::{mem, ptr};
+use usage::{Discovery, Usage};
+
+/// Maximum number of distinct (process, capability, result) triples recorded.
+pub const MAX_ENTRIES: u32 = 16384;
+
+// Commands of bpf(2).
+const BPF_MAP_CREATE: libc::c_int = 0;
+const BPF_MAP_GET_NEXT_KEY: libc::c_int = 4;
+const BPF_PROG_LOAD: libc::c_int = 5;
+const BPF_RAW_TRACEPOINT_OPEN: libc::c_int = 17;
+
+const BPF_MAP_TYPE_HASH: u32 = 1;
+const BPF_PROG_TYPE_RAW_TRACEPOINT: u32 = 17;
+const BPF_PSEUDO_MAP_FD: u8 = 1;
+
+// Helpers called by the program.
+const BPF_FUNC_MAP_UPDATE_ELEM: i32 = 2;
+const BPF_FUNC_GET_CURRENT_PID_TGID: i32 = 14;
+
+#[repr(C)]
+#[derive(Default)]
+struct MapCreateAttr {
+    map_type: u32,
+    key_size: u32,
+    value_size: u32,
+    max_entries: u32,
+    map_flags: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct MapElemAttr {
+    map_fd: u32,
+    _pad: u32,
+    key: u64,
+    next_key: u64,
+    flags: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct ProgLoadAttr {
+    prog_type: u32,
+    insn_cnt: u32,
+    insns: u64,
+    license: u64,
+    log_level: u32,
+    log_size: u32,
+    log_buf: u64,
+    kern_version: u32,
+    prog_flags: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct RawTracepointAttr {
+    name: u64,
+    prog_fd: u32,
+    _pad: u32,
+}
+
+/// One eBPF instruction (`struct bpf_insn`).
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Insn {
+    code: u8,
+    regs: u8,
+    off: i16,
+    imm: i32,
+}
+
+const fn insn(code: u8, dst: u8, src: u8, off: i16, imm: i32) -> Insn {
+    Insn {
+        code,
+        regs: (src << 4) | dst,
+        off,
+        imm,
+    }
+}
+
+// Program run on each capability check, with the tracepoint arguments
+// (cred, target_ns, capable_ns, cap, ret) as context. It records key
+// `tgid << 32 | cap << 1 | denied` in map `map`.
+fn program(map: RawFd) -> [Insn; 25] {
+    [
+        insn(0xbf, 6, 1, 0, 0), // r6 = r1
+        insn(0x85, 0, 0, 0, BPF_FUNC_GET_CURRENT_PID_TGID),
+        insn(0xbf, 7, 0, 0, 0),    // r7 = r0
+        insn(0x77, 7, 0, 0, 32),   // r7 >>= 32
+        insn(0x67, 7, 0, 0, 32),   // r7 <<= 32
+        insn(0x79, 1, 6, 24, 0),   // r1 = cap
+        insn(0xbc, 1, 1, 0, 0),    // w1 = w1
+        insn(0x67, 1, 0, 0, 1),    // r1 <<= 1
+        insn(0x4f, 7, 1, 0, 0),    // r7 |= r1
+        insn(0x79, 1, 6, 32, 0),   // r1 = ret
+        insn(0xbc, 1, 1, 0, 0),    // w1 = w1
+        insn(0x15, 1, 0, 1, 0),    // if r1 == 0 skip
+        insn(0x47, 7, 0, 0, 1),    // r7 |= 1
+        insn(0x7b, 10, 7, -8, 0),  // key = r7
+        insn(0x7a, 10, 0, -16, 1), // value = 1
+        insn(0x18, 1, BPF_PSEUDO_MAP_FD, 0, map),
+        insn(0, 0, 0, 0, 0),
+        insn(0xbf, 2, 10, 0, 0), // r2 = &key
+        insn(0x07, 2, 0, 0, -8),
+        insn(0xbf, 3, 10, 0, 0), // r3 = &value
+        insn(0x07, 3, 0, 0, -16),
+        insn(0xb7, 4, 0, 0, 0), // r4 = BPF_ANY
+        insn(0x85, 0, 0, 0, BPF_FUNC_MAP_UPDATE_ELEM),
+        insn(0xb7, 0, 0, 0, 0), // return 0
+        insn(0x95, 0, 0, 0, 0),
+    ]
+}
+
+// Issue bpf(2) command `cmd` with attributes `attr`, passed whole: the
+// kernel rejects non-zero bytes past the fields it knows, so attribute
+// structs spell out their padding, leaving none uninitialized.
+fn bpf<T>(cmd: libc::c_int, attr: &T, what: &str) -> Result<libc::c_long> {
+    let size = mem::size_of::<T>() as libc::c_uint;
+    match unsafe { libc::syscall(libc::SYS_bpf, cmd, attr as *const T, size) } {
+        -1 => Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| what.to_string())),
+        r => Ok(r),
+    }
+}
+
+fn bpf_fd<T>(cmd: libc::c_int, attr: &T, what: &str) -> Result<OwnedFd> {
+    bpf(cmd, attr, what).map(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// Tracer of capability checks.
+///
+/// The program stays attached until the tracer is dropped.
+#[derive(Debug)]
+pub struct Tracer {
+    map: OwnedFd,
+    _prog: OwnedFd,
+    _link: OwnedFd,
+    seen: HashSet<u64>,
+}
+
+impl Tracer {
+    /// Load the program and attach it to the `cap_capable` tracepoint.
+    pub fn attach() -> Result<Tracer> {
+        let map = bpf_fd(
+            BPF_MAP_CREATE,
+            &MapCreateAttr {
+                map_type: BPF_MAP_TYPE_HASH,
+                key_size: 8,
+                value_size: 8,
+                max_entries: MAX_ENTRIES,
+                ..Default::default()
+            },
+            "failed to create eBPF map",
+        )?;
+        let insns = program(map.as_raw_fd());
+        let license = b"GPL\0";
+        let prog = bpf_fd(
+            BPF_PROG_LOAD,
+            &ProgLoadAttr {
+                prog_type: BPF_PROG_TYPE_RAW_TRACEPOINT,
+                insn_cnt: insns.len() as u32,
+                insns: insns.as_ptr() as u64,
+                license: license.as_ptr() as u64,
+                ..Default::default()
+            },
+            "failed to load eBPF program",
+        )?;
+        let name = b"cap_capable\0";
+        let link = bpf_fd(
+            BPF_RAW_TRACEPOINT_OPEN,
+            &RawTracepointAttr {
+                name: name.as_ptr() as u64,
+                prog_fd: prog.as_raw_fd() as u32,
+                ..Default::default()
+            },
+            "failed to attach to cap_capable tracepoint",
+        )?;
+        Ok(Tracer {
+            map,
+            _prog: prog,
+            _link: link,
+            seen: HashSet::new(),
+        })
+    }
+
+    /// Return capability checks recorded since the previous poll.
+    ///
+    /// Each distinct (process, capability, result) triple is only reported
+    /// once over the lifetime of the tracer, however many times the check
+    /// happened, and triples are not reported in the order of checks.
+    pub fn poll(&mut self) -> Result<Vec<Usage>> {
+        let mut events = vec![];
+        let mut key: Option<u64> = None;
+        loop {
+            let mut next = 0u64;
+            let attr = MapElemAttr {
+                map_fd: self.map.as_raw_fd() as u32,
+                key: key.as_ref().map_or(ptr::null(), |k| k as *const u64) as u64,
+                next_key: &mut next as *mut u64 as u64,
+                ..Default::default()
+            };
+            match bpf(BPF_MAP_GET_NEXT_KEY, &attr, "failed to read eBPF map") {
+                Ok(_) => {}
+                Err(ref e) if e.errno() == Some(errno::Errno(libc::ENOENT)) => break,
+                Err(e) => return Err(e),
+            }
+            if self.seen.insert(next) {
+                events.extend(decode(next));
+            }
+            key = Some(next);
+        }
+        Ok(events)
+    }
+}
+
+// Decode map key `key`, ignoring capabilities unknown to this crate.
+fn decode(key: u64) -> Option<Usage> {
+    let index = (key >> 1) & 0x3f;
+    from_bitmask(1 << index)
+        .into_iter()
+        .next()
+        .map(|capability| Usage {
+            pid: (key >> 32) as i32,
+            comm: None,
+            capability,
+            denied: (key & 1) != 0,
+        })
+}
+
+/// Run `cmd` to completion while tracing, and report capabilities it
+/// exercised.
+///
+/// As with `usage::record`, processes spawned by the workload are not
+/// tracked: their checks are only part of `records`.
+pub fn record(cmd: &mut Command) -> Result<Discovery> {
+    let mut tracer = Tracer::attach()?;
+    let mut child = cmd.spawn().chain_err(|| "failed to spawn workload")?;
+    let pid = child.id();
+    let status = child.wait().chain_err(|| "failed to wait for workload")?;
+    let records = tracer.poll()?;
+    let used: CapsHashSet = records
+        .iter()
+        .filter(|u| u.pid as u32 == pid)
+        .map(|u| u.capability)
+        .collect();
+    Ok(Discovery {
+        status,
+        pid,
+        records,
+        used,
+    })
+}
@@ -0,0 +1,71 @@
+//! Temporarily lower the whole Effective set.
+//!
+//! This is the capability analogue of temporarily dropping the effective
+//! UID in set-user-ID programs: the Effective set of the current thread
+//! is stashed and cleared, while the Permitted set is left untouched so
+//! that it can be resumed later.
+
+use super::{base, CapSet, CapsHashSet};
+use errors::*;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+thread_local! {
+    static STASH: RefCell<Option<CapsHashSet>> = const { RefCell::new(None) };
+}
+
+/// Stash the Effective set of current thread, and clear it.
+///
+/// If the Effective set has already been lowered and not resumed yet,
+/// the originally stashed set is kept.
+pub fn lower_all() -> Result<()> {
+    let cur = base::read(0, CapSet::Effective)?;
+    base::clear(0, CapSet::Effective)?;
+    STASH.with(|s| {
+        let mut stash = s.borrow_mut();
+        if stash.is_none() {
+            *stash = Some(cur);
+        }
+    });
+    Ok(())
+}
+
+/// Resume the Effective set of current thread.
+///
+/// This restores the set stashed by `lower_all`, limited to capabilities
+/// which are still permitted. If nothing was stashed, the whole Permitted
+/// set is made effective.
+pub fn resume() -> Result<()> {
+    let permitted = base::read(0, CapSet::Permitted)?;
+    let target = match STASH.with(|s| s.borrow_mut().take()) {
+        Some(stash) => stash.intersection(&permitted).cloned().collect(),
+        None => permitted,
+    };
+    base::set(0, CapSet::Effective, target)
+}
+
+/// Check whether the Effective set of current thread is currently lowered.
+pub fn is_lowered() -> bool {
+    STASH.with(|s| s.borrow().is_some())
+}
+
+/// Guard keeping the Effective set lowered while in scope.
+#[derive(Debug)]
+#[must_use]
+pub struct Lowered {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for Lowered {
+    fn drop(&mut self) {
+        let _ = resume();
+    }
+}
+
+/// Lower the Effective set of current thread, until the returned guard is dropped.
+pub fn lowered() -> Result<Lowered> {
+    lower_all()?;
+    Ok(Lowered {
+        _not_send: PhantomData,
+    })
+}
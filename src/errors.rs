@@ -1,18 +1,87 @@
 //! Error handling.
 
-use errno;
+// `error_chain!` probes a cfg set by its own build script.
+#![allow(unexpected_cfgs)]
 
-error_chain!{
+#[cfg(feature = "libc")]
+pub use errno::Errno;
+
+/// Error number of a failed syscall, as `errno(3)`.
+///
+/// With the `libc` feature, this is `errno::Errno`.
+#[cfg(not(feature = "libc"))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct Errno(pub i32);
+
+#[cfg(not(feature = "libc"))]
+impl ::std::fmt::Display for Errno {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::io::Error::from_raw_os_error(self.0).fmt(f)
+    }
+}
+
+error_chain! {
     errors {
         /// Parsing error due to invalid capability name.
         InvalidCapName(name: String) {
             description("invalid capability name")
             display("invalid capability name: '{}'", name)
         }
+        /// Exec denied, as some file-permitted capabilities cannot be obtained.
+        ExecDenied(missing: Vec<::Capability>) {
+            description("exec denied, missing file-permitted capabilities")
+            display("exec denied, missing file-permitted capabilities: {:?}", missing)
+        }
+        /// Parsing error due to invalid file capabilities.
+        InvalidFileCaps(reason: String) {
+            description("invalid file capabilities")
+            display("invalid file capabilities: {}", reason)
+        }
+        /// Invalid thread or process identifier.
+        InvalidTarget(id: i32) {
+            description("invalid target")
+            display("invalid target thread or process: {}", id)
+        }
+        /// Invalid privilege transition, detected before performing it.
+        InvalidTransition(reason: String) {
+            description("invalid privilege transition")
+            display("invalid privilege transition: {}", reason)
+        }
+        /// Capability state differs from the expected one.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        StateMismatch(mismatch: ::StateMismatch) {
+            description("capability state mismatch")
+            display("{}", mismatch)
+        }
+        /// Operation denied by a security module policy (such as SELinux on
+        /// Android) rather than by missing capabilities.
+        PolicyDenied(op: String) {
+            description("operation denied by security policy")
+            display("{} denied by security policy", op)
+        }
+        /// Operation not supported on this platform.
+        Unsupported {
+            description("operation not supported on this platform")
+            display("capabilities are not supported on this platform")
+        }
         /// Syscall error, as `errno(3)`.
-        Sys(errno: errno::Errno) {
+        Sys(errno: Errno) {
             description("syscall failed")
             display("{}", errno)
         }
     }
 }
+
+impl Error {
+    /// Return the syscall error which caused this error, if any.
+    pub fn errno(&self) -> Option<Errno> {
+        if let ErrorKind::Sys(e) = *self.kind() {
+            return Some(e);
+        }
+        self.1
+            .next_error
+            .as_ref()
+            .and_then(|e| e.downcast_ref::<Error>())
+            .and_then(|e| e.errno())
+    }
+}
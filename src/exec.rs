@@ -0,0 +1,195 @@
+//! Compute capability transformations across `execve(2)`.
+//!
+//! This module implements the transformation algorithm described in
+//! `capabilities(7)`, to predict the capability state of a program
+//! after it is executed.
+
+use errno;
+use libc;
+
+use super::{sorted, CapState, Capability, CapsHashSet};
+use errors::*;
+use file::{self, FileCaps};
+use securebits::{self, SecureBits};
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Credentials and flags influencing an `execve(2)`.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct ExecParams {
+    /// Real UID of the calling thread.
+    pub uid: u32,
+    /// Effective UID of the calling thread.
+    pub euid: u32,
+    /// Owner of the executed file, if it has the set-user-ID bit.
+    pub setuid_owner: Option<u32>,
+    /// Whether the executed file changes the effective GID (set-group-ID bit).
+    pub setgid: bool,
+    /// Securebits flags of the calling thread.
+    pub securebits: SecureBits,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+}
+
+impl ExecParams {
+    /// Return parameters for the current thread, executing a file
+    /// without set-user-ID nor set-group-ID bits.
+    pub fn current() -> Result<ExecParams> {
+        Ok(ExecParams {
+            uid: unsafe { libc::getuid() },
+            euid: unsafe { libc::geteuid() },
+            setuid_owner: None,
+            setgid: false,
+            securebits: securebits::get_securebits()?,
+            no_new_privs: securebits::has_no_new_privs()?,
+        })
+    }
+}
+
+/// Compute the capability state resulting from an `execve(2)`.
+///
+/// Given the `state` of the calling thread, the capabilities of the
+/// executed file (`None` if it has none) and the exec `params`, this
+/// returns the capability state of the new program.
+/// If the exec would be rejected by the kernel, an `ExecDenied` error
+/// is returned instead.
+pub fn transform(state: &CapState, file: Option<&FileCaps>, params: &ExecParams) -> Result<CapState> {
+    execve(state, file, params).map(|(s, _)| s)
+}
+
+// Transform `state`, also returning the effective UID after exec.
+pub(crate) fn execve(
+    state: &CapState,
+    file: Option<&FileCaps>,
+    params: &ExecParams,
+) -> Result<(CapState, u32)> {
+    let mut euid = params.setuid_owner.unwrap_or(params.euid);
+    let is_setid = euid != params.uid || params.setgid;
+    let mut permitted = CapsHashSet::new();
+    let mut effective = false;
+
+    // P'(permitted) = (P(inheritable) & F(inheritable)) | (F(permitted) & P(bounding))
+    if let Some(f) = file {
+        permitted = f
+            .permitted
+            .intersection(&state.bounding)
+            .chain(f.inheritable.intersection(&state.inheritable))
+            .cloned()
+            .collect();
+        effective = f.effective;
+        if effective && !f.permitted.is_subset(&permitted) {
+            let missing = sorted(f.permitted.difference(&permitted));
+            bail!(ErrorKind::ExecDenied(missing));
+        }
+    }
+
+    // Root is special-cased, unless disabled by securebits.
+    if !params.securebits.contains(SecureBits::NOROOT) {
+        let setuid_root = euid == 0 && params.uid != 0;
+        if !(file.is_some() && setuid_root) {
+            if euid == 0 || params.uid == 0 {
+                permitted = state.bounding.union(&state.inheritable).cloned().collect();
+            }
+            if euid == 0 {
+                effective = true;
+            }
+        }
+    }
+
+    // With no_new_privs, set-user-ID bits and file capabilities cannot
+    // grant anything which was not already permitted.
+    let gained = !permitted.is_subset(&state.permitted);
+    if params.no_new_privs && (is_setid || gained) {
+        euid = params.uid;
+        permitted = permitted.intersection(&state.permitted).cloned().collect();
+    }
+
+    // Privileged files clear the ambient set.
+    let ambient = if file.is_some() || is_setid {
+        CapsHashSet::new()
+    } else {
+        state.ambient.clone()
+    };
+    permitted.extend(ambient.iter().cloned());
+    let effective = if effective {
+        permitted.clone()
+    } else {
+        ambient.clone()
+    };
+
+    let res = CapState {
+        ambient,
+        bounding: state.bounding.clone(),
+        effective,
+        inheritable: state.inheritable.clone(),
+        permitted,
+    };
+    Ok((res, euid))
+}
+
+/// Predicted outcome of executing a file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Prediction {
+    /// Capabilities attached to the file, if any.
+    pub file_caps: Option<FileCaps>,
+    /// Parameters of the exec, as derived from the file and current thread.
+    pub params: ExecParams,
+    /// Resulting capability state, or `None` if the exec would be denied.
+    pub state: Option<CapState>,
+    /// File-permitted capabilities which cannot be obtained.
+    pub missing: Vec<Capability>,
+}
+
+impl Prediction {
+    /// Check whether the exec is expected to succeed.
+    pub fn succeeds(&self) -> bool {
+        self.state.is_some()
+    }
+}
+
+/// Predict the outcome of executing file at `path` from the current thread.
+///
+/// This combines the file capabilities and set-user-ID/set-group-ID bits
+/// of `path` with the current thread state. In particular, it detects
+/// files whose effective bit is set but whose file-permitted capabilities
+/// cannot be obtained, which the kernel refuses to execute.
+pub fn predict_exec<P: AsRef<Path>>(path: P) -> Result<Prediction> {
+    let path = path.as_ref();
+    let cpath = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(p) => p,
+        Err(_) => bail!("invalid path {:?}", path),
+    };
+    let mut st: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::stat(cpath.as_ptr(), &mut st) } != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "stat error"));
+    }
+    let mut vfs: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(cpath.as_ptr(), &mut vfs) } != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "statvfs error"));
+    }
+
+    // Privileges attached to files on `nosuid` mounts are ignored.
+    let nosuid = (vfs.f_flag & libc::ST_NOSUID) != 0;
+    let mut params = ExecParams::current()?;
+    let file_caps = if nosuid { None } else { file::read(path)? };
+    if !nosuid && (st.st_mode as libc::mode_t & libc::S_ISUID) != 0 {
+        params.setuid_owner = Some(st.st_uid);
+    }
+    if !nosuid && (st.st_mode as libc::mode_t & libc::S_ISGID) != 0 {
+        params.setgid = st.st_gid != unsafe { libc::getgid() };
+    }
+
+    let state = CapState::current()?;
+    let (state, missing) = match transform(&state, file_caps.as_ref(), &params) {
+        Ok(s) => (Some(s), vec![]),
+        Err(Error(ErrorKind::ExecDenied(missing), _)) => (None, missing),
+        Err(e) => return Err(e),
+    };
+    Ok(Prediction {
+        file_caps,
+        params,
+        state,
+        missing,
+    })
+}
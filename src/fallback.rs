@@ -0,0 +1,40 @@
+// Read-only fallback via procfs.
+//
+// In some confined environments, seccomp filters deny capget(2) and
+// prctl(2) while /proc remains readable. Reads denied this way are served
+// from the thread status file instead.
+
+use super::{CapSet, CapState, CapsHashSet};
+use errors::*;
+use std::fs;
+use sys;
+
+// Whether `err` looks like a syscall denied by a seccomp filter.
+pub(crate) fn is_denied(err: &Error) -> bool {
+    match err.errno() {
+        Some(Errno(e)) => e == sys::EPERM || e == sys::EACCES || e == sys::ENOSYS,
+        None => false,
+    }
+}
+
+// Return `res`, or `f` applied to set `cset` of thread `tid` (0 for the
+// current one) from procfs if the syscall behind `res` was denied.
+pub(crate) fn or_proc<R, F>(res: Result<R>, tid: i32, cset: CapSet, f: F) -> Result<R>
+where
+    F: FnOnce(&CapsHashSet) -> R,
+{
+    match res {
+        Err(ref e) if is_denied(e) => Ok(f(proc_state(tid)?.get(cset))),
+        res => res,
+    }
+}
+
+// Read the state of thread `tid` (0 for the current one) from procfs.
+pub(crate) fn proc_state(tid: i32) -> Result<CapState> {
+    let path = match tid {
+        0 => "/proc/thread-self/status".to_string(),
+        t => format!("/proc/{}/status", t),
+    };
+    let status = fs::read_to_string(&path).chain_err(|| format!("failed to read {}", path))?;
+    CapState::from_proc_status(&status)
+}
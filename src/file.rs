@@ -0,0 +1,152 @@
+//! File capabilities.
+//!
+//! This module exposes types to represent the capabilities attached to
+//! an executable file (via the `security.capability` extended attribute).
+
+use errno;
+use libc;
+
+use super::{from_bitmask, to_bitmask, CapsHashSet};
+use errors::*;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use userns::UidMap;
+
+const XATTR_NAME_CAPS: &[u8] = b"security.capability\0";
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+const XATTR_CAPS_SZ_1: usize = 12;
+const XATTR_CAPS_SZ_2: usize = 20;
+const XATTR_CAPS_SZ_3: usize = 24;
+
+/// Capabilities attached to an executable file.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct FileCaps {
+    /// Whether the "effective" bit is set.
+    pub effective: bool,
+    /// File-permitted ("forced") capabilities.
+    pub permitted: CapsHashSet,
+    /// File-inheritable ("allowed") capabilities.
+    pub inheritable: CapsHashSet,
+    /// Root user ID of the namespace the capabilities apply to (v3 only).
+    pub rootid: Option<u32>,
+}
+
+impl FileCaps {
+    /// Parse file capabilities from the raw `security.capability` value.
+    pub fn from_xattr(data: &[u8]) -> Result<FileCaps> {
+        if data.len() < 4 {
+            bail!(ErrorKind::InvalidFileCaps("truncated header".to_string()));
+        }
+        let magic = read_u32(data, 0);
+        let (words, rootid) = match (magic & VFS_CAP_REVISION_MASK, data.len()) {
+            (VFS_CAP_REVISION_1, XATTR_CAPS_SZ_1) => (1, None),
+            (VFS_CAP_REVISION_2, XATTR_CAPS_SZ_2) => (2, None),
+            (VFS_CAP_REVISION_3, XATTR_CAPS_SZ_3) => (2, Some(read_u32(data, 20))),
+            (rev, len) => bail!(ErrorKind::InvalidFileCaps(format!(
+                "unknown revision {:#x} with size {}",
+                rev, len
+            ))),
+        };
+        let (mut permitted, mut inheritable) = (0u64, 0u64);
+        for i in 0..words {
+            permitted |= u64::from(read_u32(data, 4 + i * 8)) << (32 * i);
+            inheritable |= u64::from(read_u32(data, 8 + i * 8)) << (32 * i);
+        }
+        Ok(FileCaps {
+            effective: (magic & VFS_CAP_FLAGS_EFFECTIVE) != 0,
+            permitted: from_bitmask(permitted),
+            inheritable: from_bitmask(inheritable),
+            rootid,
+        })
+    }
+
+    /// Serialize file capabilities into a raw `security.capability` value.
+    ///
+    /// This produces a revision 3 value if `rootid` is set, or a
+    /// revision 2 value otherwise.
+    pub fn to_xattr(&self) -> Vec<u8> {
+        let mut magic = match self.rootid {
+            Some(_) => VFS_CAP_REVISION_3,
+            None => VFS_CAP_REVISION_2,
+        };
+        if self.effective {
+            magic |= VFS_CAP_FLAGS_EFFECTIVE;
+        }
+        let permitted = to_bitmask(&self.permitted);
+        let inheritable = to_bitmask(&self.inheritable);
+        let mut words = vec![
+            magic,
+            permitted as u32,
+            inheritable as u32,
+            (permitted >> 32) as u32,
+            (inheritable >> 32) as u32,
+        ];
+        if let Some(rootid) = self.rootid {
+            words.push(rootid);
+        }
+        let mut data = Vec::with_capacity(words.len() * 4);
+        for w in words {
+            data.extend_from_slice(&w.to_le_bytes());
+        }
+        data
+    }
+
+    /// Make these capabilities apply to root of the namespace mapped by `map`.
+    ///
+    /// `rootid` is set to the caller's view of UID 0 of the namespace, as
+    /// expected by the kernel when writing namespaced (revision 3) file
+    /// capabilities, e.g. into container images built rootless.
+    pub fn set_namespace_root(&mut self, map: &UidMap) -> Result<()> {
+        match map.to_outside(0) {
+            Some(rootid) => self.rootid = Some(rootid),
+            None => bail!("root is not mapped in user namespace"),
+        }
+        Ok(())
+    }
+
+    /// Translate `rootid` into the namespace mapped by `map`.
+    ///
+    /// This returns `None` if these capabilities are not namespaced, or if
+    /// their root user is not mapped in the namespace.
+    pub fn namespace_rootid(&self, map: &UidMap) -> Option<u32> {
+        self.rootid.and_then(|r| map.to_inside(r))
+    }
+}
+
+/// Read capabilities of file at `path`.
+///
+/// This returns `None` if the file has no capabilities attached.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Option<FileCaps>> {
+    let cpath = match CString::new(path.as_ref().as_os_str().as_bytes()) {
+        Ok(p) => p,
+        Err(_) => bail!("invalid path {:?}", path.as_ref()),
+    };
+    let mut buf = [0u8; XATTR_CAPS_SZ_3];
+    let ret = unsafe {
+        libc::getxattr(
+            cpath.as_ptr(),
+            XATTR_NAME_CAPS.as_ptr() as *const libc::c_char,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    if ret < 0 {
+        let err = errno::errno();
+        if err.0 == libc::ENODATA {
+            return Ok(None);
+        }
+        return Err(Error::from_kind(ErrorKind::Sys(err)).chain_err(|| "getxattr error"));
+    }
+    FileCaps::from_xattr(&buf[..ret as usize]).map(Some)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut w = [0u8; 4];
+    w.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_le_bytes(w)
+}
@@ -0,0 +1,84 @@
+//! Capability state gauges, exported via the `metrics` facade.
+//!
+//! Long-running services can periodically call `record` to publish how
+//! privileged the current thread is, and alert whenever it runs with more
+//! privileges than expected. The following gauges are set:
+//!
+//!  * `caps_capabilities`, labeled by `set`: number of capabilities in
+//!    each set;
+//!  * `caps_root_equivalent`: number of root-equivalent capabilities
+//!    (see `Risk::RootEquivalent`) in the Permitted set;
+//!  * `caps_no_new_privs`: 1 if the "no new privileges" flag is set,
+//!    0 otherwise.
+
+use metrics;
+
+use super::{CapState, Risk};
+use errors::*;
+use securebits;
+
+/// Gauge values for a thread.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Gauges {
+    /// Number of Ambient capabilities.
+    pub ambient: usize,
+    /// Number of Bounding capabilities.
+    pub bounding: usize,
+    /// Number of Effective capabilities.
+    pub effective: usize,
+    /// Number of Inheritable capabilities.
+    pub inheritable: usize,
+    /// Number of Permitted capabilities.
+    pub permitted: usize,
+    /// Number of root-equivalent Permitted capabilities.
+    pub root_equivalent: usize,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+}
+
+impl Gauges {
+    /// Compute gauge values from a capability state.
+    pub fn from_state(state: &CapState, no_new_privs: bool) -> Gauges {
+        Gauges {
+            ambient: state.ambient.len(),
+            bounding: state.bounding.len(),
+            effective: state.effective.len(),
+            inheritable: state.inheritable.len(),
+            permitted: state.permitted.len(),
+            root_equivalent: state
+                .permitted
+                .iter()
+                .filter(|c| c.risk() == Risk::RootEquivalent)
+                .count(),
+            no_new_privs,
+        }
+    }
+
+    /// Compute gauge values for the current thread.
+    pub fn current() -> Result<Gauges> {
+        Ok(Gauges::from_state(
+            &CapState::current()?,
+            securebits::has_no_new_privs()?,
+        ))
+    }
+
+    /// Publish these values to the installed `metrics` recorder.
+    pub fn export(&self) {
+        for &(label, count) in &[
+            ("ambient", self.ambient),
+            ("bounding", self.bounding),
+            ("effective", self.effective),
+            ("inheritable", self.inheritable),
+            ("permitted", self.permitted),
+        ] {
+            metrics::gauge!("caps_capabilities", "set" => label).set(count as f64);
+        }
+        metrics::gauge!("caps_root_equivalent").set(self.root_equivalent as f64);
+        metrics::gauge!("caps_no_new_privs").set(if self.no_new_privs { 1.0 } else { 0.0 });
+    }
+}
+
+/// Publish gauges for the current thread.
+pub fn record() -> Result<()> {
+    Gauges::current().map(|g| g.export())
+}
@@ -0,0 +1,229 @@
+//! Scoped capability changes.
+//!
+//! This module exposes guards which temporarily change capabilities of
+//! the current thread, restoring the previous state when they go out of
+//! scope (including on unwinding).
+//!
+//! As capabilities are per-thread, guards cannot be sent to other threads.
+
+use super::{ambient, base, bounding, CapSet, CapState, Capability, CapsHashSet};
+use errors::*;
+use std::marker::PhantomData;
+
+/// Guard temporarily raising a capability in the Effective set.
+///
+/// ```rust,no_run
+/// use caps::guard::CapGuard;
+/// use caps::Capability;
+///
+/// let _guard = CapGuard::raise(Capability::CAP_NET_BIND_SERVICE).unwrap();
+/// // `CAP_NET_BIND_SERVICE` is effective until `_guard` is dropped.
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct CapGuard {
+    cap: Capability,
+    was_effective: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl CapGuard {
+    /// Raise capability `cap` in the Effective set of current thread.
+    ///
+    /// The capability must already be in the Permitted set. It is dropped
+    /// again when the guard goes out of scope, unless it was already
+    /// effective before.
+    pub fn raise(cap: Capability) -> Result<CapGuard> {
+        let was_effective = base::has_cap(0, CapSet::Effective, cap)?;
+        if !was_effective {
+            base::raise(0, CapSet::Effective, cap)?;
+        }
+        Ok(CapGuard {
+            cap,
+            was_effective,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Return the capability raised by this guard.
+    pub fn capability(&self) -> Capability {
+        self.cap
+    }
+}
+
+impl Drop for CapGuard {
+    fn drop(&mut self) {
+        if !self.was_effective {
+            let _ = base::drop(0, CapSet::Effective, self.cap);
+        }
+    }
+}
+
+/// Run closure `f` with capability `cap` raised in the Effective set.
+///
+/// The previous Effective state of `cap` is restored once `f` returns,
+/// even if it panics. The result of `f` is returned on success.
+///
+/// ```rust,no_run
+/// use caps::Capability;
+///
+/// let listener = caps::guard::with_cap(Capability::CAP_NET_BIND_SERVICE, || {
+///     std::net::TcpListener::bind("0.0.0.0:80")
+/// });
+/// ```
+pub fn with_cap<F, T>(cap: Capability, f: F) -> Result<T>
+where
+    F: FnOnce() -> T,
+{
+    let _guard = CapGuard::raise(cap)?;
+    Ok(f())
+}
+
+/// Guard temporarily applying a reduced capability state.
+///
+/// Only the Effective, Inheritable and Ambient sets can be temporarily
+/// changed, as capabilities dropped from Permitted and Bounding sets can
+/// never be regained. The previous state is restored when the guard goes
+/// out of scope.
+///
+/// Note that this does not sandbox untrusted code: it could raise
+/// capabilities again, as long as they are still permitted.
+#[derive(Debug)]
+#[must_use]
+pub struct ScopedState {
+    snapshot: CapState,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ScopedState {
+    /// Apply capability state `state` to the current thread.
+    ///
+    /// Permitted and Bounding sets of `state` must match the current ones.
+    pub fn apply(state: &CapState) -> Result<ScopedState> {
+        let snapshot = CapState::current()?;
+        if state.permitted != snapshot.permitted || state.bounding != snapshot.bounding {
+            bail!("permitted and bounding sets cannot be temporarily changed");
+        }
+        state.apply()?;
+        Ok(ScopedState {
+            snapshot,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Return the state which will be restored.
+    pub fn snapshot(&self) -> &CapState {
+        &self.snapshot
+    }
+}
+
+impl Drop for ScopedState {
+    fn drop(&mut self) {
+        let _ = self.snapshot.apply();
+    }
+}
+
+/// Guard temporarily dropping capabilities.
+///
+/// Capabilities are removed from the Effective, Inheritable and Ambient
+/// sets of the current thread, and restored when the guard goes out of
+/// scope.
+#[derive(Debug)]
+#[must_use]
+pub struct ScopedDrop(ScopedState);
+
+impl ScopedDrop {
+    /// Temporarily drop all capabilities in `caps`.
+    pub fn new(caps: &CapsHashSet) -> Result<ScopedDrop> {
+        let mut reduced = CapState::current()?;
+        for cset in &[CapSet::Effective, CapSet::Inheritable, CapSet::Ambient] {
+            reduced.get_mut(*cset).retain(|c| !caps.contains(c));
+        }
+        ScopedState::apply(&reduced).map(ScopedDrop)
+    }
+
+    /// Return the state which will be restored.
+    pub fn snapshot(&self) -> &CapState {
+        self.0.snapshot()
+    }
+}
+
+/// Run closure `f` with capability `cap`, then permanently drop it.
+///
+/// This encodes the "use once then drop" idiom: `cap` is raised in the
+/// Effective set, `f` is run and then `cap` is removed from the
+/// Effective, Permitted, Inheritable and Ambient sets (and also from the
+/// Bounding set if `drop_bounding` is true), so that it cannot be regained.
+/// The capability is dropped even if `f` panics. Failing to drop it from
+/// one set does not prevent dropping it from the others, and the returned
+/// error lists all failing sets.
+///
+/// ```rust,no_run
+/// use caps::Capability;
+///
+/// let listener = caps::guard::use_then_drop(Capability::CAP_NET_BIND_SERVICE, false, || {
+///     std::net::TcpListener::bind("0.0.0.0:80")
+/// });
+/// ```
+pub fn use_then_drop<F, T>(cap: Capability, drop_bounding: bool, f: F) -> Result<T>
+where
+    F: FnOnce() -> T,
+{
+    // `forget` runs before `_guard` lowers `cap` again, even on unwinding.
+    let _guard = CapGuard::raise(cap)?;
+    let mut forget = Forget {
+        cap,
+        drop_bounding,
+        armed: true,
+    };
+    let res = f();
+    forget.run()?;
+    Ok(res)
+}
+
+// Permanently drop a capability, either explicitly or on unwinding.
+struct Forget {
+    cap: Capability,
+    drop_bounding: bool,
+    armed: bool,
+}
+
+impl Forget {
+    // Drop from all sets, even if some of them fail. This runs while `cap`
+    // is still raised, and the Bounding set is handled before the Effective
+    // one, as this needs CAP_SETPCAP (which may be `cap` itself), but its
+    // failure does not prevent the others.
+    fn run(&mut self) -> Result<()> {
+        self.armed = false;
+        let cap = self.cap;
+        let mut failed = vec![];
+        let mut first = None;
+        let mut attempt = |cset: CapSet, res: Result<()>| {
+            if let Err(e) = res {
+                failed.push(format!("{:?}", cset));
+                first.get_or_insert(e);
+            }
+        };
+        attempt(CapSet::Ambient, ambient::drop(cap));
+        attempt(CapSet::Inheritable, base::drop(0, CapSet::Inheritable, cap));
+        if self.drop_bounding {
+            attempt(CapSet::Bounding, bounding::drop(cap));
+        }
+        attempt(CapSet::Effective, base::drop(0, CapSet::Effective, cap));
+        attempt(CapSet::Permitted, base::drop(0, CapSet::Permitted, cap));
+        match first {
+            Some(e) => {
+                Err(e).chain_err(|| format!("failed to drop {} from {}", cap, failed.join(", ")))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Forget {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = self.run();
+        }
+    }
+}
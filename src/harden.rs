@@ -0,0 +1,79 @@
+//! One-shot lockdown of the calling thread.
+//!
+//! `harden` performs, in the correct order, what most daemons want right
+//! after acquiring their privileged resources: clear the ambient set, lock
+//! securebits, shrink the bounding set and all other sets to a keep-list,
+//! and set "no new privileges".
+
+use errno::Errno;
+
+use super::{to_bitmask, CapsHashSet};
+use errors::*;
+use lowlevel::{self, Masks};
+use securebits::{self, SecureBits};
+
+/// Options for `harden`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct HardenOptions {
+    /// Capabilities to keep in the bounding, permitted and effective sets.
+    pub keep: CapsHashSet,
+    /// Whether to set the "no new privileges" flag.
+    pub no_new_privs: bool,
+    /// Whether to set and lock `SecureBits::LOCKDOWN`.
+    pub lock_securebits: bool,
+}
+
+impl HardenOptions {
+    /// Keep capabilities `keep`, setting "no new privileges" and locking
+    /// securebits.
+    pub fn new(keep: &CapsHashSet) -> HardenOptions {
+        HardenOptions {
+            keep: keep.clone(),
+            no_new_privs: true,
+            lock_securebits: true,
+        }
+    }
+}
+
+/// Lock down the calling thread according to `opts`.
+///
+/// In order: the ambient set is cleared, securebits are locked, the
+/// bounding set is shrunk to `opts.keep`, the permitted and effective sets
+/// are set to `opts.keep` and the inheritable set is restricted to it,
+/// then "no new privileges" is set. Capabilities unknown to this crate are
+/// dropped as well. All kept capabilities must be in the permitted set.
+pub fn harden(opts: HardenOptions) -> Result<()> {
+    let keep = to_bitmask(&opts.keep);
+    let current = Masks::current().map_err(|e| sys(e, "capget error"))?;
+    if let Some(cap) = opts
+        .keep
+        .iter()
+        .find(|c| (current.permitted & c.bitmask()) == 0)
+    {
+        let reason = format!("{} is not in the permitted set", cap);
+        return Err(Error::from_kind(ErrorKind::InvalidTransition(reason)));
+    }
+
+    lowlevel::ambient_clear().map_err(|e| sys(e, "ambient set error"))?;
+    if opts.lock_securebits {
+        securebits::set_securebits(SecureBits::LOCKDOWN)?;
+    }
+    let target = Masks {
+        ambient: 0,
+        bounding: keep,
+        effective: keep,
+        inheritable: current.inheritable & keep,
+        permitted: keep,
+    };
+    target
+        .apply()
+        .map_err(|e| sys(e, "failed to apply capabilities"))?;
+    if opts.no_new_privs {
+        securebits::set_no_new_privs()?;
+    }
+    Ok(())
+}
+
+fn sys(e: Errno, context: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(e)).chain_err(|| context)
+}
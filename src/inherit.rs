@@ -0,0 +1,88 @@
+//! Verify capabilities inherited across `execve(2)`.
+//!
+//! Misconfigured file capabilities or securebits usually go unnoticed
+//! until a privileged operation fails, long after startup. Instead, a
+//! parent can pass the capability state it expects the child to run
+//! with (typically the `state` predicted by `exec::predict_exec`) in the
+//! `CAPS_EXPECT` environment variable, and the child can check it right
+//! at startup with `verify_inherited`.
+//!
+//! The expectation is encoded as a short string of hexadecimal masks
+//! (see `encode`), which can also be passed through other channels, such
+//! as a file descriptor, and checked with `decode` and `verify_state`.
+
+use super::{from_bitmask, to_bitmask, verify_state, CapSet, CapState};
+use errors::*;
+use std::env;
+use std::process::Command;
+
+/// Environment variable holding the expected state, used by `verify_inherited`.
+pub const EXPECT_VAR: &str = "CAPS_EXPECT";
+
+// Sets and their keys, in encoding order.
+const KEYS: [(&str, CapSet); 5] = [
+    ("inh", CapSet::Inheritable),
+    ("prm", CapSet::Permitted),
+    ("eff", CapSet::Effective),
+    ("bnd", CapSet::Bounding),
+    ("amb", CapSet::Ambient),
+];
+
+/// Encode `state` as a string (e.g. `"inh=0,prm=400,eff=400,bnd=400,amb=0"`).
+pub fn encode(state: &CapState) -> String {
+    KEYS.iter()
+        .map(|&(key, cset)| format!("{}={:x}", key, to_bitmask(state.get(cset))))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decode a state encoded by `encode`.
+///
+/// All five sets must be present.
+pub fn decode(s: &str) -> Result<CapState> {
+    let mut state = CapState::default();
+    let mut found = vec![];
+    for part in s.split(',').map(|p| p.trim()) {
+        let mut kv = part.splitn(2, '=');
+        let (key, value) = (kv.next().unwrap_or(""), kv.next().unwrap_or(""));
+        let cset = match KEYS.iter().find(|&&(k, _)| k == key) {
+            Some(&(_, cset)) => cset,
+            None => bail!("invalid capability set '{}'", key),
+        };
+        let mask = match u64::from_str_radix(value, 16) {
+            Ok(m) => m,
+            Err(_) => bail!("invalid capability mask '{}'", value),
+        };
+        *state.get_mut(cset) = from_bitmask(mask);
+        found.push(cset);
+    }
+    if KEYS.iter().any(|&(_, cset)| !found.contains(&cset)) {
+        bail!("missing capability sets in '{}'", s);
+    }
+    Ok(state)
+}
+
+/// Set the expected capability state of the program run by `cmd`.
+pub fn expect<'a>(cmd: &'a mut Command, state: &CapState) -> &'a mut Command {
+    cmd.env(EXPECT_VAR, encode(state))
+}
+
+/// Verify the current thread against the state expected by the parent.
+///
+/// The expectation is read from `CAPS_EXPECT` (see `expect`), which is
+/// then removed from the environment so that it is not inherited further.
+/// As it modifies the environment, this should be called early at
+/// startup, before spawning threads.
+///
+/// Return whether an expectation was found. On mismatch, an
+/// `ErrorKind::StateMismatch` error details the differences.
+pub fn verify_inherited() -> Result<bool> {
+    let value = match env::var(EXPECT_VAR) {
+        Ok(v) => v,
+        Err(env::VarError::NotPresent) => return Ok(false),
+        Err(e) => return Err(e).chain_err(|| format!("invalid {}", EXPECT_VAR)),
+    };
+    env::remove_var(EXPECT_VAR);
+    let expected = decode(&value).chain_err(|| format!("invalid {}", EXPECT_VAR))?;
+    verify_state(&expected).map(|_| true)
+}
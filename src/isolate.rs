@@ -0,0 +1,140 @@
+//! Closures run in forked children.
+//!
+//! Some capability changes cannot be undone (dropping from the Permitted
+//! or Bounding sets, switching ids), and some privileged operations are
+//! risky. `run_in_child` forks, applies a capability state in the child
+//! only, runs a closure there and sends its result back to the parent,
+//! whose own state is never modified:
+//!
+//! ```rust,no_run
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::isolate;
+//! use caps::{CapSet, CapState};
+//!
+//! # fn main() {
+//! let state = CapState {
+//!     bounding: caps![],
+//!     ..CapState::current().unwrap()
+//! };
+//! let empty = isolate::run_in_child(&state, || {
+//!     caps::read(None, CapSet::Bounding).unwrap().is_empty()
+//! })
+//! .unwrap();
+//! assert!(empty);
+//! # }
+//! ```
+//!
+//! Results are serialized with `serde_json`. As always after `fork(2)` in a
+//! multi-threaded program, the child holds a single thread, so closures
+//! must not wait on locks held by other threads of the parent.
+//!
+//! This module requires the `isolate` feature.
+
+use errno;
+use libc;
+
+use super::CapState;
+use errors::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::any::Any;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::panic::{self, AssertUnwindSafe};
+
+// Outcome of the child, as sent to the parent.
+#[derive(Serialize, Deserialize)]
+enum Report<T> {
+    Done(T),
+    Failed { errno: Option<i32>, message: String },
+    Panicked(String),
+}
+
+/// Run `f` in a forked child with capability state `state`.
+///
+/// The child applies `state`, runs `f` and exits, and the result of `f`
+/// is returned. Failures to apply `state` are returned as errors, as are
+/// panics of `f` and abnormal terminations of the child.
+pub fn run_in_child<F, T>(state: &CapState, f: F) -> Result<T>
+where
+    F: FnOnce() -> T,
+    T: Serialize + DeserializeOwned,
+{
+    let mut fds = [-1; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "pipe2 error"));
+    }
+    let pid = unsafe { libc::fork() };
+    if pid == 0 {
+        unsafe { libc::close(fds[0]) };
+        let report = match state.apply() {
+            Ok(()) => match panic::catch_unwind(AssertUnwindSafe(f)) {
+                Ok(res) => Report::Done(res),
+                Err(e) => Report::Panicked(panic_message(&*e)),
+            },
+            Err(e) => Report::Failed {
+                errno: e.errno().map(|e| e.0),
+                message: e.to_string(),
+            },
+        };
+        let mut pipe = unsafe { File::from_raw_fd(fds[1]) };
+        let code = match serde_json::to_vec(&report) {
+            Ok(buf) if pipe.write_all(&buf).is_ok() => 0,
+            _ => 127,
+        };
+        unsafe { libc::_exit(code) };
+    }
+    let fork_err = errno::errno();
+    unsafe { libc::close(fds[1]) };
+    if pid < 0 {
+        unsafe { libc::close(fds[0]) };
+        return Err(Error::from_kind(ErrorKind::Sys(fork_err)).chain_err(|| "fork error"));
+    }
+
+    let mut buf = vec![];
+    let mut pipe = unsafe { File::from_raw_fd(fds[0]) };
+    let read = pipe.read_to_end(&mut buf);
+    let status = wait(pid)?;
+    read.chain_err(|| "failed to read report from child")?;
+    if buf.is_empty() || !libc::WIFEXITED(status) || libc::WEXITSTATUS(status) != 0 {
+        if libc::WIFSIGNALED(status) {
+            bail!("child killed by signal {}", libc::WTERMSIG(status));
+        }
+        bail!("child exited with status {}", libc::WEXITSTATUS(status));
+    }
+    match serde_json::from_slice(&buf).chain_err(|| "invalid report from child")? {
+        Report::Done(res) => Ok(res),
+        Report::Failed {
+            errno: Some(e),
+            message,
+        } => Err(Error::from_kind(ErrorKind::Sys(errno::Errno(e))).chain_err(|| message)),
+        Report::Failed { message, .. } => bail!(message),
+        Report::Panicked(message) => bail!("child panicked: {}", message),
+    }
+}
+
+fn wait(pid: libc::pid_t) -> Result<libc::c_int> {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } == pid {
+            return Ok(status);
+        }
+        let err = errno::errno();
+        if err.0 != libc::EINTR {
+            return Err(Error::from_kind(ErrorKind::Sys(err)).chain_err(|| "waitpid error"));
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
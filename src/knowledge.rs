@@ -0,0 +1,229 @@
+//! Knowledge base of privileged operations.
+//!
+//! This module maps capabilities to the common operations they grant,
+//! along with the syscalls involved. It is not exhaustive, but covers
+//! the typical reasons for a program to require a capability.
+
+use super::Capability;
+
+/// A privileged operation, granted by a capability.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Operation {
+    /// Capability required by this operation.
+    pub capability: Capability,
+    /// Short human-readable description.
+    pub description: &'static str,
+    /// Syscalls which may perform this operation.
+    pub syscalls: &'static [&'static str],
+}
+
+macro_rules! op {
+    ($cap:ident, $desc:expr, [$($sc:expr),*]) => {
+        Operation {
+            capability: Capability::$cap,
+            description: $desc,
+            syscalls: &[$($sc),*],
+        }
+    };
+}
+
+/// All known privileged operations.
+pub const OPERATIONS: &[Operation] = &[
+    op!(
+        CAP_CHOWN,
+        "change file ownership",
+        ["chown", "fchown", "lchown", "fchownat"]
+    ),
+    op!(
+        CAP_DAC_OVERRIDE,
+        "bypass file permission checks",
+        ["open", "openat", "execve"]
+    ),
+    op!(
+        CAP_DAC_READ_SEARCH,
+        "bypass file read and directory search checks",
+        ["open", "openat"]
+    ),
+    op!(
+        CAP_DAC_READ_SEARCH,
+        "open files by handle",
+        ["open_by_handle_at"]
+    ),
+    op!(
+        CAP_FOWNER,
+        "change mode and times of files owned by others",
+        ["chmod", "fchmod", "fchmodat", "utimensat"]
+    ),
+    op!(
+        CAP_FSETID,
+        "keep set-user-ID bits when modifying files",
+        ["chmod", "fchmod", "write"]
+    ),
+    op!(
+        CAP_KILL,
+        "send signals to processes of other users",
+        ["kill", "tkill", "tgkill"]
+    ),
+    op!(
+        CAP_SETGID,
+        "change group IDs",
+        ["setgid", "setregid", "setresgid", "setfsgid", "setgroups"]
+    ),
+    op!(
+        CAP_SETUID,
+        "change user IDs",
+        ["setuid", "setreuid", "setresuid", "setfsuid"]
+    ),
+    op!(
+        CAP_SETPCAP,
+        "drop capabilities from the bounding set",
+        ["prctl"]
+    ),
+    op!(CAP_SETPCAP, "change securebits flags", ["prctl"]),
+    op!(
+        CAP_LINUX_IMMUTABLE,
+        "set immutable and append-only file flags",
+        ["ioctl"]
+    ),
+    op!(CAP_NET_BIND_SERVICE, "bind to ports below 1024", ["bind"]),
+    op!(
+        CAP_NET_BROADCAST,
+        "broadcast and listen to multicast",
+        ["setsockopt"]
+    ),
+    op!(
+        CAP_NET_ADMIN,
+        "configure network interfaces, routes and firewall",
+        ["ioctl", "setsockopt", "sendmsg"]
+    ),
+    op!(CAP_NET_RAW, "use raw and packet sockets", ["socket"]),
+    op!(
+        CAP_IPC_LOCK,
+        "lock memory",
+        ["mlock", "mlock2", "mlockall", "shmctl"]
+    ),
+    op!(
+        CAP_IPC_OWNER,
+        "bypass System V IPC permission checks",
+        ["msgget", "semget", "shmget", "shmat"]
+    ),
+    op!(
+        CAP_SYS_MODULE,
+        "load and unload kernel modules",
+        ["init_module", "finit_module", "delete_module"]
+    ),
+    op!(
+        CAP_SYS_RAWIO,
+        "perform I/O port operations",
+        ["iopl", "ioperm"]
+    ),
+    op!(CAP_SYS_CHROOT, "change root directory", ["chroot"]),
+    op!(
+        CAP_SYS_PTRACE,
+        "trace and inspect arbitrary processes",
+        ["ptrace", "process_vm_readv", "process_vm_writev"]
+    ),
+    op!(CAP_SYS_PACCT, "configure process accounting", ["acct"]),
+    op!(
+        CAP_SYS_ADMIN,
+        "mount and unmount filesystems",
+        ["mount", "umount2", "pivot_root"]
+    ),
+    op!(
+        CAP_SYS_ADMIN,
+        "set hostname and domain name",
+        ["sethostname", "setdomainname"]
+    ),
+    op!(CAP_SYS_ADMIN, "manage swap areas", ["swapon", "swapoff"]),
+    op!(CAP_SYS_ADMIN, "enter namespaces", ["setns"]),
+    op!(
+        CAP_SYS_BOOT,
+        "reboot and load new kernels",
+        ["reboot", "kexec_load", "kexec_file_load"]
+    ),
+    op!(
+        CAP_SYS_NICE,
+        "raise priority and set realtime scheduling",
+        [
+            "setpriority",
+            "nice",
+            "sched_setscheduler",
+            "sched_setparam"
+        ]
+    ),
+    op!(
+        CAP_SYS_NICE,
+        "set memory placement policies",
+        ["mbind", "migrate_pages", "move_pages"]
+    ),
+    op!(
+        CAP_SYS_RESOURCE,
+        "raise resource limits",
+        ["setrlimit", "prlimit64"]
+    ),
+    op!(
+        CAP_SYS_TIME,
+        "set system clock",
+        ["settimeofday", "clock_settime", "adjtimex", "clock_adjtime"]
+    ),
+    op!(CAP_SYS_TTY_CONFIG, "hang up virtual terminals", ["vhangup"]),
+    op!(CAP_MKNOD, "create special files", ["mknod", "mknodat"]),
+    op!(
+        CAP_LEASE,
+        "establish leases on files owned by others",
+        ["fcntl"]
+    ),
+    op!(
+        CAP_AUDIT_WRITE,
+        "write records to kernel audit log",
+        ["sendto", "sendmsg"]
+    ),
+    op!(
+        CAP_AUDIT_CONTROL,
+        "configure kernel audit subsystem",
+        ["sendto", "sendmsg"]
+    ),
+    op!(
+        CAP_SETFCAP,
+        "set file capabilities",
+        ["setxattr", "lsetxattr", "fsetxattr"]
+    ),
+    op!(
+        CAP_MAC_OVERRIDE,
+        "override Mandatory Access Control policy",
+        []
+    ),
+    op!(
+        CAP_MAC_ADMIN,
+        "configure Mandatory Access Control policy",
+        []
+    ),
+    op!(CAP_SYSLOG, "read and clear kernel log", ["syslog"]),
+    op!(
+        CAP_WAKE_ALARM,
+        "set timers waking up the system",
+        ["timerfd_create", "timer_create"]
+    ),
+    op!(CAP_BLOCK_SUSPEND, "block system suspend", ["epoll_ctl"]),
+    op!(
+        CAP_AUDIT_READ,
+        "read kernel audit log via multicast netlink",
+        ["bind"]
+    ),
+];
+
+/// Return all known operations granted by capability `cap`.
+pub fn operations(cap: Capability) -> Vec<&'static Operation> {
+    OPERATIONS
+        .iter()
+        .filter(|op| op.capability == cap)
+        .collect()
+}
+
+/// Return all known operations which syscall `name` may perform.
+pub fn by_syscall(name: &str) -> Vec<&'static Operation> {
+    OPERATIONS
+        .iter()
+        .filter(|op| op.syscalls.contains(&name))
+        .collect()
+}
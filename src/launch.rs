@@ -0,0 +1,381 @@
+//! Launch programs with a configured capability state.
+//!
+//! This is a programmatic equivalent of `capsh`/`setpriv` for supervisors
+//! which do not use `std::process::Command`: the launcher forks, configures
+//! capabilities, securebits and ids of the child, then executes the target
+//! program. The child only performs async-signal-safe operations (see the
+//! `lowlevel` module), and failures are reported back to the parent.
+
+use errno;
+use libc;
+
+use super::{to_bitmask, CapsHashSet};
+use errors::*;
+use lowlevel;
+use securebits::SecureBits;
+use std::env;
+use std::ffi::{CString, OsStr, OsString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+// Steps performed in the child, as reported on failure.
+const STEPS: &[&str] = &[
+    "securebits error",
+    "bounding set error",
+    "setgroups error",
+    "setresgid error",
+    "keepcaps error",
+    "setresuid error",
+    "capset error",
+    "ambient set error",
+    "chdir error",
+    "execve error",
+];
+
+/// Configuration for launching a program, mirroring libcap's `cap_launch`.
+///
+/// In the child, in order:
+///  * securebits are set, if requested;
+///  * capabilities outside of the bounding set are dropped from it;
+///  * supplementary groups, gid and uid are switched, if requested,
+///    retaining permitted capabilities;
+///  * ambient capabilities are raised in the inheritable and ambient sets,
+///    all other ambient capabilities are cleared;
+///  * the working directory is changed, if requested;
+///  * the program is executed.
+///
+/// Thus `SecureBits::NO_CAP_AMBIENT_RAISE` prevents raising ambient
+/// capabilities, and those must be in the permitted set of the parent.
+#[derive(Debug, Clone)]
+pub struct Launcher {
+    program: PathBuf,
+    args: Vec<OsString>,
+    ambient: CapsHashSet,
+    bounding: Option<CapsHashSet>,
+    securebits: Option<SecureBits>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    groups: Option<Vec<u32>>,
+    keep_groups: bool,
+    chdir: Option<PathBuf>,
+    clear_env: bool,
+    envs: Vec<(OsString, OsString)>,
+}
+
+impl Launcher {
+    /// Start configuring the launch of `program`.
+    ///
+    /// By default the child keeps the ids, bounding set and environment of
+    /// the parent, with an empty ambient set.
+    pub fn new<P: AsRef<Path>>(program: P) -> Launcher {
+        Launcher {
+            program: program.as_ref().to_path_buf(),
+            args: vec![],
+            ambient: CapsHashSet::new(),
+            bounding: None,
+            securebits: None,
+            uid: None,
+            gid: None,
+            groups: None,
+            keep_groups: false,
+            chdir: None,
+            clear_env: false,
+            envs: vec![],
+        }
+    }
+
+    /// Append argument `arg`.
+    pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Launcher {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Append arguments `args`.
+    pub fn args<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(mut self, args: I) -> Launcher {
+        self.args
+            .extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+
+    /// Set the ambient capabilities of the child.
+    pub fn ambient(mut self, caps: &CapsHashSet) -> Launcher {
+        self.ambient = caps.clone();
+        self
+    }
+
+    /// Restrict the bounding set of the child to `caps`.
+    pub fn bounding(mut self, caps: &CapsHashSet) -> Launcher {
+        self.bounding = Some(caps.clone());
+        self
+    }
+
+    /// Set the securebits of the child.
+    pub fn securebits(mut self, bits: SecureBits) -> Launcher {
+        self.securebits = Some(bits);
+        self
+    }
+
+    /// Switch real, effective and saved uids of the child to `uid`.
+    pub fn user(mut self, uid: u32) -> Launcher {
+        self.uid = Some(uid);
+        self
+    }
+
+    /// Switch real, effective and saved gids of the child to `gid`.
+    pub fn group(mut self, gid: u32) -> Launcher {
+        self.gid = Some(gid);
+        self
+    }
+
+    /// Set the supplementary groups of the child.
+    pub fn groups(mut self, groups: &[u32]) -> Launcher {
+        self.groups = Some(groups.to_vec());
+        self
+    }
+
+    /// Explicitly keep supplementary groups of the parent when switching ids.
+    ///
+    /// Switching ids otherwise requires setting supplementary groups.
+    pub fn keep_groups(mut self) -> Launcher {
+        self.keep_groups = true;
+        self
+    }
+
+    /// Change the working directory of the child.
+    pub fn chdir<P: AsRef<Path>>(mut self, dir: P) -> Launcher {
+        self.chdir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Do not inherit the environment of the parent.
+    pub fn clear_env(mut self) -> Launcher {
+        self.clear_env = true;
+        self
+    }
+
+    /// Set environment variable `key` to `value` in the child.
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(mut self, key: K, value: V) -> Launcher {
+        self.envs
+            .push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Fork and execute the program.
+    ///
+    /// On success, the pid of the child is returned and must be waited for.
+    pub fn launch(&self) -> Result<libc::pid_t> {
+        let switch_ids = self.uid.is_some() || self.gid.is_some();
+        if switch_ids && self.groups.is_none() && !self.keep_groups {
+            let reason = "switching ids requires setting or keeping supplementary groups";
+            return Err(Error::from_kind(ErrorKind::InvalidTransition(
+                reason.into(),
+            )));
+        }
+        // Everything the child needs is prepared before forking.
+        let path = cstring(self.program.as_os_str())?;
+        let mut argv = vec![path.clone()];
+        for arg in &self.args {
+            argv.push(cstring(arg)?);
+        }
+        let mut vars: Vec<(OsString, OsString)> = if self.clear_env {
+            vec![]
+        } else {
+            env::vars_os().collect()
+        };
+        for (key, value) in &self.envs {
+            vars.retain(|(k, _)| k != key);
+            vars.push((key.clone(), value.clone()));
+        }
+        let mut envp = vec![];
+        for (key, value) in vars {
+            let mut var = key;
+            var.push("=");
+            var.push(value);
+            envp.push(cstring(&var)?);
+        }
+        let chdir = match self.chdir {
+            Some(ref dir) => Some(cstring(dir.as_os_str())?),
+            None => None,
+        };
+        let child = Child {
+            path: &path,
+            argv: ptrs(&argv),
+            envp: ptrs(&envp),
+            ambient: to_bitmask(&self.ambient),
+            bounding: self.bounding.as_ref().map(to_bitmask),
+            securebits: self.securebits,
+            uid: self.uid,
+            gid: self.gid,
+            groups: self.groups.as_deref(),
+            chdir: chdir.as_ref(),
+        };
+
+        let mut fds = [-1; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            return Err(
+                Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "pipe2 error")
+            );
+        }
+        let pid = unsafe { libc::fork() };
+        if pid == 0 {
+            unsafe { libc::close(fds[0]) };
+            let (step, err) = child.run();
+            let report = [step as i32, err.0];
+            unsafe {
+                libc::write(
+                    fds[1],
+                    report.as_ptr() as *const libc::c_void,
+                    std::mem::size_of_val(&report),
+                );
+                libc::_exit(127);
+            }
+        }
+        let fork_err = errno::errno();
+        unsafe { libc::close(fds[1]) };
+        if pid < 0 {
+            unsafe { libc::close(fds[0]) };
+            return Err(Error::from_kind(ErrorKind::Sys(fork_err)).chain_err(|| "fork error"));
+        }
+
+        // The pipe is closed without any report once `execve` succeeded.
+        let mut report = [0i32; 2];
+        let n = loop {
+            let n = unsafe {
+                libc::read(
+                    fds[0],
+                    report.as_mut_ptr() as *mut libc::c_void,
+                    std::mem::size_of_val(&report),
+                )
+            };
+            if n >= 0 || errno::errno().0 != libc::EINTR {
+                break n;
+            }
+        };
+        unsafe { libc::close(fds[0]) };
+        if n == 0 {
+            return Ok(pid);
+        }
+        let mut status = 0;
+        unsafe { libc::waitpid(pid, &mut status, 0) };
+        if n as usize != std::mem::size_of_val(&report) {
+            bail!("failed to read launch report from child");
+        }
+        let step = STEPS.get(report[0] as usize).unwrap_or(&"launch error");
+        Err(Error::from_kind(ErrorKind::Sys(errno::Errno(report[1]))).chain_err(|| *step))
+    }
+}
+
+/// Fork and execute `program` with arguments `args`.
+///
+/// This is a shorthand for a `Launcher` with the given ambient set,
+/// bounding set, securebits and `(uid, gid)` ids. Supplementary groups
+/// are cleared when switching ids.
+pub fn spawn_with_caps<P: AsRef<Path>, S: AsRef<OsStr>>(
+    program: P,
+    args: &[S],
+    ambient: &CapsHashSet,
+    bounding: &CapsHashSet,
+    securebits: Option<SecureBits>,
+    ids: Option<(u32, u32)>,
+) -> Result<libc::pid_t> {
+    let mut launcher = Launcher::new(program)
+        .args(args)
+        .ambient(ambient)
+        .bounding(bounding);
+    if let Some(bits) = securebits {
+        launcher = launcher.securebits(bits);
+    }
+    if let Some((uid, gid)) = ids {
+        launcher = launcher.user(uid).group(gid).groups(&[]);
+    }
+    launcher.launch()
+}
+
+fn cstring(s: &OsStr) -> Result<CString> {
+    CString::new(s.as_bytes()).chain_err(|| format!("invalid argument {:?}", s))
+}
+
+fn ptrs(strings: &[CString]) -> Vec<*const libc::c_char> {
+    let mut res: Vec<_> = strings.iter().map(|s| s.as_ptr()).collect();
+    res.push(std::ptr::null());
+    res
+}
+
+// Fully prepared child configuration.
+struct Child<'a> {
+    path: &'a CString,
+    argv: Vec<*const libc::c_char>,
+    envp: Vec<*const libc::c_char>,
+    ambient: u64,
+    bounding: Option<u64>,
+    securebits: Option<SecureBits>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    groups: Option<&'a [u32]>,
+    chdir: Option<&'a CString>,
+}
+
+impl<'a> Child<'a> {
+    // Configure and exec the child, returning the failed step on error.
+    fn run(&self) -> (usize, errno::Errno) {
+        if let Some(bits) = self.securebits {
+            if let Err(e) = lowlevel::set_securebits(bits.bits()) {
+                return (0, e);
+            }
+        }
+        if let Some(bounding) = self.bounding {
+            for index in 0..64 {
+                if (bounding & (1u64 << index)) == 0 && lowlevel::bounding_read(index) {
+                    if let Err(e) = lowlevel::bounding_drop(index) {
+                        return (1, e);
+                    }
+                }
+            }
+        }
+        if let Some(groups) = self.groups {
+            if unsafe { libc::setgroups(groups.len(), groups.as_ptr()) } != 0 {
+                return (2, errno::errno());
+            }
+        }
+        if let Some(gid) = self.gid {
+            if unsafe { libc::setresgid(gid, gid, gid) } != 0 {
+                return (3, errno::errno());
+            }
+        }
+        if let Some(uid) = self.uid {
+            // Not needed, and possibly locked, without setuid fixups.
+            let fixup = SecureBits::NO_SETUID_FIXUP.bits() | SecureBits::KEEP_CAPS.bits();
+            let res = lowlevel::get_securebits().and_then(|bits| match bits & fixup {
+                0 => lowlevel::set_keepcaps(true),
+                _ => Ok(()),
+            });
+            if let Err(e) = res {
+                return (4, e);
+            }
+            if unsafe { libc::setresuid(uid, uid, uid) } != 0 {
+                return (5, errno::errno());
+            }
+        }
+        let res = lowlevel::capget().and_then(|(e, p, i)| lowlevel::capset(e, p, i | self.ambient));
+        if let Err(e) = res {
+            return (6, e);
+        }
+        if let Err(e) = lowlevel::ambient_clear() {
+            return (7, e);
+        }
+        for index in 0..64 {
+            if (self.ambient & (1u64 << index)) != 0 {
+                if let Err(e) = lowlevel::ambient_raise(index) {
+                    return (7, e);
+                }
+            }
+        }
+        if let Some(dir) = self.chdir {
+            if unsafe { libc::chdir(dir.as_ptr()) } != 0 {
+                return (8, errno::errno());
+            }
+        }
+        unsafe { libc::execve(self.path.as_ptr(), self.argv.as_ptr(), self.envp.as_ptr()) };
+        (9, errno::errno())
+    }
+}
@@ -5,6 +5,23 @@
 //! (Effective, Inheritable, Permitted) as well as Linux-specific
 //! Ambient and Bounding capabilities sets.
 //!
+//! Capabilities are a per-thread attribute: changing them only affects
+//! the calling thread (see the `this_thread` module), while other
+//! threads of the same process are left untouched.
+//!
+//! On platforms other than Linux and Android, this crate still builds but
+//! only provides types and parsing: operations on threads always fail with
+//! `ErrorKind::Unsupported`.
+//!
+//! Syscalls are issued via libc, with the default `libc` feature, or via
+//! rustix with the `rustix` feature. Building with `rustix` only drops
+//! the libc dependency, and leaves out modules which still need it (such
+//! as `psx`, `launch` or `file`): only the core API is then available.
+//! The rustix backend is only available on Linux; other platforms with
+//! native privilege support require `libc`. It also always uses version 3
+//! of the capget/capset interface, thus requires Linux 2.6.26 or later,
+//! while the libc backend falls back to older versions on older kernels.
+//!
 //! ```rust
 //! use caps::{Capability, CapSet};
 //!
@@ -18,27 +35,196 @@
 //! }
 //! ```
 
+extern crate caps_core;
 #[macro_use]
 extern crate error_chain;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "clap")]
+extern crate clap;
+#[cfg(feature = "libc")]
 extern crate errno;
+#[cfg(feature = "libc")]
 extern crate libc;
+#[cfg(feature = "metrics")]
+extern crate metrics;
+#[cfg(feature = "nix")]
+extern crate nix;
+#[cfg(feature = "procfs")]
+extern crate procfs;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "rustix")]
+extern crate rustix;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+#[cfg(feature = "serde_yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "toml")]
+extern crate toml;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+
+#[cfg(all(target_os = "linux", not(any(feature = "libc", feature = "rustix"))))]
+compile_error!("either the `libc` or the `rustix` feature must be enabled");
+#[cfg(all(
+    any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "solaris"
+    ),
+    not(feature = "libc")
+))]
+compile_error!("the `libc` feature is required on this platform");
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_support; // Arbitrary values for fuzzing
+pub mod backend; // Cross-platform privilege backends
+#[cfg(target_os = "freebsd")]
+pub mod capsicum; // FreeBSD Capsicum capability mode
+pub mod docker; // Docker capability flags
+pub mod errors; // Error wrapping
+pub mod knowledge; // Privileged operations knowledge base
+#[cfg(feature = "mock")]
+pub mod mock; // In-memory privilege backend for tests
+#[cfg_attr(not(feature = "libc"), allow(dead_code))]
+mod nr; // All kernel-related constants
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub mod privileges; // illumos and Solaris privilege sets
+pub mod selinux; // SELinux names of capabilities
+#[cfg(feature = "serde")]
+mod serde_support; // Serialization of capabilities
+pub mod sets; // Predefined capability groups
+pub mod strace; // Required capabilities inferred from strace logs
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod stub; // Stub for unsupported platforms
+
+// Modules relying on Linux kernel interfaces.
+macro_rules! linux_only {
+    ($($item:item)*) => {
+        $( #[cfg(any(target_os = "linux", target_os = "android"))] $item )*
+    };
+}
 
+linux_only! {
 mod ambient;     // Implementation of Ambient set
+#[cfg(feature = "libc")]
+pub mod audit;   // Audit against a hardening baseline
 mod base;        // Implementation of POSIX sets
 mod bounding;    // Implementation of Bounding set
-pub mod errors;  // Error wrapping
-mod nr;          // All kernel-related constants
+pub mod cache;   // Cached capability state for hot paths
+#[cfg(feature = "capi")]
+pub mod capi;    // C ABI over the core API
+#[cfg(feature = "libc")]
+pub mod checkpoint; // Checkpoint/restore of per-thread state
+#[cfg(feature = "clap")]
+mod clap_support; // Command-line parsing with clap
+#[cfg(feature = "libc")]
+pub mod command; // Capabilities of std::process::Command children
+pub mod compare; // Structured comparison of capability states
+#[cfg(feature = "config")]
+pub mod config;  // Config-file-driven privilege policy
+#[cfg(feature = "libc")]
+pub mod diagnostics; // Inspection and troubleshooting helpers
+#[cfg(feature = "ebpf")]
+pub mod ebpf;    // Tracing of capability checks with eBPF
+pub mod effective; // Temporary lowering of Effective set
+#[cfg(feature = "libc")]
+pub mod exec;    // Capabilities transformation across execve
+mod fallback;    // Read-only fallback via procfs
+#[cfg(feature = "libc")]
+pub mod file;    // File capabilities
+#[cfg(feature = "metrics")]
+pub mod gauges;  // Capability state gauges for the metrics facade
+pub mod guard;   // Scoped capability changes
+#[cfg(feature = "libc")]
+pub mod harden;  // One-shot lockdown
+pub mod inherit; // Verification of capabilities inherited across exec
+#[cfg(feature = "isolate")]
+pub mod isolate; // Closures run in forked children
+#[cfg(feature = "libc")]
+pub mod launch;  // Fork/exec launcher
+#[cfg(feature = "libcap")]
+pub mod libcap;  // Interoperability with libcap
+#[cfg(feature = "lockdown")]
+pub mod lockdown; // Seccomp lock against capability changes
+pub mod lowlevel; // Async-signal-safe operations on raw masks
+#[cfg(feature = "nix")]
+mod nix_support; // Interoperability with nix types
+pub mod observe; // Observer hooks for capability changes
+pub mod oci;     // Capabilities block of OCI runtime configurations
+pub mod pidfd;   // Process file descriptors helpers
+#[cfg(feature = "libc")]
+pub mod pidns;   // Processes in other PID namespaces
+pub mod policy;  // Declarative capability policies
+#[cfg(feature = "libc")]
+pub mod profiles; // Named privilege profiles
+#[cfg(feature = "procfs")]
+mod procfs_support; // Interoperability with procfs types
+#[cfg(feature = "libc")]
+pub mod psx;     // Process-wide changes across all threads
+#[cfg(feature = "libc")]
+pub mod raw;     // Raw capget/capset interface
+#[cfg(feature = "libc")]
+pub mod recommend; // Least-privilege recommendations from observed usage
 pub mod runtime; // Features/legacy detection at runtime
+#[cfg(feature = "libc")]
+pub mod scan;    // System-wide process scanning
 pub mod securebits; // Thread security bits
+pub mod simple;     // Simplified API in the style of libcap-ng
+#[cfg(feature = "libc")]
+pub mod simulate;   // Fork/exec/setuid chains simulation
+#[cfg(feature = "proptest")]
+pub mod strategy;   // Proptest strategies for capability values
+mod state;       // Whole capability state of a thread
+mod sys;         // Raw syscalls, via libc or rustix
+pub mod systemd; // Generation of systemd unit hardening settings
+#[cfg(feature = "test-util")]
+pub mod test_util; // Scoped capability changes in tests
+pub mod this_thread; // Explicit current-thread operations
+#[cfg(feature = "libc")]
+pub mod threads; // Multi-threading helpers
+#[cfg(feature = "libc")]
+pub mod tool;    // Reusable logic of the `caps` command-line tool
+mod trace;       // Optional tracing instrumentation
+#[cfg(feature = "libc")]
+pub mod transition; // Combined privilege transitions
+pub mod usage;   // Discovery of exercised capabilities from audit records
+#[cfg(feature = "libc")]
+pub mod userns;  // User namespaces
+#[cfg(feature = "libc")]
+pub mod watchdog; // Continuous policy enforcement
+#[cfg(feature = "libc")]
+pub mod whatif;  // Impact analysis for dropping capabilities
+}
 
 use errors::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::iter::FromIterator;
 
+linux_only! {
+#[cfg(feature = "clap")]
+pub use clap_support::{CapabilityParser, CapsListParser};
+pub use observe::CapsDiff;
+pub use state::{verify_state, CapState, CapStateBuilder, SetMismatch, StateMismatch};
+}
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub use stub::{clear, drop, drop_all_except, ensure, has_cap, raise, read, set, set_strict};
+
 /// Linux capabilities sets.
 ///
 /// All capabilities sets supported by Linux, including standard
 /// POSIX and custom ones. See `capabilities(7)`.
-#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CapSet {
     /// Ambient capabilities set (from Linux 4.3).
     Ambient,
@@ -52,6 +238,79 @@ pub enum CapSet {
     Permitted,
 }
 
+/// Target of a capability operation.
+///
+/// Capabilities are a per-thread attribute. A process is targeted
+/// through its main thread, whose TID equals the process PID.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Target {
+    /// The calling thread.
+    CurrentThread,
+    /// The thread with the given TID.
+    Thread(i32),
+    /// The main thread of the process with the given PID.
+    Process(i32),
+    /// The main thread of the process referred to by the given pidfd.
+    ///
+    /// The pidfd is resolved to a PID before each operation, and checked
+    /// again afterwards to make sure that the process did not exit in the
+    /// meantime (which would allow its PID to be recycled).
+    PidFd(i32),
+}
+
+impl Target {
+    /// Return the kernel identifier for this target (0 for current thread).
+    ///
+    /// Non-positive TIDs and PIDs are rejected with an `InvalidTarget` error.
+    pub fn tid(&self) -> Result<i32> {
+        match *self {
+            Target::CurrentThread => Ok(0),
+            Target::Thread(t) | Target::Process(t) if t > 0 => Ok(t),
+            Target::Thread(t) | Target::Process(t) => bail!(ErrorKind::InvalidTarget(t)),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Target::PidFd(fd) => pidfd::pid(fd),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Target::PidFd(_) => bail!(ErrorKind::Unsupported),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'a> From<&'a std::os::unix::io::OwnedFd> for Target {
+    fn from(fd: &'a std::os::unix::io::OwnedFd) -> Target {
+        use std::os::unix::io::AsRawFd;
+        Target::PidFd(fd.as_raw_fd())
+    }
+}
+
+impl From<Option<i32>> for Target {
+    /// Convert a legacy optional TID, where `None` and `Some(0)` mean
+    /// the current thread.
+    fn from(tid: Option<i32>) -> Target {
+        match tid {
+            None | Some(0) => Target::CurrentThread,
+            Some(t) => Target::Thread(t),
+        }
+    }
+}
+
+/// Privilege-escalation risk of a capability.
+///
+/// Risk levels are ordered, from the least to the most dangerous one.
+/// A capability is considered root-equivalent when it is known to be
+/// trivially escalated into full root privileges.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum Risk {
+    /// Narrowly scoped privilege, safe to grant in most cases.
+    Low,
+    /// Privilege that can degrade or observe other parts of the system.
+    Medium,
+    /// Privilege that can bypass significant security boundaries.
+    High,
+    /// Privilege that can be escalated into full root access.
+    RootEquivalent,
+}
+
 /// Linux capabilities.
 ///
 /// All capabilities supported by Linux, including standard
@@ -222,109 +481,397 @@ impl std::str::FromStr for Capability {
 
 impl Capability {
     /// Returns the bitmask corresponding to this capability value.
-    #[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn bitmask(&self) -> u64 {
         1u64 << (*self as u8)
     }
 
     /// Returns the index of this capability, i.e. its kernel-defined value.
-    #[cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn index(&self) -> u8 {
-        (*self as u8)
+        *self as u8
+    }
+
+    /// Returns the privilege-escalation risk of holding this capability.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn risk(&self) -> Risk {
+        match *self {
+            Capability::CAP_CHOWN
+            | Capability::CAP_DAC_OVERRIDE
+            | Capability::CAP_DAC_READ_SEARCH
+            | Capability::CAP_FOWNER
+            | Capability::CAP_SETGID
+            | Capability::CAP_SETUID
+            | Capability::CAP_SETFCAP
+            | Capability::CAP_SYS_MODULE
+            | Capability::CAP_SYS_RAWIO
+            | Capability::CAP_SYS_PTRACE
+            | Capability::CAP_SYS_ADMIN
+            | Capability::CAP_SYS_BOOT
+            | Capability::CAP_MKNOD
+            | Capability::CAP_MAC_ADMIN => Risk::RootEquivalent,
+            Capability::CAP_FSETID
+            | Capability::CAP_KILL
+            | Capability::CAP_SETPCAP
+            | Capability::CAP_LINUX_IMMUTABLE
+            | Capability::CAP_NET_ADMIN
+            | Capability::CAP_NET_RAW
+            | Capability::CAP_IPC_OWNER
+            | Capability::CAP_SYS_CHROOT
+            | Capability::CAP_SYS_TIME
+            | Capability::CAP_AUDIT_CONTROL
+            | Capability::CAP_MAC_OVERRIDE => Risk::High,
+            Capability::CAP_SYS_PACCT
+            | Capability::CAP_SYS_NICE
+            | Capability::CAP_SYS_RESOURCE
+            | Capability::CAP_SYS_TTY_CONFIG
+            | Capability::CAP_AUDIT_READ
+            | Capability::CAP_SYSLOG => Risk::Medium,
+            Capability::CAP_NET_BIND_SERVICE
+            | Capability::CAP_NET_BROADCAST
+            | Capability::CAP_IPC_LOCK
+            | Capability::CAP_LEASE
+            | Capability::CAP_AUDIT_WRITE
+            | Capability::CAP_WAKE_ALARM
+            | Capability::CAP_BLOCK_SUSPEND => Risk::Low,
+        }
     }
 }
 
 /// An `HashSet` specialized on `Capability`.
 pub type CapsHashSet = std::collections::HashSet<Capability>;
 
+/// Build a `CapsHashSet` from a list of capability names.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate caps;
+///
+/// fn main() {
+///     let set = caps![CAP_CHOWN, CAP_KILL];
+///     assert_eq!(set.len(), 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! caps {
+    ($($cap:ident),* $(,)*) => {{
+        let mut set = $crate::CapsHashSet::new();
+        $( set.insert($crate::Capability::$cap); )*
+        set
+    }};
+}
+
+/// Assert that the current thread holds a capability.
+///
+/// The Effective set is checked unless another one is given first. This
+/// panics if the capability is not held, or it can not be checked.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate caps;
+///
+/// fn main() {
+///     if caps::has_cap(None, caps::CapSet::Bounding, caps::Capability::CAP_CHOWN).unwrap() {
+///         assert_has_cap!(Bounding, CAP_CHOWN);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_has_cap {
+    ($cap:ident) => {
+        $crate::assert_has_cap!(Effective, $cap)
+    };
+    ($cset:ident, $cap:ident) => {{
+        let held = $crate::has_cap(None, $crate::CapSet::$cset, $crate::Capability::$cap);
+        if !held.unwrap_or(false) {
+            panic!(
+                "{} is not held in {} set",
+                stringify!($cap),
+                stringify!($cset)
+            );
+        }
+    }};
+}
+
+/// Assert that the current thread does not hold a capability.
+///
+/// The Effective set is checked unless another one is given first. This
+/// panics if the capability is held, or it can not be checked.
+#[macro_export]
+macro_rules! assert_no_cap {
+    ($cap:ident) => {
+        $crate::assert_no_cap!(Effective, $cap)
+    };
+    ($cset:ident, $cap:ident) => {{
+        let held = $crate::has_cap(None, $crate::CapSet::$cset, $crate::Capability::$cap);
+        if held.unwrap_or(true) {
+            panic!("{} is held in {} set", stringify!($cap), stringify!($cset));
+        }
+    }};
+}
+
+/// Same as `assert_has_cap!`, only checked in debug builds.
+#[macro_export]
+macro_rules! debug_assert_has_cap {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_has_cap!($($arg)*);
+        }
+    };
+}
+
+/// Same as `assert_no_cap!`, only checked in debug builds.
+#[macro_export]
+macro_rules! debug_assert_no_cap {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_no_cap!($($arg)*);
+        }
+    };
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Check if a thread contains a capability in a set.
 ///
-/// Check if set `cset` for thread `tid` contains capability `cap`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot check Ambient or Bounding capabilities of other processes.
-pub fn has_cap(tid: Option<i32>, cset: CapSet, cap: Capability) -> Result<bool> {
-    let t = tid.unwrap_or(0);
-    match cset {
-        CapSet::Ambient if t == 0 => ambient::has_cap(cap),
-        CapSet::Bounding if t == 0 => bounding::has_cap(cap),
-        CapSet::Effective | CapSet::Inheritable | CapSet::Permitted => base::has_cap(t, cset, cap),
-        _ => bail!("operation not supported"),
-    }
+/// Check if set `cset` for `target` contains capability `cap`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot check Ambient or Bounding capabilities of other threads.
+///
+/// If the underlying syscall is denied (e.g. by a seccomp filter), the
+/// set is read from procfs instead.
+pub fn has_cap<T: Into<Target>>(target: T, cset: CapSet, cap: Capability) -> Result<bool> {
+    on_target(target.into(), |t| {
+        let res = match cset {
+            CapSet::Ambient if t == 0 => ambient::has_cap(cap),
+            CapSet::Bounding if t == 0 => bounding::has_cap(cap),
+            CapSet::Effective | CapSet::Inheritable | CapSet::Permitted => {
+                base::has_cap(t, cset, cap)
+            }
+            _ => bail!("operation not supported"),
+        };
+        fallback::or_proc(res, t, cset, |set| set.contains(&cap))
+    })
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Return all capabilities in a set for a thread.
 ///
-/// Return current content of set `cset` for thread `tid`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot read Ambient or Bounding capabilities of other processes.
-pub fn read(tid: Option<i32>, cset: CapSet) -> Result<CapsHashSet> {
-    let t = tid.unwrap_or(0);
-    match cset {
-        CapSet::Ambient if t == 0 => ambient::read(),
-        CapSet::Bounding if t == 0 => bounding::read(),
-        CapSet::Effective | CapSet::Inheritable | CapSet::Permitted => base::read(t, cset),
-        _ => bail!("operation not supported"),
-    }
+/// Return current content of set `cset` for `target`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot read Ambient or Bounding capabilities of other threads.
+///
+/// If the underlying syscall is denied (e.g. by a seccomp filter), the
+/// set is read from procfs instead.
+pub fn read<T: Into<Target>>(target: T, cset: CapSet) -> Result<CapsHashSet> {
+    on_target(target.into(), |t| {
+        let res = match cset {
+            CapSet::Ambient if t == 0 => ambient::read(),
+            CapSet::Bounding if t == 0 => bounding::read(),
+            CapSet::Effective | CapSet::Inheritable | CapSet::Permitted => base::read(t, cset),
+            _ => bail!("operation not supported"),
+        };
+        fallback::or_proc(res, t, cset, |set| set.clone())
+    })
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Set a capability set for a thread to a new value.
 ///
-/// All and only capabilities in `value` will be set for set `cset` for thread `tid`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot manipulate Ambient set of other processes.
+/// All and only capabilities in `value` will be set for set `cset` for `target`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot manipulate Ambient set of other threads.
 /// Capabilities cannot be set in Bounding set.
-pub fn set(tid: Option<i32>, cset: CapSet, value: CapsHashSet) -> Result<()> {
-    let t = tid.unwrap_or(0);
-    match cset {
+pub fn set<T: Into<Target>>(target: T, cset: CapSet, value: CapsHashSet) -> Result<()> {
+    on_change("set", target.into(), cset, |t| match cset {
         CapSet::Ambient if t == 0 => ambient::set(&value),
         CapSet::Effective | CapSet::Inheritable | CapSet::Permitted => base::set(t, cset, value),
         _ => bail!("operation not supported"),
-    }
+    })
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Clear all capabilities in a set for a thread.
 ///
-/// All capabilities will be cleared from set `cset` for thread `tid`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot manipulate Ambient or Bounding set of other processes.
-pub fn clear(tid: Option<i32>, cset: CapSet) -> Result<()> {
-    let t = tid.unwrap_or(0);
-    match cset {
+/// All capabilities will be cleared from set `cset` for `target`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot manipulate Ambient or Bounding set of other threads.
+pub fn clear<T: Into<Target>>(target: T, cset: CapSet) -> Result<()> {
+    on_change("clear", target.into(), cset, |t| match cset {
         CapSet::Ambient if t == 0 => ambient::clear(),
         CapSet::Bounding if t == 0 => bounding::clear(),
         CapSet::Effective | CapSet::Permitted | CapSet::Inheritable => base::clear(t, cset),
         _ => bail!("operation not supported"),
-    }
+    })
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Raise a single capability in a set for a thread.
 ///
-/// Capabilities `cap` will be raised from set `cset` of thread `tid`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot manipulate Ambient set of other processes.
+/// Capabilities `cap` will be raised from set `cset` of `target`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot manipulate Ambient set of other threads.
 /// Capabilities cannot be raised in Bounding set.
-pub fn raise(tid: Option<i32>, cset: CapSet, cap: Capability) -> Result<()> {
-    let t = tid.unwrap_or(0);
-    match cset {
+pub fn raise<T: Into<Target>>(target: T, cset: CapSet, cap: Capability) -> Result<()> {
+    on_change("raise", target.into(), cset, |t| match cset {
         CapSet::Ambient if t == 0 => ambient::raise(cap),
         CapSet::Effective | CapSet::Permitted | CapSet::Inheritable => base::raise(t, cset, cap),
         _ => bail!("operation not supported"),
-    }
+    })
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
 /// Drop a single capability from a set for a thread.
 ///
-/// Capabilities `cap` will be dropped from set `cset` of thread `tid`.
-/// If `tid` is `None`, this operates on current thread (tid=0).
-/// It cannot manipulate Ambient and Bounding sets of other processes.
-pub fn drop(tid: Option<i32>, cset: CapSet, cap: Capability) -> Result<()> {
-    let t = tid.unwrap_or(0);
-    match cset {
+/// Capabilities `cap` will be dropped from set `cset` of `target`.
+/// Passing `None` as `target` operates on current thread.
+/// It cannot manipulate Ambient and Bounding sets of other threads.
+pub fn drop<T: Into<Target>>(target: T, cset: CapSet, cap: Capability) -> Result<()> {
+    on_change("drop", target.into(), cset, |t| match cset {
         CapSet::Ambient if t == 0 => ambient::drop(cap),
         CapSet::Bounding if t == 0 => bounding::drop(cap),
         CapSet::Effective | CapSet::Permitted | CapSet::Inheritable => base::drop(t, cset, cap),
         _ => bail!("operation not supported"),
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Set a capability set for a thread, then verify the result.
+///
+/// The set is read back after being set, and an `ErrorKind::StateMismatch`
+/// error is returned if it does not exactly match `value` (e.g. if the
+/// kernel silently ignored part of the request).
+pub fn set_strict<T: Into<Target>>(target: T, cset: CapSet, value: CapsHashSet) -> Result<()> {
+    let target = target.into();
+    set(target, cset, value.clone())?;
+    match check_applied(cset, &value, &read(target, cset)?) {
+        Some(mismatch) => bail!(ErrorKind::StateMismatch(StateMismatch {
+            sets: vec![mismatch]
+        })),
+        None => Ok(()),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Converge a capability set for a thread to a desired value.
+///
+/// The current content of set `cset` for `target` is read first, and
+/// syscalls are only issued if it differs from `desired`. This returns
+/// whether changes were made, which allows reconcilers to run it
+/// repeatedly. As the Bounding set can only be lowered, raising
+/// capabilities in it fails with `ErrorKind::InvalidTransition`.
+pub fn ensure<T: Into<Target>>(target: T, cset: CapSet, desired: &CapsHashSet) -> Result<bool> {
+    let target = target.into();
+    let current = read(target, cset)?;
+    if current == *desired {
+        return Ok(false);
+    }
+    if cset == CapSet::Bounding {
+        let raised = sorted(desired.difference(&current));
+        if !raised.is_empty() {
+            let reason = format!("cannot raise {:?} in bounding set", raised);
+            bail!(ErrorKind::InvalidTransition(reason));
+        }
+        for cap in current.difference(desired) {
+            drop(target, cset, *cap)?;
+        }
+    } else {
+        set(target, cset, desired.clone())?;
+    }
+    Ok(true)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+// Compare an applied capability set with the requested one.
+pub(crate) fn check_applied(
+    cset: CapSet,
+    requested: &CapsHashSet,
+    actual: &CapsHashSet,
+) -> Option<SetMismatch> {
+    if requested == actual {
+        return None;
+    }
+    let missing = sorted(requested.difference(actual));
+    let unexpected = sorted(actual.difference(requested));
+    Some(SetMismatch {
+        set: cset,
+        missing,
+        unexpected,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+// Run `f` on the kernel identifier of `target`, verifying before and
+// afterwards that a pidfd target still refers to the same process.
+fn on_target<R, F>(target: Target, f: F) -> Result<R>
+where
+    F: FnOnce(i32) -> Result<R>,
+{
+    let check_alive = || -> Result<()> {
+        if let Target::PidFd(fd) = target {
+            if !pidfd::is_alive(fd)? {
+                bail!(ErrorKind::InvalidTarget(fd));
+            }
+        }
+        Ok(())
+    };
+    let tid = target.tid()?;
+    check_alive()?;
+    let res = f(tid)?;
+    check_alive()?;
+    Ok(res)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+// Run `on_target` for change `op` of set `cset`, notifying observers.
+fn on_change<F>(op: &'static str, target: Target, cset: CapSet, f: F) -> Result<()>
+where
+    F: FnOnce(i32) -> Result<()>,
+{
+    trace::change(op, target, Some(cset), || {
+        if !observe::is_active() {
+            return on_target(target, f);
+        }
+        let before = read(target, cset).ok();
+        on_target(target, f)?;
+        if let (Some(before), Ok(after)) = (before, read(target, cset)) {
+            observe::notify(&CapsDiff::new(target, cset, &before, &after));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Drop all capabilities except the ones in `keep`, for current thread.
+///
+/// Capabilities not in `keep` are removed from all sets (Bounding,
+/// Ambient, Inheritable, Effective and Permitted). Dropping from the
+/// Bounding set requires `CAP_SETPCAP`, thus it is performed first.
+/// Capabilities unknown to this crate but supported by the running kernel
+/// are dropped as well.
+pub fn drop_all_except(keep: &CapsHashSet) -> Result<()> {
+    let kept = to_bitmask(keep);
+    for index in 0..64 {
+        if (kept & (1u64 << index)) != 0 || !lowlevel::bounding_read(index) {
+            continue;
+        }
+        match lowlevel::bounding_drop(index) {
+            Ok(()) => {}
+            // Past the last capability of the running kernel.
+            Err(e) if e.0 == sys::EINVAL => break,
+            Err(e) => {
+                return Err(
+                    Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "PR_CAPBSET_DROP error")
+                )
+            }
+        }
+    }
+    let amb = ambient::read()?;
+    ambient::set(&amb.intersection(keep).cloned().collect())?;
+    for cset in &[CapSet::Inheritable, CapSet::Effective, CapSet::Permitted] {
+        let cur = base::read(0, *cset)?;
+        base::set(0, *cset, cur.intersection(keep).cloned().collect())?;
     }
+    Ok(())
 }
 
 /// Return an `HashSet` with all known capabilities.
@@ -372,6 +919,34 @@ pub fn all() -> CapsHashSet {
     CapsHashSet::from_iter(slice)
 }
 
+// All known capabilities, in kernel order.
+#[cfg(any(feature = "arbitrary", feature = "clap", feature = "proptest"))]
+pub(crate) fn all_sorted() -> Vec<Capability> {
+    sorted(&all())
+}
+
+// Capabilities of `caps`, in kernel order.
+pub(crate) fn sorted<'a, I: IntoIterator<Item = &'a Capability>>(caps: I) -> Vec<Capability> {
+    let mut res: Vec<_> = caps.into_iter().cloned().collect();
+    res.sort_by_key(|c| c.index());
+    res
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+// Convert a raw kernel bitmask into a set of known capabilities.
+pub(crate) fn from_bitmask(mask: u64) -> CapsHashSet {
+    all()
+        .into_iter()
+        .filter(|c| (mask & c.bitmask()) != 0)
+        .collect()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+// Convert a set of capabilities into a raw kernel bitmask.
+pub(crate) fn to_bitmask(set: &CapsHashSet) -> u64 {
+    set.iter().fold(0, |acc, c| acc | c.bitmask())
+}
+
 /// Convert an informal capability name into a canonical form.
 ///
 /// This converts the input string to uppercase and ensures that it starts with
@@ -392,7 +967,7 @@ pub fn to_canonical(s: &str) -> String {
 #[test]
 fn test_all_roundtrip() {
     let all = all();
-    assert!(all.len() > 0);
+    assert!(!all.is_empty());
     for c in all {
         let name = c.to_string();
         let parsed: Capability = name.parse().unwrap();
@@ -409,6 +984,23 @@ fn test_parse_invalid() {
     assert!(p2.is_err());
 }
 
+#[test]
+fn test_risk() {
+    assert_eq!(Capability::CAP_SYS_ADMIN.risk(), Risk::RootEquivalent);
+    assert_eq!(Capability::CAP_NET_BIND_SERVICE.risk(), Risk::Low);
+    assert!(Risk::RootEquivalent > Risk::High);
+    assert!(Risk::Medium > Risk::Low);
+}
+
+#[test]
+fn test_target() {
+    assert_eq!(Target::from(None).tid().unwrap(), 0);
+    assert_eq!(Target::from(Some(0)), Target::CurrentThread);
+    assert_eq!(Target::Process(1).tid().unwrap(), 1);
+    assert!(Target::Thread(-1).tid().is_err());
+    assert!(Target::Process(0).tid().is_err());
+}
+
 #[test]
 fn test_to_canonical() {
     use std::str::FromStr;
@@ -417,3 +1009,32 @@ fn test_to_canonical() {
     let p2 = "sys_admin";
     assert!(Capability::from_str(&to_canonical(p2)).is_ok());
 }
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn test_check_applied() {
+    let requested = caps![CAP_CHOWN, CAP_KILL];
+    assert_eq!(
+        check_applied(CapSet::Effective, &requested, &requested),
+        None
+    );
+    let mismatch =
+        check_applied(CapSet::Effective, &requested, &caps![CAP_CHOWN, CAP_SYSLOG]).unwrap();
+    assert_eq!(mismatch.set, CapSet::Effective);
+    assert_eq!(mismatch.missing, vec![Capability::CAP_KILL]);
+    assert_eq!(mismatch.unexpected, vec![Capability::CAP_SYSLOG]);
+}
+
+#[test]
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn test_unsupported() {
+    let err = read(None, CapSet::Effective).unwrap_err();
+    match *err.kind() {
+        ErrorKind::Unsupported => {}
+        ref k => panic!("unexpected error: {:?}", k),
+    }
+    assert!(has_cap(None, CapSet::Permitted, Capability::CAP_CHOWN).is_err());
+    // Types and parsing still work.
+    let cap: Capability = to_canonical("chown").parse().unwrap();
+    assert_eq!(cap, Capability::CAP_CHOWN);
+}
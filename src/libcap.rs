@@ -0,0 +1,226 @@
+//! Interoperability with libcap's `cap_t`.
+//!
+//! Codebases migrating away from libcap may still call into it for some
+//! operations. `CapT` owns a libcap capability state and converts it from
+//! and into a `CapState`, or from and into libcap's text representation
+//! (as used by `cap_from_text(3)` and `setcap(8)`).
+//!
+//! libcap (`libcap.so.2`) is loaded at runtime, so that building does not
+//! require its development files: the first conversion fails if it can
+//! not be loaded. As the library is shared with the rest of the process,
+//! `cap_t` values can be passed to and from other libcap callers.
+//!
+//! A `cap_t` only holds the Effective, Inheritable and Permitted sets.
+
+use errno;
+use libc::{self, c_char, c_int, c_void, ssize_t};
+
+use super::{all, CapSet, CapState, Capability};
+use errors::*;
+use std::ffi::{CStr, CString};
+use std::sync::OnceLock;
+
+/// Raw libcap capability state.
+#[allow(non_camel_case_types)]
+pub type cap_t = *mut c_void;
+
+// Values of `cap_flag_t` and `cap_flag_value_t`.
+const CAP_EFFECTIVE: c_int = 0;
+const CAP_PERMITTED: c_int = 1;
+const CAP_INHERITABLE: c_int = 2;
+const CAP_CLEAR: c_int = 0;
+const CAP_SET: c_int = 1;
+
+// libcap entry points, resolved at runtime.
+struct Lib {
+    cap_init: unsafe extern "C" fn() -> cap_t,
+    cap_free: unsafe extern "C" fn(*mut c_void) -> c_int,
+    cap_get_flag: unsafe extern "C" fn(cap_t, c_int, c_int, *mut c_int) -> c_int,
+    cap_set_flag: unsafe extern "C" fn(cap_t, c_int, c_int, *const c_int, c_int) -> c_int,
+    cap_to_text: unsafe extern "C" fn(cap_t, *mut ssize_t) -> *mut c_char,
+    cap_from_text: unsafe extern "C" fn(*const c_char) -> cap_t,
+}
+
+static LIB: OnceLock<::std::result::Result<Lib, String>> = OnceLock::new();
+
+fn lib() -> Result<&'static Lib> {
+    match *LIB.get_or_init(|| unsafe { load() }) {
+        Ok(ref lib) => Ok(lib),
+        Err(ref e) => bail!("failed to load libcap: {}", e),
+    }
+}
+
+// Symbols are transmuted into the type of their `Lib` field.
+#[allow(clippy::missing_transmute_annotations)]
+unsafe fn load() -> ::std::result::Result<Lib, String> {
+    let handle = libc::dlopen("libcap.so.2\0".as_ptr() as *const c_char, libc::RTLD_NOW);
+    if handle.is_null() {
+        return Err(dlerror());
+    }
+    macro_rules! sym {
+        ($name:ident) => {{
+            let sym = libc::dlsym(
+                handle,
+                concat!(stringify!($name), "\0").as_ptr() as *const c_char,
+            );
+            if sym.is_null() {
+                return Err(dlerror());
+            }
+            ::std::mem::transmute::<*mut c_void, _>(sym)
+        }};
+    }
+    Ok(Lib {
+        cap_init: sym!(cap_init),
+        cap_free: sym!(cap_free),
+        cap_get_flag: sym!(cap_get_flag),
+        cap_set_flag: sym!(cap_set_flag),
+        cap_to_text: sym!(cap_to_text),
+        cap_from_text: sym!(cap_from_text),
+    })
+}
+
+unsafe fn dlerror() -> String {
+    let e = libc::dlerror();
+    if e.is_null() {
+        "unknown error".to_string()
+    } else {
+        CStr::from_ptr(e).to_string_lossy().into_owned()
+    }
+}
+
+fn sys_error(call: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| format!("{} error", call))
+}
+
+// Sets held by a `cap_t`, with their libcap flag.
+const FLAGS: [(CapSet, c_int); 3] = [
+    (CapSet::Effective, CAP_EFFECTIVE),
+    (CapSet::Inheritable, CAP_INHERITABLE),
+    (CapSet::Permitted, CAP_PERMITTED),
+];
+
+/// Owned libcap capability state, released with `cap_free` on drop.
+#[derive(Debug)]
+pub struct CapT {
+    raw: cap_t,
+}
+
+impl CapT {
+    /// Allocate an empty state (`cap_init`).
+    pub fn new() -> Result<CapT> {
+        let raw = unsafe { (lib()?.cap_init)() };
+        if raw.is_null() {
+            return Err(sys_error("cap_init"));
+        }
+        Ok(CapT { raw })
+    }
+
+    /// Convert the Effective, Inheritable and Permitted sets of `state`.
+    pub fn from_state(state: &CapState) -> Result<CapT> {
+        let lib = lib()?;
+        let res = CapT::new()?;
+        for &(cset, flag) in &FLAGS {
+            let values: Vec<c_int> = state.get(cset).iter().map(|c| c.index() as c_int).collect();
+            if values.is_empty() {
+                continue;
+            }
+            let ret = unsafe {
+                (lib.cap_set_flag)(
+                    res.raw,
+                    flag,
+                    values.len() as c_int,
+                    values.as_ptr(),
+                    CAP_SET,
+                )
+            };
+            if ret != 0 {
+                return Err(sys_error("cap_set_flag"));
+            }
+        }
+        Ok(res)
+    }
+
+    /// Convert into a `CapState`, with empty Ambient and Bounding sets.
+    ///
+    /// Capabilities unknown to the loaded libcap are never set.
+    pub fn to_state(&self) -> Result<CapState> {
+        let lib = lib()?;
+        let mut state = CapState::default();
+        for &(cset, flag) in &FLAGS {
+            for cap in all() {
+                if self.get_flag(lib, cap, flag)? {
+                    state.get_mut(cset).insert(cap);
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Parse libcap's text representation (`cap_from_text`), e.g. `"cap_chown=ep"`.
+    pub fn from_text(text: &str) -> Result<CapT> {
+        let lib = lib()?;
+        let text = CString::new(text).chain_err(|| format!("invalid text {:?}", text))?;
+        let raw = unsafe { (lib.cap_from_text)(text.as_ptr()) };
+        if raw.is_null() {
+            return Err(sys_error("cap_from_text"));
+        }
+        Ok(CapT { raw })
+    }
+
+    /// Format as libcap's text representation (`cap_to_text`).
+    pub fn to_text(&self) -> Result<String> {
+        let lib = lib()?;
+        let text = unsafe { (lib.cap_to_text)(self.raw, ::std::ptr::null_mut()) };
+        if text.is_null() {
+            return Err(sys_error("cap_to_text"));
+        }
+        let res = unsafe { CStr::from_ptr(text) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { (lib.cap_free)(text as *mut c_void) };
+        Ok(res)
+    }
+
+    /// Take ownership of a state allocated by libcap.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a valid `cap_t`, which must not be freed elsewhere.
+    /// libcap must be loadable for it to be freed on drop.
+    pub unsafe fn from_raw(raw: cap_t) -> CapT {
+        CapT { raw }
+    }
+
+    /// Return the raw state, still owned by `self`.
+    pub fn as_raw(&self) -> cap_t {
+        self.raw
+    }
+
+    /// Release ownership of the raw state, to be freed with `cap_free`.
+    pub fn into_raw(self) -> cap_t {
+        let raw = self.raw;
+        ::std::mem::forget(self);
+        raw
+    }
+
+    fn get_flag(&self, lib: &Lib, cap: Capability, flag: c_int) -> Result<bool> {
+        let mut value = CAP_CLEAR;
+        let ret = unsafe { (lib.cap_get_flag)(self.raw, cap.index() as c_int, flag, &mut value) };
+        // Capabilities newer than libcap are rejected, and thus not set.
+        if ret != 0 && errno::errno().0 == libc::EINVAL {
+            return Ok(false);
+        }
+        if ret != 0 {
+            return Err(sys_error("cap_get_flag"));
+        }
+        Ok(value == CAP_SET)
+    }
+}
+
+impl Drop for CapT {
+    fn drop(&mut self) {
+        if let Ok(lib) = lib() {
+            unsafe { (lib.cap_free)(self.raw) };
+        }
+    }
+}
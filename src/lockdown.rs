@@ -0,0 +1,256 @@
+//! Irreversible lockdown of capability changes via seccomp.
+//!
+//! Dropping capabilities is only as strong as the guarantee that they
+//! cannot be raised back: a compromised process keeping some permitted
+//! capabilities can still `capset` them into its effective set, or raise
+//! them in the ambient set before an exec. `deny_future_capset` installs
+//! a minimal seccomp filter forbidding such changes, to be called right
+//! after the final privilege drop.
+//!
+//! This module requires the `lockdown` feature.
+
+use errno;
+use libc;
+
+use errors::*;
+use nr;
+use securebits;
+
+// Audit architecture of the native ABI, from <linux/audit.h>.
+#[cfg(target_arch = "x86")]
+const AUDIT_ARCH: u32 = 0x4000_0003;
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "arm")]
+const AUDIT_ARCH: u32 = 0x4000_0028;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+#[cfg(target_arch = "powerpc")]
+const AUDIT_ARCH: u32 = 0x0000_0014;
+#[cfg(all(target_arch = "powerpc64", target_endian = "big"))]
+const AUDIT_ARCH: u32 = 0x8000_0015;
+#[cfg(all(target_arch = "powerpc64", target_endian = "little"))]
+const AUDIT_ARCH: u32 = 0xc000_0015;
+#[cfg(all(any(target_arch = "mips", target_arch = "mips32r6"), target_endian = "big"))]
+const AUDIT_ARCH: u32 = 0x0000_0008;
+#[cfg(all(any(target_arch = "mips", target_arch = "mips32r6"), target_endian = "little"))]
+const AUDIT_ARCH: u32 = 0x4000_0008;
+#[cfg(all(any(target_arch = "mips64", target_arch = "mips64r6"), target_endian = "big"))]
+const AUDIT_ARCH: u32 = 0x8000_0008;
+#[cfg(all(any(target_arch = "mips64", target_arch = "mips64r6"), target_endian = "little"))]
+const AUDIT_ARCH: u32 = 0xc000_0008;
+#[cfg(target_arch = "riscv32")]
+const AUDIT_ARCH: u32 = 0x4000_00f3;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+#[cfg(target_arch = "loongarch64")]
+const AUDIT_ARCH: u32 = 0xc000_0102;
+#[cfg(target_arch = "s390x")]
+const AUDIT_ARCH: u32 = 0x8000_0016;
+#[cfg(target_arch = "sparc64")]
+const AUDIT_ARCH: u32 = 0x8000_002b;
+
+// BPF opcodes, from <linux/bpf_common.h> (not bound by libc on all targets).
+const BPF_LD: u16 = 0x00;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_W: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_JEQ: u16 = 0x10;
+#[cfg(target_arch = "x86_64")]
+const BPF_JGE: u16 = 0x30;
+const BPF_K: u16 = 0x00;
+
+// Offsets of `struct seccomp_data` fields.
+const DATA_NR: u32 = 0;
+const DATA_ARCH: u32 = 4;
+#[cfg(target_endian = "little")]
+const DATA_ARG0: u32 = 16;
+#[cfg(target_endian = "big")]
+const DATA_ARG0: u32 = 20;
+const DATA_ARG1: u32 = DATA_ARG0 + 8;
+
+// Start of x32 syscall numbers, sharing the x86_64 audit architecture.
+#[cfg(target_arch = "x86_64")]
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+/// Forbid further capability changes in the whole process.
+///
+/// The filter makes the following calls fail with `EPERM`, in all threads
+/// of the process and their future children:
+///  * `capset(2)`,
+///  * `prctl(2)` with `PR_CAP_AMBIENT_RAISE`, `PR_SET_KEEPCAPS` or
+///    `PR_SET_SECUREBITS`,
+///  * all syscalls issued through a foreign ABI (such as 32-bit syscalls
+///    on a 64-bit kernel), which the filter cannot inspect.
+///
+/// Reading capabilities and shrinking the bounding and ambient sets keep
+/// working. As required by the kernel, "no new privileges" is set first.
+/// Seccomp filters cannot be removed, hence this is irreversible.
+pub fn deny_future_capset() -> Result<()> {
+    securebits::set_no_new_privs()?;
+    let mut filter = program();
+    let prog = libc::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_mut_ptr(),
+    };
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            libc::SECCOMP_SET_MODE_FILTER,
+            libc::SECCOMP_FILTER_FLAG_TSYNC,
+            &prog as *const libc::sock_fprog,
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        -1 => Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "seccomp error")),
+        tid => bail!(
+            "thread {} cannot be synchronized to the seccomp filter",
+            tid
+        ),
+    }
+}
+
+// Build the BPF program of the filter.
+fn program() -> Vec<libc::sock_filter> {
+    let deny = libc::SECCOMP_RET_ERRNO | (libc::EPERM as u32 & libc::SECCOMP_RET_DATA);
+    let allow = libc::SECCOMP_RET_ALLOW;
+    let mut prog = vec![
+        load(DATA_ARCH),
+        jump(BPF_JEQ, AUDIT_ARCH, 1, 0),
+        ret(deny),
+        load(DATA_NR),
+    ];
+    #[cfg(target_arch = "x86_64")]
+    prog.extend_from_slice(&abi_check(cfg!(target_pointer_width = "32"), deny));
+    prog.extend_from_slice(&[
+        jump(BPF_JEQ, nr::CAPSET as u32, 0, 1),
+        ret(deny),
+        jump(BPF_JEQ, libc::SYS_prctl as u32, 1, 0),
+        ret(allow),
+        load(DATA_ARG0),
+        jump(BPF_JEQ, nr::PR_SET_KEEPCAPS as u32, 4, 0),
+        jump(BPF_JEQ, nr::PR_SET_SECUREBITS as u32, 3, 0),
+        jump(BPF_JEQ, nr::PR_CAP_AMBIENT as u32, 0, 3),
+        load(DATA_ARG1),
+        jump(BPF_JEQ, nr::PR_CAP_AMBIENT_RAISE as u32, 0, 1),
+        ret(deny),
+        ret(allow),
+    ]);
+    prog
+}
+
+// x86_64 and x32 syscalls share an audit architecture, x32 numbers
+// having X32_SYSCALL_BIT set: deny those of the other ABI.
+#[cfg(target_arch = "x86_64")]
+fn abi_check(x32: bool, deny: u32) -> [libc::sock_filter; 2] {
+    let (jt, jf) = if x32 { (1, 0) } else { (0, 1) };
+    [jump(BPF_JGE, X32_SYSCALL_BIT, jt, jf), ret(deny)]
+}
+
+fn load(offset: u32) -> libc::sock_filter {
+    stmt(BPF_LD | BPF_W | BPF_ABS, offset)
+}
+
+fn ret(value: u32) -> libc::sock_filter {
+    stmt(BPF_RET | BPF_K, value)
+}
+
+fn stmt(code: u16, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+// Jump `jt` instructions ahead if the accumulator matches `k` per `op`,
+// `jf` otherwise.
+fn jump(op: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: BPF_JMP | op | BPF_K,
+        jt,
+        jf,
+        k,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DENY: u32 = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+
+    // Run `prog` against a syscall, as the kernel would.
+    fn run(prog: &[libc::sock_filter], arch: u32, nr: u32, args: [u32; 2]) -> u32 {
+        let (mut pc, mut acc) = (0, 0);
+        loop {
+            let insn = prog[pc];
+            pc += 1;
+            match insn.code {
+                c if c == BPF_LD | BPF_W | BPF_ABS => {
+                    acc = match insn.k {
+                        DATA_NR => nr,
+                        DATA_ARCH => arch,
+                        DATA_ARG0 => args[0],
+                        DATA_ARG1 => args[1],
+                        k => panic!("unexpected offset {}", k),
+                    }
+                }
+                c if c == BPF_RET | BPF_K => return insn.k,
+                c => {
+                    let taken = match c & !(BPF_JMP | BPF_K) {
+                        BPF_JEQ => acc == insn.k,
+                        #[cfg(target_arch = "x86_64")]
+                        BPF_JGE => acc >= insn.k,
+                        op => panic!("unexpected opcode {:#x}", op),
+                    };
+                    pc += if taken { insn.jt } else { insn.jf } as usize;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_program() {
+        let prog = program();
+        let allow = libc::SECCOMP_RET_ALLOW;
+        let capset = nr::CAPSET as u32;
+        let prctl = libc::SYS_prctl as u32;
+        let getpid = libc::SYS_getpid as u32;
+        assert_eq!(run(&prog, AUDIT_ARCH, capset, [0, 0]), DENY);
+        assert_eq!(run(&prog, AUDIT_ARCH, getpid, [0, 0]), allow);
+        assert_eq!(run(&prog, !AUDIT_ARCH, getpid, [0, 0]), DENY);
+        let ambient = nr::PR_CAP_AMBIENT as u32;
+        let raise = [ambient, nr::PR_CAP_AMBIENT_RAISE as u32];
+        let lower = [ambient, nr::PR_CAP_AMBIENT_LOWER as u32];
+        assert_eq!(run(&prog, AUDIT_ARCH, prctl, raise), DENY);
+        assert_eq!(run(&prog, AUDIT_ARCH, prctl, lower), allow);
+        let keepcaps = [nr::PR_SET_KEEPCAPS as u32, 1];
+        assert_eq!(run(&prog, AUDIT_ARCH, prctl, keepcaps), DENY);
+        let securebits = [nr::PR_SET_SECUREBITS as u32, 0];
+        assert_eq!(run(&prog, AUDIT_ARCH, prctl, securebits), DENY);
+        let bounding = [nr::PR_CAPBSET_DROP as u32, 0];
+        assert_eq!(run(&prog, AUDIT_ARCH, prctl, bounding), allow);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_abi_check() {
+        let allow = libc::SECCOMP_RET_ALLOW;
+        let prog = |x32| {
+            let mut prog = vec![load(DATA_NR)];
+            prog.extend_from_slice(&abi_check(x32, DENY));
+            prog.push(ret(allow));
+            prog
+        };
+        // Native capset and x32 capset, respectively.
+        let (x86_64, x32) = (126, X32_SYSCALL_BIT + 126);
+        assert_eq!(run(&prog(false), AUDIT_ARCH, x86_64, [0, 0]), allow);
+        assert_eq!(run(&prog(false), AUDIT_ARCH, x32, [0, 0]), DENY);
+        assert_eq!(run(&prog(true), AUDIT_ARCH, x86_64, [0, 0]), DENY);
+        assert_eq!(run(&prog(true), AUDIT_ARCH, x32, [0, 0]), allow);
+    }
+}
@@ -0,0 +1,211 @@
+//! Async-signal-safe capability operations.
+//!
+//! Functions in this module perform no allocation, no locking and no
+//! formatting: they work on raw bitmasks (see `Capability::bitmask`) and
+//! capability indices (see `Capability::index`), and report failures as
+//! plain `errno` values. This makes them safe to use where the rest of
+//! this crate is not, e.g. in signal handlers or between `fork` and `exec`
+//! (via `std::os::unix::process::CommandExt::pre_exec`).
+//!
+//! All functions operate on the calling thread.
+
+use caps_core;
+
+use super::{to_bitmask, CapState};
+use errors::Errno;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::result;
+#[cfg(feature = "libc")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use sys::Core;
+
+/// Result of low-level operations.
+pub type Result<T> = result::Result<T, Errno>;
+
+/// Whole capability state of a thread, as raw bitmasks.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Masks {
+    /// Ambient capabilities set, one bit per capability index.
+    pub ambient: u64,
+    /// Bounding capabilities set, one bit per capability index.
+    pub bounding: u64,
+    /// Effective capabilities set, one bit per capability index.
+    pub effective: u64,
+    /// Inheritable capabilities set, one bit per capability index.
+    pub inheritable: u64,
+    /// Permitted capabilities set, one bit per capability index.
+    pub permitted: u64,
+}
+
+impl<'a> From<&'a CapState> for Masks {
+    fn from(state: &'a CapState) -> Masks {
+        Masks {
+            ambient: to_bitmask(&state.ambient),
+            bounding: to_bitmask(&state.bounding),
+            effective: to_bitmask(&state.effective),
+            inheritable: to_bitmask(&state.inheritable),
+            permitted: to_bitmask(&state.permitted),
+        }
+    }
+}
+
+impl Masks {
+    /// Read the whole capability state of the calling thread.
+    pub fn current() -> Result<Masks> {
+        let (effective, permitted, inheritable) = capget()?;
+        Ok(Masks {
+            ambient: caps_core::ambient_mask(&Core),
+            bounding: caps_core::bounding_mask(&Core),
+            effective,
+            inheritable,
+            permitted,
+        })
+    }
+
+    /// Restrict this state to capabilities in `allowed`, set by set.
+    ///
+    /// The result is consistent: effective and ambient capabilities are
+    /// also restricted to the resulting permitted (and inheritable) sets.
+    pub fn restrict(&self, allowed: &Masks) -> Masks {
+        let permitted = self.permitted & allowed.permitted;
+        let inheritable = self.inheritable & allowed.inheritable;
+        Masks {
+            ambient: self.ambient & allowed.ambient & permitted & inheritable,
+            bounding: self.bounding & allowed.bounding,
+            effective: self.effective & allowed.effective & permitted,
+            inheritable,
+            permitted,
+        }
+    }
+
+    /// Apply this state to the calling thread.
+    ///
+    /// As for `CapState::apply`, capabilities are dropped from the bounding
+    /// set first and raised in the ambient set last.
+    pub fn apply(&self) -> Result<()> {
+        for index in 0..64 {
+            if bounding_read(index) && (self.bounding & (1u64 << index)) == 0 {
+                bounding_drop(index)?;
+            }
+        }
+        ambient_clear()?;
+        capset(self.effective, self.permitted, self.inheritable)?;
+        for index in 0..64 {
+            if (self.ambient & (1u64 << index)) != 0 {
+                ambient_raise(index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read (effective, permitted, inheritable) masks of the calling thread.
+pub fn capget() -> Result<(u64, u64, u64)> {
+    caps_core::capget(&Core).map_err(errno)
+}
+
+/// Set effective, permitted and inheritable masks of the calling thread.
+pub fn capset(effective: u64, permitted: u64, inheritable: u64) -> Result<()> {
+    caps_core::capset(&Core, effective, permitted, inheritable).map_err(errno)
+}
+
+/// Check whether capability `index` is in the bounding set.
+///
+/// Unknown capabilities are reported as not held.
+pub fn bounding_read(index: u8) -> bool {
+    caps_core::bounding_read(&Core, index)
+}
+
+/// Drop capability `index` from the bounding set.
+pub fn bounding_drop(index: u8) -> Result<()> {
+    caps_core::bounding_drop(&Core, index).map_err(errno)
+}
+
+/// Check whether capability `index` is in the ambient set.
+///
+/// Unknown capabilities are reported as not held.
+pub fn ambient_read(index: u8) -> bool {
+    caps_core::ambient_read(&Core, index)
+}
+
+/// Raise capability `index` in the ambient set.
+pub fn ambient_raise(index: u8) -> Result<()> {
+    caps_core::ambient_raise(&Core, index).map_err(errno)
+}
+
+/// Lower capability `index` in the ambient set.
+pub fn ambient_lower(index: u8) -> Result<()> {
+    caps_core::ambient_lower(&Core, index).map_err(errno)
+}
+
+/// Clear the ambient set.
+pub fn ambient_clear() -> Result<()> {
+    caps_core::ambient_clear(&Core).map_err(errno)
+}
+
+/// Read the securebits of the calling thread as a raw value.
+pub fn get_securebits() -> Result<u32> {
+    caps_core::get_securebits(&Core).map_err(errno)
+}
+
+/// Set the securebits of the calling thread to the raw value `bits`.
+pub fn set_securebits(bits: u32) -> Result<()> {
+    caps_core::set_securebits(&Core, bits).map_err(errno)
+}
+
+/// Set the "keep capabilities" flag of the calling thread.
+pub fn set_keepcaps(keep: bool) -> Result<()> {
+    caps_core::set_keepcaps(&Core, keep).map_err(errno)
+}
+
+/// Set the `no_new_privs` attribute of the calling thread.
+pub fn set_no_new_privs() -> Result<()> {
+    caps_core::set_no_new_privs(&Core).map_err(errno)
+}
+
+fn errno(e: caps_core::Errno) -> Errno {
+    Errno(e.0)
+}
+
+// `Masks` which can be shared with signal handlers.
+#[cfg(feature = "libc")]
+pub(crate) struct AtomicMasks {
+    ambient: AtomicU64,
+    bounding: AtomicU64,
+    effective: AtomicU64,
+    inheritable: AtomicU64,
+    permitted: AtomicU64,
+}
+
+#[cfg(feature = "libc")]
+impl AtomicMasks {
+    pub(crate) const fn new() -> AtomicMasks {
+        AtomicMasks {
+            ambient: AtomicU64::new(0),
+            bounding: AtomicU64::new(0),
+            effective: AtomicU64::new(0),
+            inheritable: AtomicU64::new(0),
+            permitted: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn store(&self, masks: Masks) {
+        self.ambient.store(masks.ambient, Ordering::SeqCst);
+        self.bounding.store(masks.bounding, Ordering::SeqCst);
+        self.effective.store(masks.effective, Ordering::SeqCst);
+        self.inheritable.store(masks.inheritable, Ordering::SeqCst);
+        self.permitted.store(masks.permitted, Ordering::SeqCst);
+    }
+
+    pub(crate) fn load(&self) -> Masks {
+        Masks {
+            ambient: self.ambient.load(Ordering::SeqCst),
+            bounding: self.bounding.load(Ordering::SeqCst),
+            effective: self.effective.load(Ordering::SeqCst),
+            inheritable: self.inheritable.load(Ordering::SeqCst),
+            permitted: self.permitted.load(Ordering::SeqCst),
+        }
+    }
+}
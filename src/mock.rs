@@ -0,0 +1,160 @@
+//! In-memory privilege backend for tests.
+//!
+//! `MockBackend` implements `PrivilegeBackend` without touching the real
+//! thread state, and records all requested operations. Code written
+//! against the backend trait can then be unit-tested unprivileged:
+//!
+//! ```rust
+//! use caps::backend::PrivilegeBackend;
+//! use caps::mock::{MockBackend, Op};
+//!
+//! let backend = MockBackend::new(&["CAP_NET_RAW", "CAP_CHOWN"]);
+//! backend.restrict(&["CAP_NET_RAW"]).unwrap();
+//! assert_eq!(backend.permitted().unwrap(), vec!["CAP_NET_RAW"]);
+//! assert_eq!(backend.ops(), vec![Op::Restrict(vec!["CAP_NET_RAW".to_string()])]);
+//! ```
+//!
+//! Privileges are matched case-insensitively. As with real backends,
+//! privileges which are not permitted cannot be raised, and dropped ones
+//! cannot be regained.
+//!
+//! This module requires the `mock` feature.
+
+use errno;
+use libc;
+
+use backend::PrivilegeBackend;
+use errors::*;
+use std::sync::{Mutex, MutexGuard};
+
+/// Operation requested from a `MockBackend`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub enum Op {
+    /// `raise` of a privilege.
+    Raise(String),
+    /// `lower` of a privilege.
+    Lower(String),
+    /// `drop` of a privilege.
+    Drop(String),
+    /// `restrict` to a keep-list.
+    Restrict(Vec<String>),
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    effective: Vec<String>,
+    permitted: Vec<String>,
+    ops: Vec<Op>,
+}
+
+/// In-memory privilege backend, recording requested operations.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    inner: Mutex<Inner>,
+}
+
+impl MockBackend {
+    /// Return a backend holding (permitted and in effect) `privileges`.
+    pub fn new(privileges: &[&str]) -> MockBackend {
+        MockBackend::with_sets(privileges, privileges)
+    }
+
+    /// Return a backend with privileges `effective` in effect, out of
+    /// `permitted` ones.
+    ///
+    /// Effective privileges which are not permitted are ignored.
+    pub fn with_sets(effective: &[&str], permitted: &[&str]) -> MockBackend {
+        let permitted: Vec<String> = permitted.iter().map(|p| p.to_string()).collect();
+        let effective = effective
+            .iter()
+            .filter(|e| contains(&permitted, e))
+            .map(|e| e.to_string())
+            .collect();
+        MockBackend {
+            inner: Mutex::new(Inner {
+                effective,
+                permitted,
+                ops: vec![],
+            }),
+        }
+    }
+
+    /// Return all operations requested so far, including failed ones.
+    pub fn ops(&self) -> Vec<Op> {
+        self.lock().ops.clone()
+    }
+
+    /// Forget recorded operations.
+    pub fn clear_ops(&self) {
+        self.lock().ops.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // State is always consistent, even if a holder panicked.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl PrivilegeBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn effective(&self) -> Result<Vec<String>> {
+        Ok(self.lock().effective.clone())
+    }
+
+    fn permitted(&self) -> Result<Vec<String>> {
+        Ok(self.lock().permitted.clone())
+    }
+
+    fn raise(&self, name: &str) -> Result<()> {
+        let mut inner = self.lock();
+        inner.ops.push(Op::Raise(name.to_string()));
+        if !contains(&inner.permitted, name) {
+            return Err(denied("raise"));
+        }
+        if !contains(&inner.effective, name) {
+            inner.effective.push(name.to_string());
+        }
+        Ok(())
+    }
+
+    fn lower(&self, name: &str) -> Result<()> {
+        let mut inner = self.lock();
+        inner.ops.push(Op::Lower(name.to_string()));
+        remove(&mut inner.effective, name);
+        Ok(())
+    }
+
+    fn drop(&self, name: &str) -> Result<()> {
+        let mut inner = self.lock();
+        inner.ops.push(Op::Drop(name.to_string()));
+        remove(&mut inner.effective, name);
+        remove(&mut inner.permitted, name);
+        Ok(())
+    }
+
+    fn restrict(&self, keep: &[&str]) -> Result<()> {
+        let mut inner = self.lock();
+        inner
+            .ops
+            .push(Op::Restrict(keep.iter().map(|k| k.to_string()).collect()));
+        inner.effective.retain(|p| contains(keep, p));
+        inner.permitted.retain(|p| contains(keep, p));
+        Ok(())
+    }
+}
+
+fn contains<S: AsRef<str>>(set: &[S], name: &str) -> bool {
+    set.iter().any(|p| p.as_ref().eq_ignore_ascii_case(name))
+}
+
+fn remove(set: &mut Vec<String>, name: &str) {
+    set.retain(|p| !p.eq_ignore_ascii_case(name));
+}
+
+// Error of an operation denied as by the kernel.
+fn denied(op: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(errno::Errno(libc::EPERM))).chain_err(|| op)
+}
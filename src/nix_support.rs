@@ -0,0 +1,28 @@
+use errno;
+use nix;
+
+use super::Target;
+use errors::*;
+
+impl From<nix::unistd::Pid> for Target {
+    fn from(pid: nix::unistd::Pid) -> Target {
+        Target::Process(pid.as_raw())
+    }
+}
+
+impl From<nix::errno::Errno> for Error {
+    fn from(e: nix::errno::Errno) -> Error {
+        Error::from_kind(ErrorKind::Sys(errno::Errno(e as i32)))
+    }
+}
+
+impl From<Error> for nix::errno::Errno {
+    /// Convert into the underlying syscall error, falling back to
+    /// `EINVAL` for errors which were not caused by a syscall.
+    fn from(e: Error) -> nix::errno::Errno {
+        match e.errno() {
+            Some(errno::Errno(raw)) => nix::errno::Errno::from_raw(raw),
+            None => nix::errno::Errno::EINVAL,
+        }
+    }
+}
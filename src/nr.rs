@@ -39,46 +39,14 @@ pub const CAP_WAKE_ALARM: u8 = 35;
 pub const CAP_BLOCK_SUSPEND: u8 = 36;
 pub const CAP_AUDIT_READ: u8 = 37;
 
-/* from <sys/prctl.h> */
+/* from <sys/prctl.h> and <unistd.h> */
 
-pub const PR_GET_KEEPCAPS: i32 = 7;
-pub const PR_SET_KEEPCAPS: i32 = 8;
-pub const PR_CAPBSET_READ: i32 = 23;
-pub const PR_CAPBSET_DROP: i32 = 24;
-pub const PR_CAP_AMBIENT: i32 = 47;
-pub const PR_CAP_AMBIENT_IS_SET: i32 = 1;
-pub const PR_CAP_AMBIENT_RAISE: i32 = 2;
-pub const PR_CAP_AMBIENT_LOWER: i32 = 3;
-pub const PR_CAP_AMBIENT_CLEAR_ALL: i32 = 4;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use caps_core::nr::*;
 
-/* from <unistd.h> */
+/* from <linux/nsfs.h> */
 
-#[cfg(target_arch = "x86")]
-pub const CAPGET: i32 = 184;
-#[cfg(target_arch = "x86")]
-pub const CAPSET: i32 = 185;
-
-#[cfg(target_arch = "x86_64")]
-pub const CAPGET: i64 = 125;
-#[cfg(target_arch = "x86_64")]
-pub const CAPSET: i64 = 126;
-
-#[cfg(target_arch = "aarch64")]
-pub const CAPGET: i64 = 90;
-#[cfg(target_arch = "aarch64")]
-pub const CAPSET: i64 = 91;
-
-#[cfg(target_arch = "powerpc")]
-pub const CAPGET: i64 = 183;
-#[cfg(target_arch = "powerpc")]
-pub const CAPSET: i64 = 184;
-
-#[cfg(target_arch = "mips")]
-pub const CAPGET: i32 = 4204;
-#[cfg(target_arch = "mips")]
-pub const CAPSET: i32 = 4205;
-
-#[cfg(target_arch = "arm")]
-pub const CAPGET: i32 = 184;
-#[cfg(target_arch = "arm")]
-pub const CAPSET: i32 = 185;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const NS_GET_PARENT: u64 = 0xb702;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const NS_GET_OWNER_UID: u64 = 0xb704;
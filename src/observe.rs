@@ -0,0 +1,163 @@
+//! Observers of capability changes.
+//!
+//! Applications can register callbacks which are invoked with a `CapsDiff`
+//! every time this crate modifies a capability set, via `set`, `clear`,
+//! `raise`, `drop` or `CapState::apply`. This is a single place to log,
+//! count or audit every privilege transition.
+//!
+//! Observers are process-wide and invoked synchronously, on the thread
+//! which performed the change, after it succeeded. Changes which leave a
+//! set untouched are not reported. When no observer is registered, this
+//! costs a single atomic load per change.
+//!
+//! Changes made by other processes can be monitored with `watch`, which
+//! polls their state through procfs.
+
+use super::{CapSet, CapState, CapsHashSet, Target};
+use errors::*;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use trace;
+
+/// Change of a single capability set.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CapsDiff {
+    /// Target of the change.
+    pub target: Target,
+    /// Changed set.
+    pub set: CapSet,
+    /// Capabilities which were added to the set.
+    pub added: CapsHashSet,
+    /// Capabilities which were removed from the set.
+    pub removed: CapsHashSet,
+}
+
+impl CapsDiff {
+    /// Compute the change of set `set` from `before` to `after`.
+    pub fn new(target: Target, set: CapSet, before: &CapsHashSet, after: &CapsHashSet) -> Self {
+        CapsDiff {
+            target,
+            set,
+            added: after.difference(before).cloned().collect(),
+            removed: before.difference(after).cloned().collect(),
+        }
+    }
+
+    /// Whether the set was left untouched.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Identifier of a registered observer, for `unregister`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct ObserverId(u64);
+
+type Observer = Arc<dyn Fn(&CapsDiff) + Send + Sync>;
+
+static OBSERVERS: RwLock<Vec<(u64, Observer)>> = RwLock::new(Vec::new());
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Register `f` to be invoked on every capability change.
+pub fn register<F>(f: F) -> ObserverId
+where
+    F: Fn(&CapsDiff) + Send + Sync + 'static,
+{
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let mut observers = OBSERVERS.write().unwrap_or_else(|e| e.into_inner());
+    observers.push((id, Arc::new(f)));
+    ACTIVE.store(true, Ordering::SeqCst);
+    ObserverId(id)
+}
+
+/// Unregister observer `id`, returning whether it was registered.
+pub fn unregister(id: ObserverId) -> bool {
+    let mut observers = OBSERVERS.write().unwrap_or_else(|e| e.into_inner());
+    let len = observers.len();
+    observers.retain(|&(i, _)| i != id.0);
+    ACTIVE.store(!observers.is_empty(), Ordering::SeqCst);
+    observers.len() != len
+}
+
+// Whether any observer is registered, or changes are being traced.
+pub(crate) fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst) || trace::enabled()
+}
+
+// Invoke all observers with `diff`, unless it is empty.
+//
+// Observers are called without holding the lock, so that they can
+// themselves change capabilities or (un)register observers.
+pub(crate) fn notify(diff: &CapsDiff) {
+    if diff.is_empty() {
+        return;
+    }
+    trace::changed(diff);
+    let observers: Vec<Observer> = OBSERVERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(_, f)| f.clone())
+        .collect();
+    for f in observers {
+        f(diff);
+    }
+}
+
+/// Iterator over capability changes of another process, see `watch`.
+#[derive(Debug)]
+pub struct Watch {
+    pid: i32,
+    interval: Duration,
+    last: CapState,
+    pending: VecDeque<CapsDiff>,
+}
+
+/// Watch process `pid`, polling its state every `interval`.
+///
+/// The returned iterator blocks until some capability set changes, and
+/// yields one diff per changed set. It ends when the process exits (or
+/// its state can no longer be read). Changes which are reverted within
+/// `interval` go unnoticed.
+pub fn watch(pid: i32, interval: Duration) -> Result<Watch> {
+    Ok(Watch {
+        pid,
+        interval,
+        last: CapState::from_proc(pid, pid)?,
+        pending: VecDeque::new(),
+    })
+}
+
+impl Iterator for Watch {
+    type Item = CapsDiff;
+
+    fn next(&mut self) -> Option<CapsDiff> {
+        while self.pending.is_empty() {
+            thread::sleep(self.interval);
+            let state = CapState::from_proc(self.pid, self.pid).ok()?;
+            for cset in &[
+                CapSet::Bounding,
+                CapSet::Inheritable,
+                CapSet::Effective,
+                CapSet::Permitted,
+                CapSet::Ambient,
+            ] {
+                let diff = CapsDiff::new(
+                    Target::Process(self.pid),
+                    *cset,
+                    self.last.get(*cset),
+                    state.get(*cset),
+                );
+                if !diff.is_empty() {
+                    self.pending.push_back(diff);
+                }
+            }
+            self.last = state;
+        }
+        self.pending.pop_front()
+    }
+}
@@ -0,0 +1,102 @@
+//! Capabilities block of OCI runtime configurations.
+//!
+//! OCI bundles describe the capabilities of their container process in
+//! the `linux.capabilities` object of `config.json`. `OciCapabilities`
+//! maps a `CapState` to this object and back, and emits it as JSON (it is
+//! also serializable with the `serde` feature):
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::oci::OciCapabilities;
+//! use caps::CapState;
+//!
+//! # fn main() {
+//! let state = CapState {
+//!     bounding: caps![CAP_NET_BIND_SERVICE],
+//!     permitted: caps![CAP_NET_BIND_SERVICE],
+//!     ..Default::default()
+//! };
+//! let json = OciCapabilities::from_state(&state).to_json();
+//! assert!(json.starts_with("{\n  \"bounding\": [\n    \"CAP_NET_BIND_SERVICE\"\n  ],"));
+//! # }
+//! ```
+
+use super::{sorted, CapState, Capability};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The `linux.capabilities` object of an OCI runtime configuration.
+///
+/// Each list is sorted by capability index.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct OciCapabilities {
+    /// Bounding capabilities.
+    pub bounding: Vec<Capability>,
+    /// Effective capabilities.
+    pub effective: Vec<Capability>,
+    /// Inheritable capabilities.
+    pub inheritable: Vec<Capability>,
+    /// Permitted capabilities.
+    pub permitted: Vec<Capability>,
+    /// Ambient capabilities.
+    pub ambient: Vec<Capability>,
+}
+
+impl OciCapabilities {
+    /// Build the capabilities block of state `state`.
+    pub fn from_state(state: &CapState) -> OciCapabilities {
+        OciCapabilities {
+            bounding: sorted(&state.bounding),
+            effective: sorted(&state.effective),
+            inheritable: sorted(&state.inheritable),
+            permitted: sorted(&state.permitted),
+            ambient: sorted(&state.ambient),
+        }
+    }
+
+    /// Return the capability state described by this block.
+    pub fn to_state(&self) -> CapState {
+        let set = |caps: &[Capability]| caps.iter().cloned().collect();
+        CapState {
+            ambient: set(&self.ambient),
+            bounding: set(&self.bounding),
+            effective: set(&self.effective),
+            inheritable: set(&self.inheritable),
+            permitted: set(&self.permitted),
+        }
+    }
+
+    /// Format as the JSON object expected under `linux.capabilities`.
+    ///
+    /// Keys follow the order of the OCI runtime specification, and the
+    /// object is indented by two spaces.
+    pub fn to_json(&self) -> String {
+        let lists = [
+            ("bounding", &self.bounding),
+            ("effective", &self.effective),
+            ("inheritable", &self.inheritable),
+            ("permitted", &self.permitted),
+            ("ambient", &self.ambient),
+        ];
+        let fields: Vec<_> = lists
+            .iter()
+            .map(|&(key, caps)| {
+                if caps.is_empty() {
+                    return format!("  \"{}\": []", key);
+                }
+                let names: Vec<_> = caps.iter().map(|c| format!("    \"{}\"", c)).collect();
+                format!("  \"{}\": [\n{}\n  ]", key, names.join(",\n"))
+            })
+            .collect();
+        format!("{{\n{}\n}}", fields.join(",\n"))
+    }
+}
+
+impl<'a> From<&'a CapState> for OciCapabilities {
+    fn from(state: &'a CapState) -> OciCapabilities {
+        OciCapabilities::from_state(state)
+    }
+}
@@ -0,0 +1,47 @@
+//! Process file descriptors helpers.
+//!
+//! A pidfd is a file descriptor referring to a process (from Linux 5.3).
+//! Unlike a PID, it cannot be recycled to refer to another process once
+//! the original one has exited.
+
+use errors::*;
+use std::fs;
+use std::os::unix::io::{FromRawFd, OwnedFd, RawFd};
+use sys;
+
+/// Open a pidfd referring to process `pid`.
+pub fn open(pid: i32) -> Result<OwnedFd> {
+    let ret = sys::pidfd_open(pid);
+    match ret {
+        fd if fd >= 0 => Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) }),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "pidfd_open error")),
+    }
+}
+
+/// Resolve pidfd `fd` to the PID of its process.
+///
+/// This fails if the process has exited, or if it is not visible from
+/// the PID namespace of the caller.
+pub fn pid(fd: RawFd) -> Result<i32> {
+    let fdinfo = fs::read_to_string(format!("/proc/self/fdinfo/{}", fd))
+        .chain_err(|| format!("failed to read fdinfo for fd {}", fd))?;
+    let pid = fdinfo
+        .lines()
+        .find(|l| l.starts_with("Pid:"))
+        .and_then(|l| l["Pid:".len()..].trim().parse::<i32>().ok());
+    match pid {
+        Some(p) if p > 0 => Ok(p),
+        _ => bail!(ErrorKind::InvalidTarget(fd)),
+    }
+}
+
+/// Check whether the process referred to by pidfd `fd` is still alive.
+///
+/// Exited processes are reported as such even before being reaped, and no
+/// permission over the process is needed.
+pub fn is_alive(fd: RawFd) -> Result<bool> {
+    match sys::pidfd_alive(fd) {
+        r if r >= 0 => Ok(r == 1),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "pidfd error")),
+    }
+}
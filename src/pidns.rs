@@ -0,0 +1,99 @@
+//! Processes in other PID namespaces.
+//!
+//! Processes of child PID namespaces (e.g. container payloads) are visible
+//! in `/proc` under their PID in the namespace of that procfs mount, while
+//! they know themselves by a namespace-local PID. This module translates
+//! between both, based on the `NSpid` field of `/proc/<pid>/status` and on
+//! the `/proc/<pid>/ns/pid` link, so that such processes can be targeted
+//! and their capabilities read.
+
+use libc;
+
+use super::CapState;
+use errors::*;
+use scan;
+use std::fs;
+
+/// PID namespace information of a process.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct NsInfo {
+    /// PID, in the namespace of the `/proc` mount.
+    pub pid: libc::pid_t,
+    /// Inode number identifying the PID namespace of the process.
+    pub ns: u64,
+    /// PIDs in each nested namespace, from `/proc`'s one to the innermost.
+    pub ns_pids: Vec<libc::pid_t>,
+}
+
+impl NsInfo {
+    /// Read the PID namespace information of process `pid`.
+    pub fn read(pid: libc::pid_t) -> Result<NsInfo> {
+        let path = format!("/proc/{}/status", pid);
+        let status = fs::read_to_string(&path).chain_err(|| format!("failed to read {}", path))?;
+        let ns_pids = match status.lines().find(|l| l.starts_with("NSpid:")) {
+            Some(l) => l["NSpid:".len()..]
+                .split_whitespace()
+                .map(|p| p.parse())
+                .collect::<::std::result::Result<Vec<_>, _>>()
+                .chain_err(|| format!("invalid NSpid in {}", path))?,
+            // NSpid is only reported since Linux 4.1.
+            None => vec![pid],
+        };
+        Ok(NsInfo {
+            pid,
+            ns: ns_inode(pid)?,
+            ns_pids,
+        })
+    }
+
+    /// Return the PID of the process in its own namespace.
+    pub fn local_pid(&self) -> libc::pid_t {
+        *self.ns_pids.last().unwrap_or(&self.pid)
+    }
+
+    /// Return whether the process is in a PID namespace nested below `/proc`'s one.
+    pub fn is_nested(&self) -> bool {
+        self.ns_pids.len() > 1
+    }
+}
+
+/// Return the inode number identifying the PID namespace of process `pid`.
+pub fn ns_inode(pid: libc::pid_t) -> Result<u64> {
+    let path = format!("/proc/{}/ns/pid", pid);
+    let link = fs::read_link(&path).chain_err(|| format!("failed to read {}", path))?;
+    // The link target looks like "pid:[4026531836]".
+    link.to_str()
+        .and_then(|l| l.strip_prefix("pid:["))
+        .and_then(|l| l.strip_suffix(']'))
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(|| format!("invalid link {:?}", link).into())
+}
+
+/// Translate PID `local` of namespace `ns` into a PID of `/proc`'s namespace.
+pub fn to_host(ns: u64, local: libc::pid_t) -> Option<libc::pid_t> {
+    all()
+        .find(|i| i.ns == ns && i.local_pid() == local)
+        .map(|i| i.pid)
+}
+
+/// Read the capability state of process `local` of namespace `ns`.
+pub fn read_state(ns: u64, local: libc::pid_t) -> Result<(NsInfo, CapState)> {
+    let pid = match to_host(ns, local) {
+        Some(pid) => pid,
+        None => bail!(ErrorKind::InvalidTarget(local)),
+    };
+    let state = CapState::from_proc(pid, pid)?;
+    Ok((NsInfo::read(pid)?, state))
+}
+
+/// Iterate over all processes, with their namespace information.
+///
+/// This is like `scan::all_processes`, with namespace information added.
+pub fn all_processes() -> impl Iterator<Item = (NsInfo, CapState)> {
+    scan::all_processes().filter_map(|(pid, state)| NsInfo::read(pid).ok().map(|i| (i, state)))
+}
+
+// Iterate over namespace information of all processes.
+fn all() -> impl Iterator<Item = NsInfo> {
+    scan::pids().filter_map(|pid| NsInfo::read(pid).ok())
+}
@@ -0,0 +1,255 @@
+//! Declarative capability policies.
+//!
+//! A `Policy` describes the maximum privileges a process is allowed to
+//! hold. `verify` inspects the current thread and reports every deviation,
+//! which is suitable both for asserting at startup and for compliance
+//! checks, while `Policy::enforce` brings the current thread in line.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+
+use super::{CapSet, CapState, Capability, CapsHashSet};
+use errors::*;
+use lowlevel::Masks;
+use securebits::{self, SecureBits};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Capability policy.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Policy {
+    /// Capabilities allowed in any set; `None` allows all of them.
+    pub allowed: Option<CapsHashSet>,
+    /// Capabilities denied in all sets, which take precedence over allowed ones.
+    pub denied: CapsHashSet,
+    /// Maximum capabilities allowed in each set; sets not listed are
+    /// unconstrained.
+    pub max: HashMap<CapSet, CapsHashSet>,
+    /// Whether "no new privileges" must be set.
+    pub no_new_privs: bool,
+    /// Securebits which must be set.
+    pub securebits: SecureBits,
+}
+
+/// Deviation from a policy.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Violation {
+    /// Capabilities held beyond the maximum allowed in a set.
+    Excess { set: CapSet, caps: CapsHashSet },
+    /// Denied capabilities held in a set.
+    Denied { set: CapSet, caps: CapsHashSet },
+    /// "No new privileges" is required but not set.
+    NoNewPrivs,
+    /// Required securebits which are not set.
+    SecureBits { missing: SecureBits },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::Excess { set, ref caps } => {
+                write!(f, "{:?} set holds disallowed {}", set, names(caps))
+            }
+            Violation::Denied { set, ref caps } => {
+                write!(f, "{:?} set holds denied {}", set, names(caps))
+            }
+            Violation::NoNewPrivs => write!(f, "no_new_privs is not set"),
+            Violation::SecureBits { missing } => {
+                write!(f, "securebits {:#x} are not set", missing.bits())
+            }
+        }
+    }
+}
+
+// All sets, in an order suitable for dropping capabilities.
+const SETS: &[CapSet] = &[
+    CapSet::Bounding,
+    CapSet::Ambient,
+    CapSet::Inheritable,
+    CapSet::Effective,
+    CapSet::Permitted,
+];
+
+fn names(caps: &CapsHashSet) -> String {
+    let mut names: Vec<String> = caps.iter().map(|c| c.to_string()).collect();
+    names.sort();
+    names.join(", ")
+}
+
+impl Policy {
+    /// Allow only capabilities `caps`, in any set.
+    pub fn allow(mut self, caps: &CapsHashSet) -> Policy {
+        self.allowed = Some(caps.clone());
+        self
+    }
+
+    /// Deny capabilities `caps` in all sets.
+    pub fn deny(mut self, caps: &CapsHashSet) -> Policy {
+        self.denied.extend(caps.iter().cloned());
+        self
+    }
+
+    /// Check whether this policy allows `cap` in set `set`.
+    pub fn allows(&self, set: CapSet, cap: Capability) -> bool {
+        !self.denied.contains(&cap)
+            && self.allowed.iter().all(|a| a.contains(&cap))
+            && self.max.get(&set).into_iter().all(|m| m.contains(&cap))
+    }
+    /// Allow at most capabilities `caps` in set `set`.
+    pub fn max(mut self, set: CapSet, caps: &CapsHashSet) -> Policy {
+        self.max.insert(set, caps.clone());
+        self
+    }
+
+    /// Check capability state `state`, securebits and "no new privileges"
+    /// flag against this policy.
+    pub fn check(
+        &self,
+        state: &CapState,
+        securebits: SecureBits,
+        no_new_privs: bool,
+    ) -> Vec<Violation> {
+        let mut res = vec![];
+        for cset in SETS {
+            let held = state.get(*cset);
+            let denied: CapsHashSet = held.intersection(&self.denied).cloned().collect();
+            let excess: CapsHashSet = held
+                .iter()
+                .filter(|c| !self.denied.contains(c) && !self.allows(*cset, **c))
+                .cloned()
+                .collect();
+            if !excess.is_empty() {
+                res.push(Violation::Excess {
+                    set: *cset,
+                    caps: excess,
+                });
+            }
+            if !denied.is_empty() {
+                res.push(Violation::Denied {
+                    set: *cset,
+                    caps: denied,
+                });
+            }
+        }
+        if self.no_new_privs && !no_new_privs {
+            res.push(Violation::NoNewPrivs);
+        }
+        let missing = self.securebits.bits() & !securebits.bits();
+        if missing != 0 {
+            res.push(Violation::SecureBits {
+                missing: SecureBits::from_bits(missing),
+            });
+        }
+        res
+    }
+
+    /// Capabilities allowed by this policy in each set, as raw bitmasks.
+    ///
+    /// Capabilities unknown to this crate are allowed in sets which are
+    /// not constrained by an allow-list nor a maximum.
+    pub fn allowed_masks(&self) -> Masks {
+        let known = super::to_bitmask(&super::all());
+        let mask = |set: CapSet| {
+            let mut res = super::all()
+                .into_iter()
+                .filter(|c| self.allows(set, *c))
+                .fold(0, |m, c| m | c.bitmask());
+            if self.allowed.is_none() && !self.max.contains_key(&set) {
+                res |= !known;
+            }
+            res
+        };
+        Masks {
+            ambient: mask(CapSet::Ambient),
+            bounding: mask(CapSet::Bounding),
+            effective: mask(CapSet::Effective),
+            inheritable: mask(CapSet::Inheritable),
+            permitted: mask(CapSet::Permitted),
+        }
+    }
+
+    /// Remove all capabilities not allowed by this policy from the current
+    /// thread.
+    ///
+    /// Removal is best-effort (e.g. the bounding set can only be shrunk
+    /// with `CAP_SETPCAP`), but an error is returned if any denied
+    /// capability is still held afterwards. Securebits and "no new
+    /// privileges" are not changed.
+    pub fn enforce(&self) -> Result<()> {
+        for cset in SETS {
+            for cap in super::read(None, *cset)? {
+                if !self.allows(*cset, cap) {
+                    let _ = super::drop(None, *cset, cap);
+                }
+            }
+        }
+        let state = CapState::current()?;
+        let mut left = CapsHashSet::new();
+        for cset in SETS {
+            left.extend(state.get(*cset).intersection(&self.denied).cloned());
+        }
+        if !left.is_empty() {
+            bail!("denied capabilities could not be removed: {}", names(&left));
+        }
+        Ok(())
+    }
+}
+
+/// Environment variable listing capabilities to keep, used by `from_env`.
+pub const KEEP_VAR: &str = "CAPS_KEEP";
+/// Environment variable listing capabilities to deny, used by `from_env`.
+pub const DENY_VAR: &str = "CAPS_DENY";
+
+/// Parse a comma-separated list of capability names (e.g. `"cap_net_raw,kill"`).
+pub fn parse_list(s: &str) -> Result<CapsHashSet> {
+    s.split(',')
+        .map(|n| n.trim())
+        .filter(|n| !n.is_empty())
+        .map(|n| super::to_canonical(n).parse())
+        .collect()
+}
+
+/// Build a policy from `CAPS_KEEP` and `CAPS_DENY` environment variables.
+///
+/// `CAPS_KEEP` lists allowed capabilities, and `CAPS_DENY` denied ones.
+/// `None` is returned if neither variable is set.
+pub fn from_env() -> Result<Option<Policy>> {
+    let keep = env::var(KEEP_VAR).ok();
+    let deny = env::var(DENY_VAR).ok();
+    if keep.is_none() && deny.is_none() {
+        return Ok(None);
+    }
+    let mut policy = Policy::default();
+    if let Some(ref keep) = keep {
+        let caps = parse_list(keep).chain_err(|| format!("invalid {}", KEEP_VAR))?;
+        policy = policy.allow(&caps);
+    }
+    if let Some(ref deny) = deny {
+        let caps = parse_list(deny).chain_err(|| format!("invalid {}", DENY_VAR))?;
+        policy = policy.deny(&caps);
+    }
+    Ok(Some(policy))
+}
+
+/// Enforce the policy from the environment (see `from_env`), if any.
+///
+/// This is meant as an opt-in startup hook, e.g. for container entrypoints.
+/// Return whether a policy was found and enforced.
+pub fn apply_from_env() -> Result<bool> {
+    match from_env()? {
+        Some(policy) => policy.enforce().map(|_| true),
+        None => Ok(false),
+    }
+}
+
+/// Verify the current thread against `policy`, returning all violations.
+pub fn verify(policy: &Policy) -> Result<Vec<Violation>> {
+    let state = CapState::current()?;
+    let bits = securebits::get_securebits()?;
+    let nnp = securebits::has_no_new_privs()?;
+    Ok(policy.check(&state, bits, nnp))
+}
@@ -0,0 +1,287 @@
+//! illumos and Solaris privilege sets.
+//!
+//! On illumos (SmartOS, OmniOS...) and Solaris, root powers are split into
+//! fine-grained privileges (see `privileges(5)`), such as `net_privaddr` or
+//! `proc_fork`. Each process has four privilege sets, mapped by `PrivSet`
+//! onto their closest Linux capabilities counterparts:
+//!
+//!  * the Effective, Permitted and Inheritable sets, as on Linux,
+//!  * the Limit set, bounding all others like the Linux Bounding set.
+//!
+//! Unlike Linux, unprivileged processes hold the "basic" privileges (such
+//! as `proc_fork` or `file_read`), which can be dropped as well:
+//!
+//! ```rust,no_run
+//! use caps::privileges::{self, PrivSet, Privileges};
+//!
+//! let mut keep = Privileges::basic().unwrap();
+//! keep.remove("proc_exec").unwrap();
+//! keep.add("net_privaddr").unwrap();
+//! privileges::set(PrivSet::Permitted, &keep).unwrap();
+//! ```
+//!
+//! This module is only available on illumos and Solaris.
+
+use errno;
+use libc::{c_char, c_int};
+
+use super::{CapSet, Capability};
+use errors::*;
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+// Opaque `priv_set_t`.
+enum PrivSetT {}
+
+// `priv_op_t` values.
+const PRIV_ON: c_int = 0;
+const PRIV_OFF: c_int = 1;
+const PRIV_SET: c_int = 2;
+
+extern "C" {
+    fn priv_allocset() -> *mut PrivSetT;
+    fn priv_freeset(set: *mut PrivSetT);
+    fn priv_emptyset(set: *mut PrivSetT);
+    fn priv_fillset(set: *mut PrivSetT);
+    fn priv_basicset(set: *mut PrivSetT);
+    fn priv_copyset(src: *const PrivSetT, dst: *mut PrivSetT);
+    fn priv_isequalset(a: *const PrivSetT, b: *const PrivSetT) -> c_int;
+    fn priv_addset(set: *mut PrivSetT, name: *const c_char) -> c_int;
+    fn priv_delset(set: *mut PrivSetT, name: *const c_char) -> c_int;
+    fn priv_ismember(set: *const PrivSetT, name: *const c_char) -> c_int;
+    fn priv_getbynum(num: c_int) -> *const c_char;
+    fn getppriv(which: *const c_char, set: *mut PrivSetT) -> c_int;
+    fn setppriv(op: c_int, which: *const c_char, set: *const PrivSetT) -> c_int;
+}
+
+/// Process privilege sets.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum PrivSet {
+    /// Effective set, privileges currently in use.
+    Effective,
+    /// Inheritable set, privileges passed across exec.
+    Inheritable,
+    /// Permitted set, bounding the Effective set.
+    Permitted,
+    /// Limit set, bounding all sets of the process and its children.
+    Limit,
+}
+
+impl PrivSet {
+    /// Return the privilege set corresponding to a Linux capability set.
+    ///
+    /// The Bounding set maps to the Limit set, while the Ambient set has
+    /// no counterpart.
+    pub fn from_cap_set(cset: CapSet) -> Option<PrivSet> {
+        match cset {
+            CapSet::Effective => Some(PrivSet::Effective),
+            CapSet::Inheritable => Some(PrivSet::Inheritable),
+            CapSet::Permitted => Some(PrivSet::Permitted),
+            CapSet::Bounding => Some(PrivSet::Limit),
+            CapSet::Ambient => None,
+        }
+    }
+
+    // Name of this set, as `priv_ptype_t`.
+    fn name(self) -> *const c_char {
+        let name: &'static [u8] = match self {
+            PrivSet::Effective => b"Effective\0",
+            PrivSet::Inheritable => b"Inheritable\0",
+            PrivSet::Permitted => b"Permitted\0",
+            PrivSet::Limit => b"Limit\0",
+        };
+        name.as_ptr() as *const c_char
+    }
+}
+
+/// A set of privileges, as `priv_set_t`.
+pub struct Privileges {
+    raw: *mut PrivSetT,
+}
+
+impl Privileges {
+    /// Return an empty set.
+    pub fn empty() -> Result<Privileges> {
+        let set = Privileges::alloc()?;
+        unsafe { priv_emptyset(set.raw) };
+        Ok(set)
+    }
+
+    /// Return a set with all privileges.
+    pub fn full() -> Result<Privileges> {
+        let set = Privileges::alloc()?;
+        unsafe { priv_fillset(set.raw) };
+        Ok(set)
+    }
+
+    /// Return the set of basic privileges, held by unprivileged processes.
+    pub fn basic() -> Result<Privileges> {
+        let set = Privileges::alloc()?;
+        unsafe { priv_basicset(set.raw) };
+        Ok(set)
+    }
+
+    /// Return the set of privileges corresponding to Linux capabilities
+    /// `caps` (see `for_capability`).
+    pub fn from_capabilities<'a, I>(caps: I) -> Result<Privileges>
+    where
+        I: IntoIterator<Item = &'a Capability>,
+    {
+        let mut set = Privileges::empty()?;
+        for cap in caps {
+            for name in for_capability(*cap) {
+                set.add(name)?;
+            }
+        }
+        Ok(set)
+    }
+
+    fn alloc() -> Result<Privileges> {
+        let raw = unsafe { priv_allocset() };
+        if raw.is_null() {
+            return Err(sys("priv_allocset error"));
+        }
+        Ok(Privileges { raw })
+    }
+
+    /// Add privilege `name` (e.g. `"net_privaddr"`).
+    pub fn add(&mut self, name: &str) -> Result<()> {
+        let name = priv_name(name)?;
+        match unsafe { priv_addset(self.raw, name.as_ptr()) } {
+            0 => Ok(()),
+            _ => Err(sys("priv_addset error")),
+        }
+    }
+
+    /// Remove privilege `name`.
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        let name = priv_name(name)?;
+        match unsafe { priv_delset(self.raw, name.as_ptr()) } {
+            0 => Ok(()),
+            _ => Err(sys("priv_delset error")),
+        }
+    }
+
+    /// Check whether privilege `name` is in this set.
+    pub fn contains(&self, name: &str) -> Result<bool> {
+        let name = priv_name(name)?;
+        Ok(unsafe { priv_ismember(self.raw, name.as_ptr()) } != 0)
+    }
+
+    /// Return the names of all privileges in this set.
+    pub fn names(&self) -> Vec<String> {
+        (0..)
+            .map(|n| unsafe { priv_getbynum(n) })
+            .take_while(|p| !p.is_null())
+            .filter(|&p| unsafe { priv_ismember(self.raw, p) } != 0)
+            .map(|p| unsafe { CStr::from_ptr(p) }.to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+impl Clone for Privileges {
+    fn clone(&self) -> Privileges {
+        let set = Privileges::alloc().expect("priv_allocset failed");
+        unsafe { priv_copyset(self.raw, set.raw) };
+        set
+    }
+}
+
+impl PartialEq for Privileges {
+    fn eq(&self, other: &Privileges) -> bool {
+        unsafe { priv_isequalset(self.raw, other.raw) != 0 }
+    }
+}
+
+impl Eq for Privileges {}
+
+impl fmt::Debug for Privileges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+impl Drop for Privileges {
+    fn drop(&mut self) {
+        unsafe { priv_freeset(self.raw) };
+    }
+}
+
+/// Return privilege set `set` of the current process.
+pub fn get(set: PrivSet) -> Result<Privileges> {
+    let privs = Privileges::alloc()?;
+    match unsafe { getppriv(set.name(), privs.raw) } {
+        0 => Ok(privs),
+        _ => Err(sys("getppriv error")),
+    }
+}
+
+/// Replace privilege set `set` of the current process with `privs`.
+pub fn set(set: PrivSet, privs: &Privileges) -> Result<()> {
+    setppriv_op(PRIV_SET, set, privs)
+}
+
+/// Add `privs` to privilege set `set` of the current process (`PRIV_ON`).
+pub fn raise(set: PrivSet, privs: &Privileges) -> Result<()> {
+    setppriv_op(PRIV_ON, set, privs)
+}
+
+/// Remove `privs` from privilege set `set` of the current process
+/// (`PRIV_OFF`).
+pub fn drop(set: PrivSet, privs: &Privileges) -> Result<()> {
+    setppriv_op(PRIV_OFF, set, privs)
+}
+
+/// Check whether privilege `name` is in set `set` of the current process.
+pub fn has_priv(set: PrivSet, name: &str) -> Result<bool> {
+    get(set)?.contains(name)
+}
+
+/// Return the privileges closest to Linux capability `cap`.
+///
+/// Capabilities without a counterpart map to no privileges.
+pub fn for_capability(cap: Capability) -> &'static [&'static str] {
+    match cap {
+        Capability::CAP_CHOWN => &["file_chown", "file_chown_self"],
+        Capability::CAP_DAC_OVERRIDE => &["file_dac_read", "file_dac_write", "file_dac_execute"],
+        Capability::CAP_DAC_READ_SEARCH => &["file_dac_read", "file_dac_search"],
+        Capability::CAP_FOWNER => &["file_owner"],
+        Capability::CAP_FSETID => &["file_setid"],
+        Capability::CAP_KILL => &["proc_owner"],
+        Capability::CAP_SETGID | Capability::CAP_SETUID => &["proc_setid"],
+        Capability::CAP_LINUX_IMMUTABLE => &["file_flag_set"],
+        Capability::CAP_NET_BIND_SERVICE => &["net_privaddr"],
+        Capability::CAP_NET_ADMIN => &["sys_ip_config", "sys_net_config"],
+        Capability::CAP_NET_RAW => &["net_rawaccess", "net_icmpaccess"],
+        Capability::CAP_IPC_LOCK => &["proc_lock_memory"],
+        Capability::CAP_IPC_OWNER => &["ipc_dac_read", "ipc_dac_write", "ipc_owner"],
+        Capability::CAP_SYS_MODULE => &["sys_config"],
+        Capability::CAP_SYS_RAWIO => &["sys_devices"],
+        Capability::CAP_SYS_CHROOT => &["proc_chroot"],
+        Capability::CAP_SYS_PTRACE => &["proc_owner"],
+        Capability::CAP_SYS_PACCT => &["sys_acct"],
+        Capability::CAP_SYS_ADMIN => &["sys_admin", "sys_mount"],
+        Capability::CAP_SYS_NICE => &["proc_priocntl"],
+        Capability::CAP_SYS_RESOURCE => &["sys_resource"],
+        Capability::CAP_SYS_TIME => &["sys_time"],
+        Capability::CAP_MKNOD => &["sys_devices"],
+        Capability::CAP_AUDIT_CONTROL | Capability::CAP_AUDIT_WRITE => &["sys_audit"],
+        Capability::CAP_AUDIT_READ => &["proc_audit"],
+        _ => &[],
+    }
+}
+
+fn setppriv_op(op: c_int, set: PrivSet, privs: &Privileges) -> Result<()> {
+    match unsafe { setppriv(op, set.name(), privs.raw) } {
+        0 => Ok(()),
+        _ => Err(sys("setppriv error")),
+    }
+}
+
+fn priv_name(name: &str) -> Result<CString> {
+    CString::new(name).chain_err(|| format!("invalid privilege name '{}'", name))
+}
+
+fn sys(context: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| context)
+}
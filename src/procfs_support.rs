@@ -0,0 +1,39 @@
+use procfs::process::{Process, Status};
+
+use super::{from_bitmask, CapState};
+use errors::*;
+use std::convert::TryFrom;
+
+impl<'a> TryFrom<&'a Status> for CapState {
+    type Error = Error;
+
+    /// Convert the capability fields of a process status.
+    ///
+    /// As for `CapState::from_proc_status`, the Bounding set is required
+    /// while a missing Ambient set (before Linux 4.3) is read as empty.
+    fn try_from(status: &'a Status) -> Result<CapState> {
+        let bounding = match status.capbnd {
+            Some(b) => b,
+            None => bail!("missing capabilities in process status"),
+        };
+        Ok(CapState {
+            ambient: from_bitmask(status.capamb.unwrap_or(0)),
+            bounding: from_bitmask(bounding),
+            effective: from_bitmask(status.capeff),
+            inheritable: from_bitmask(status.capinh),
+            permitted: from_bitmask(status.capprm),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a Process> for CapState {
+    type Error = Error;
+
+    /// Read the capability state of the main thread of a process.
+    fn try_from(process: &'a Process) -> Result<CapState> {
+        let status = process
+            .status()
+            .chain_err(|| format!("failed to read status of process {}", process.pid()))?;
+        CapState::try_from(&status)
+    }
+}
@@ -0,0 +1,120 @@
+//! Named privilege profiles.
+//!
+//! This module ships a small registry of vetted configurations for
+//! common kinds of daemons, so that teams converge on them instead of
+//! inventing their own. Each profile names the capabilities to keep, and
+//! is applied via `harden`, which also locks securebits and sets "no new
+//! privileges":
+//!
+//! ```rust,no_run
+//! use caps::profiles;
+//!
+//! // After binding privileged ports:
+//! profiles::apply_profile("web-server").unwrap();
+//! ```
+
+use super::{Capability, CapsHashSet};
+use errors::*;
+use harden::{self, HardenOptions};
+
+/// A named privilege profile.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Profile {
+    /// Name of the profile.
+    pub name: &'static str,
+    /// Short description of the intended use.
+    pub description: &'static str,
+    /// Capabilities kept in the bounding, permitted and effective sets.
+    pub caps: &'static [Capability],
+    /// Whether to set and lock `SecureBits::LOCKDOWN`.
+    pub lock_securebits: bool,
+    /// Whether to set the "no new privileges" flag.
+    pub no_new_privs: bool,
+}
+
+impl Profile {
+    /// Return kept capabilities, as a set.
+    pub fn to_set(&self) -> CapsHashSet {
+        self.caps.iter().cloned().collect()
+    }
+
+    /// Return options to `harden` the calling thread with this profile.
+    pub fn options(&self) -> HardenOptions {
+        HardenOptions {
+            keep: self.to_set(),
+            no_new_privs: self.no_new_privs,
+            lock_securebits: self.lock_securebits,
+        }
+    }
+}
+
+/// All registered profiles, sorted by name.
+pub const PROFILES: &[Profile] = &[
+    Profile {
+        name: "dhcp-client",
+        description: "DHCP client configuring network interfaces",
+        caps: &[
+            Capability::CAP_NET_ADMIN,
+            Capability::CAP_NET_BIND_SERVICE,
+            Capability::CAP_NET_RAW,
+        ],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+    Profile {
+        name: "dns-resolver",
+        description: "DNS server or resolver listening on port 53",
+        caps: &[Capability::CAP_NET_BIND_SERVICE],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+    Profile {
+        name: "none",
+        description: "Fully unprivileged process",
+        caps: &[],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+    Profile {
+        name: "ntp",
+        description: "Time synchronization daemon",
+        caps: &[Capability::CAP_NET_BIND_SERVICE, Capability::CAP_SYS_TIME],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+    Profile {
+        name: "ping",
+        description: "ICMP client using raw sockets",
+        caps: &[Capability::CAP_NET_RAW],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+    Profile {
+        name: "web-server",
+        description: "HTTP server listening on ports 80 and 443",
+        caps: &[Capability::CAP_NET_BIND_SERVICE],
+        lock_securebits: true,
+        no_new_privs: true,
+    },
+];
+
+/// Return the profile named `name`, if any.
+pub fn get(name: &str) -> Option<&'static Profile> {
+    PROFILES.iter().find(|p| p.name == name)
+}
+
+/// Return the names of all registered profiles.
+pub fn names() -> Vec<&'static str> {
+    PROFILES.iter().map(|p| p.name).collect()
+}
+
+/// Lock down the calling thread with the profile named `name`.
+///
+/// See `harden::harden` for details. All capabilities kept by the
+/// profile must be in the permitted set.
+pub fn apply_profile(name: &str) -> Result<()> {
+    match get(name) {
+        Some(profile) => harden::harden(profile.options()),
+        None => bail!("unknown profile '{}', expected one of {:?}", name, names()),
+    }
+}
@@ -0,0 +1,253 @@
+//! Process-wide capability changes.
+//!
+//! Capabilities are a per-thread attribute, thus changing them in a
+//! multi-threaded process only affects the calling thread. This module
+//! mirrors what libcap's `libpsx` does: it applies a capability state to
+//! every thread of the current process, by signalling each of them and
+//! performing the change from within a signal handler.
+//!
+//! The realtime signal `SIGRTMAX` is temporarily used for this purpose
+//! and must not be otherwise used by the application.
+
+use errno;
+use libc;
+
+use super::{all, to_bitmask, CapState};
+use errors::*;
+use lowlevel::{AtomicMasks, Masks};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use this_thread;
+
+// Maximum time to wait for all signalled threads to apply the change.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Serializes process-wide changes.
+static LOCK: Mutex<()> = Mutex::new(());
+
+// Pending change, as raw bitmasks, either a whole state or a restriction.
+static PENDING: AtomicMasks = AtomicMasks::new();
+static RESTRICT: AtomicBool = AtomicBool::new(false);
+
+// Number of threads which applied the change, successfully or not.
+static DONE: AtomicUsize = AtomicUsize::new(0);
+static FAILED: AtomicUsize = AtomicUsize::new(0);
+
+/// Apply capability state `state` to all threads of the current process.
+///
+/// The state is first applied to the calling thread, then to all other
+/// threads, as the same raw masks: capabilities unknown to this crate
+/// (which `state` cannot hold) are kept as in the calling thread.
+/// Threads are enumerated via `/proc/self/task` until no new thread
+/// shows up. An error is returned if any thread failed to apply
+/// the state, in which case threads may be left in inconsistent states.
+pub fn set_all_threads(state: &CapState) -> Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let masks = masks_of(state)?;
+    masks.apply().map_err(|e| {
+        Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "failed to apply capabilities")
+    })?;
+    run(masks, false, None)
+}
+
+/// Apply capability state `state` to thread `tid` of the current process.
+///
+/// As with `set_all_threads`, capabilities unknown to this crate are set
+/// as in the calling thread.
+pub fn set_thread(tid: libc::pid_t, state: &CapState) -> Result<()> {
+    set_thread_masks(tid, &masks_of(state)?)
+}
+
+/// Apply raw capability masks `masks` to thread `tid` of the current process.
+///
+/// Unlike `set_thread`, all 64 bits of each set are applied as given.
+pub fn set_thread_masks(tid: libc::pid_t, masks: &Masks) -> Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if tid == this_thread::gettid() {
+        return masks.apply().map_err(|e| {
+            Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "failed to apply capabilities")
+        });
+    }
+    run(*masks, false, Some(tid))
+}
+
+// Raw masks of `state`, completed with the capabilities of the calling
+// thread which are unknown to this crate.
+fn masks_of(state: &CapState) -> Result<Masks> {
+    let current = Masks::current().map_err(|e| {
+        Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "failed to read capabilities")
+    })?;
+    let unknown = !to_bitmask(&all());
+    let masks = Masks::from(state);
+    Ok(Masks {
+        ambient: masks.ambient | (current.ambient & unknown),
+        bounding: masks.bounding | (current.bounding & unknown),
+        effective: masks.effective | (current.effective & unknown),
+        inheritable: masks.inheritable | (current.inheritable & unknown),
+        permitted: masks.permitted | (current.permitted & unknown),
+    })
+}
+
+/// Restrict capabilities of all threads of the current process to `allowed`.
+///
+/// Unlike `set_all_threads`, this only ever drops capabilities, and thus
+/// works for threads whose capabilities differ: each thread keeps its
+/// capabilities which are also in `allowed` (see `Masks::restrict`).
+pub fn restrict_all_threads(allowed: &Masks) -> Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Masks::current()
+        .and_then(|m| m.restrict(allowed).apply())
+        .map_err(|e| {
+            Error::from_kind(ErrorKind::Sys(e)).chain_err(|| "failed to restrict capabilities")
+        })?;
+    run(*allowed, true, None)
+}
+
+// Apply a pending change to thread `tid`, or to all other threads.
+fn run(masks: Masks, restrict: bool, tid: Option<libc::pid_t>) -> Result<()> {
+    PENDING.store(masks);
+    RESTRICT.store(restrict, Ordering::SeqCst);
+    DONE.store(0, Ordering::SeqCst);
+    FAILED.store(0, Ordering::SeqCst);
+
+    let signo = libc::SIGRTMAX();
+    let old = install_handler(signo)?;
+    let res = match tid {
+        Some(tid) => signal_one(signo, tid),
+        None => signal_all(signo),
+    };
+    let signalled = match res {
+        Ok(n) => {
+            restore_handler(signo, &old);
+            n
+        }
+        Err(e) => {
+            abandon_handler(signo, &old);
+            return Err(e);
+        }
+    };
+
+    let failed = FAILED.load(Ordering::SeqCst);
+    if failed > 0 {
+        bail!(
+            "{} out of {} threads failed to apply capabilities",
+            failed,
+            signalled
+        );
+    }
+    Ok(())
+}
+
+// Signal all other threads, and wait for them to apply the change.
+fn signal_all(signo: libc::c_int) -> Result<usize> {
+    let pid = unsafe { libc::getpid() };
+    let mut seen = HashSet::new();
+    seen.insert(this_thread::gettid());
+    let mut signalled = 0;
+    loop {
+        let mut new = 0;
+        for tid in tasks()? {
+            if !seen.insert(tid) {
+                continue;
+            }
+            let r = unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, signo) };
+            if r == 0 {
+                new += 1;
+            } else if errno::errno().0 != libc::ESRCH {
+                return Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+                    .chain_err(|| format!("tgkill error for thread {}", tid)));
+            }
+        }
+        signalled += new;
+        wait_done(signalled)?;
+        if new == 0 {
+            return Ok(signalled);
+        }
+    }
+}
+
+// Signal thread `tid`, and wait for it to apply the change.
+fn signal_one(signo: libc::c_int, tid: libc::pid_t) -> Result<usize> {
+    let pid = unsafe { libc::getpid() };
+    let r = unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, signo) };
+    if r != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+            .chain_err(|| format!("tgkill error for thread {}", tid)));
+    }
+    wait_done(1)?;
+    Ok(1)
+}
+
+// Wait until `count` threads have handled the signal.
+fn wait_done(count: usize) -> Result<()> {
+    let start = Instant::now();
+    while DONE.load(Ordering::SeqCst) < count {
+        if start.elapsed() > TIMEOUT {
+            bail!("timed out waiting for threads to apply capabilities");
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    Ok(())
+}
+
+// List all threads of the current process.
+fn tasks() -> Result<Vec<libc::pid_t>> {
+    let mut res = vec![];
+    for entry in fs::read_dir("/proc/self/task").chain_err(|| "failed to list threads")? {
+        let entry = entry.chain_err(|| "failed to list threads")?;
+        if let Some(tid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            res.push(tid);
+        }
+    }
+    Ok(res)
+}
+
+fn install_handler(signo: libc::c_int) -> Result<libc::sigaction> {
+    let mut sa: libc::sigaction = unsafe { std::mem::zeroed() };
+    sa.sa_sigaction = handler as *const () as libc::sighandler_t;
+    sa.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+    unsafe { libc::sigemptyset(&mut sa.sa_mask) };
+    let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sigaction(signo, &sa, &mut old) } != 0 {
+        return Err(
+            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "sigaction error")
+        );
+    }
+    Ok(old)
+}
+
+fn restore_handler(signo: libc::c_int, old: &libc::sigaction) {
+    unsafe { libc::sigaction(signo, old, std::ptr::null_mut()) };
+}
+
+// Restore the previous disposition after some threads did not acknowledge
+// the signal. These may still have it pending, and would be killed along
+// with the whole process if the default action were reinstated; switching
+// to `SIG_IGN` instead discards the pending signals.
+fn abandon_handler(signo: libc::c_int, old: &libc::sigaction) {
+    if old.sa_sigaction != libc::SIG_DFL {
+        return restore_handler(signo, old);
+    }
+    let mut sa: libc::sigaction = unsafe { std::mem::zeroed() };
+    sa.sa_sigaction = libc::SIG_IGN;
+    unsafe { libc::sigemptyset(&mut sa.sa_mask) };
+    unsafe { libc::sigaction(signo, &sa, std::ptr::null_mut()) };
+}
+
+extern "C" fn handler(_: libc::c_int, _: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    let saved = errno::errno();
+    let pending = PENDING.load();
+    let res = if RESTRICT.load(Ordering::SeqCst) {
+        Masks::current().and_then(|m| m.restrict(&pending).apply())
+    } else {
+        pending.apply()
+    };
+    if res.is_err() {
+        FAILED.fetch_add(1, Ordering::SeqCst);
+    }
+    DONE.fetch_add(1, Ordering::SeqCst);
+    errno::set_errno(saved);
+}
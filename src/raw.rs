@@ -0,0 +1,59 @@
+//! Raw capget/capset interface.
+//!
+//! This module exposes the kernel ABI of `capget(2)` and `capset(2)`, for
+//! advanced users implementing flows not modeled by the rest of this crate.
+//! Most programs should use the high-level API instead.
+//!
+//! ```rust
+//! use caps::raw::{self, CapUserData, CapUserHeader};
+//!
+//! let version = raw::probe_version().unwrap();
+//! assert!([raw::CAPS_V1, raw::CAPS_V2, raw::CAPS_V3].contains(&version));
+//!
+//! let mut hdr = CapUserHeader { version, pid: 0 };
+//! let mut data = CapUserData::default();
+//! raw::capget(&mut hdr, &mut data).unwrap();
+//! // Clear the Effective set.
+//! data.effective_s0 = 0;
+//! data.effective_s1 = 0;
+//! raw::capset(&mut hdr, &data).unwrap();
+//! ```
+//!
+//! Unlike the rest of this crate, these functions do not negotiate the
+//! interface version: `hdr.version` is passed as is, and overwritten by
+//! the kernel with its preferred version if rejected.
+
+use caps_core::{self, Libc, Syscalls};
+use errno;
+
+use errors::*;
+
+pub use caps_core::{CapUserData, CapUserDataUnit, CapUserHeader, CAPS_V1, CAPS_V2, CAPS_V3};
+
+/// Return the capget/capset interface version preferred by the kernel.
+///
+/// This issues `capget(2)` with version 0, which the kernel rejects,
+/// reporting its own preferred version.
+pub fn probe_version() -> Result<u32> {
+    caps_core::probe_version(&Libc).map_err(|e| sys_error(e, "capget error"))
+}
+
+/// Issue `capget(2)` with header `hdr`, filling `data`.
+///
+/// With version 1, only capabilities 0 to 31 are filled.
+pub fn capget(hdr: &mut CapUserHeader, data: &mut CapUserData) -> Result<()> {
+    Libc.capget(hdr, data)
+        .map_err(|e| sys_error(e, "capget error"))
+}
+
+/// Issue `capset(2)` with header `hdr` and `data`.
+///
+/// With version 1, capabilities 32 to 63 are ignored by the kernel.
+pub fn capset(hdr: &mut CapUserHeader, data: &CapUserData) -> Result<()> {
+    Libc.capset(hdr, data)
+        .map_err(|e| sys_error(e, "capset error"))
+}
+
+fn sys_error(e: caps_core::Errno, what: &'static str) -> Error {
+    Error::from_kind(ErrorKind::Sys(errno::Errno(e.0))).chain_err(|| what)
+}
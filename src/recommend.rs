@@ -0,0 +1,115 @@
+//! Least-privilege recommendations from observed capability usage.
+//!
+//! This closes the loop from observation to enforcement: uses recorded
+//! from the audit log (`usage`) or with eBPF (`ebpf`) are compared to the
+//! capabilities a workload was granted, yielding capabilities to drop and
+//! a policy which keeps only the exercised ones.
+//!
+//! ```rust,no_run
+//! use caps::recommend::Recommendation;
+//! use caps::{usage, CapState};
+//! use std::path::Path;
+//! use std::process::Command;
+//!
+//! let grant = CapState::current().unwrap();
+//! let log = Path::new(usage::AUDIT_LOG);
+//! let discovery = usage::record(&mut Command::new("my-daemon"), log).unwrap();
+//! let recommendation = Recommendation::from_discovery(&grant, &discovery);
+//! println!("{}", recommendation);
+//! print!("{}", recommendation.hardening().to_unit());
+//! ```
+//!
+//! Recommendations are only as good as the observed workload: code paths
+//! not exercised while recording are not accounted for.
+
+use super::{sorted, CapSet, CapState, CapsHashSet};
+use harden::HardenOptions;
+use policy::Policy;
+use std::fmt;
+use systemd::Hardening;
+use usage::{Discovery, Usage};
+
+/// Recommendation for a workload, given its grant and observed usage.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Recommendation {
+    /// Capabilities exercised by the workload, whose checks were granted
+    /// or not.
+    pub used: CapsHashSet,
+    /// Capabilities held in any set of the grant but never exercised.
+    pub drop: CapsHashSet,
+    /// Capabilities exercised but not permitted by the grant.
+    ///
+    /// Such checks failed, unless the system only audited them (e.g. in a
+    /// permissive SELinux domain). The workload may cope with the failure,
+    /// so these should be reviewed before being granted.
+    pub missing: CapsHashSet,
+}
+
+impl Recommendation {
+    /// Compare grant `grant` to capability uses `uses`.
+    ///
+    /// All `uses` are accounted for, whatever their process.
+    pub fn new(grant: &CapState, uses: &[Usage]) -> Recommendation {
+        let used: CapsHashSet = uses.iter().map(|u| u.capability).collect();
+        Recommendation::from_used(grant, used)
+    }
+
+    /// Compare grant `grant` to capabilities exercised by the workload of
+    /// `discovery`.
+    pub fn from_discovery(grant: &CapState, discovery: &Discovery) -> Recommendation {
+        Recommendation::from_used(grant, discovery.used.clone())
+    }
+
+    fn from_used(grant: &CapState, used: CapsHashSet) -> Recommendation {
+        let held: CapsHashSet = [
+            CapSet::Ambient,
+            CapSet::Bounding,
+            CapSet::Effective,
+            CapSet::Inheritable,
+            CapSet::Permitted,
+        ]
+        .iter()
+        .flat_map(|cset| grant.get(*cset).iter().cloned())
+        .collect();
+        Recommendation {
+            drop: held.difference(&used).cloned().collect(),
+            missing: used.difference(&grant.permitted).cloned().collect(),
+            used,
+        }
+    }
+
+    /// Return a policy allowing only exercised capabilities, with "no new
+    /// privileges".
+    pub fn policy(&self) -> Policy {
+        Policy {
+            no_new_privs: true,
+            ..Policy::default().allow(&self.used)
+        }
+    }
+
+    /// Return options to `harden` a thread with exercised capabilities only,
+    /// as a profile would.
+    pub fn options(&self) -> HardenOptions {
+        HardenOptions::new(&self.used)
+    }
+
+    /// Return systemd hardening settings from the recommended policy.
+    pub fn hardening(&self) -> Hardening {
+        Hardening::from_policy(&self.policy())
+    }
+}
+
+impl fmt::Display for Recommendation {
+    /// Format one line per category, listing lowercase capability names.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Used: {}", names(&self.used))?;
+        writeln!(f, "Drop: {}", names(&self.drop))?;
+        write!(f, "Missing: {}", names(&self.missing))
+    }
+}
+
+fn names(caps: &CapsHashSet) -> String {
+    let names: Vec<String> = sorted(caps)
+        .iter().map(|c| c.to_string().to_lowercase()).collect();
+    names.join(",")
+}
@@ -7,6 +7,7 @@
 
 use super::{ambient, CapSet, Capability, CapsHashSet};
 use errors::*;
+use std::fs;
 
 /// Check whether the running kernel supports the ambient set.
 ///
@@ -28,3 +29,62 @@ pub fn all_supported() -> CapsHashSet {
     }
     supported
 }
+
+/// Kernel settings restricting the creation of user namespaces.
+///
+/// Each setting is `None` when not available on the running kernel.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct UsernsKnobs {
+    /// `kernel.unprivileged_userns_clone` (Debian and derivatives).
+    pub unprivileged_userns_clone: Option<bool>,
+    /// `kernel.apparmor_restrict_unprivileged_userns` (Ubuntu).
+    pub apparmor_restrict_unprivileged_userns: Option<bool>,
+    /// `user.max_user_namespaces`.
+    pub max_user_namespaces: Option<u64>,
+}
+
+impl UsernsKnobs {
+    /// Read user namespace settings of the running kernel.
+    pub fn read() -> UsernsKnobs {
+        UsernsKnobs {
+            unprivileged_userns_clone: sysctl("kernel/unprivileged_userns_clone").map(|v| v != 0),
+            apparmor_restrict_unprivileged_userns: sysctl(
+                "kernel/apparmor_restrict_unprivileged_userns",
+            )
+            .map(|v| v != 0),
+            max_user_namespaces: sysctl("user/max_user_namespaces"),
+        }
+    }
+
+    /// Check whether these settings allow creating a user namespace.
+    ///
+    /// `privileged` tells whether the caller holds `CAP_SYS_ADMIN`, which
+    /// bypasses restrictions on unprivileged callers.
+    pub fn allow_creation(&self, privileged: bool) -> bool {
+        if self.max_user_namespaces == Some(0) {
+            return false;
+        }
+        privileged
+            || (self.unprivileged_userns_clone != Some(false)
+                && self.apparmor_restrict_unprivileged_userns != Some(true))
+    }
+}
+
+/// Check whether creating a user namespace is likely to succeed.
+///
+/// This is based on kernel settings (see `UsernsKnobs`) and on the
+/// capabilities of the current thread. Creation may still fail, e.g. if
+/// the process is multi-threaded, or if it is denied by a seccomp filter
+/// or an LSM policy.
+pub fn userns_creation_likely() -> bool {
+    let privileged =
+        super::has_cap(None, CapSet::Effective, Capability::CAP_SYS_ADMIN).unwrap_or(false);
+    UsernsKnobs::read().allow_creation(privileged)
+}
+
+// Read a numeric sysctl, relative to `/proc/sys`.
+fn sysctl(name: &str) -> Option<u64> {
+    fs::read_to_string(format!("/proc/sys/{}", name))
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+}
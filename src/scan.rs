@@ -0,0 +1,251 @@
+//! System-wide scanning of process capabilities.
+//!
+//! This walks `/proc` to inspect the capability state of every process on
+//! the host, as a foundation for auditing tools. Processes are inspected
+//! through their main thread.
+//!
+//! Processes routinely exit while being scanned: those are silently
+//! skipped, as are processes whose status can not be read.
+//!
+//! `host_report` flags over-privileged processes, and `leaks` flags
+//! descendants of a process which gained capabilities. Results are
+//! serializable with the `serde` feature.
+
+use libc;
+
+use super::{sorted, CapSet, CapState, Capability, CapsHashSet, Risk};
+use errors::*;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+
+/// Process holding a capability.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Holder {
+    /// Process ID.
+    pub pid: libc::pid_t,
+    /// Command name, from `/proc/<pid>/comm`.
+    pub comm: String,
+    /// Command line arguments, empty for kernel threads.
+    pub cmdline: Vec<String>,
+}
+
+impl Holder {
+    fn new(pid: libc::pid_t) -> Holder {
+        let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+        let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+        Holder {
+            pid,
+            comm: comm.trim_end_matches('\n').to_string(),
+            cmdline: cmdline
+                .split(|&b| b == 0)
+                .filter(|a| !a.is_empty())
+                .map(|a| String::from_utf8_lossy(a).into_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Iterate over all processes, yielding their PID and capability state.
+///
+/// Processes are yielded in `/proc` order. If `/proc` can not be listed,
+/// nothing is yielded.
+pub fn all_processes() -> impl Iterator<Item = (libc::pid_t, CapState)> {
+    pids().filter_map(|pid| CapState::from_proc(pid, pid).ok().map(|s| (pid, s)))
+}
+
+/// Return all processes holding capability `cap` in set `cset`.
+pub fn holders(cap: Capability, cset: CapSet) -> Vec<Holder> {
+    all_processes()
+        .filter(|(_, state)| state.get(cset).contains(&cap))
+        .map(|(pid, _)| Holder::new(pid))
+        .collect()
+}
+
+/// Reason for flagging a process as over-privileged.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Concern {
+    /// Root-equivalent capabilities are permitted.
+    RootEquivalent(Vec<Capability>),
+    /// The Bounding set is full, thus any capability can be regained.
+    FullBounding,
+    /// Capabilities are permitted to a process with non-root effective UID.
+    NonRootCaps(Vec<Capability>),
+}
+
+impl Concern {
+    /// Check the capability state `state` of a process with effective UID `euid`.
+    pub fn check(euid: u32, state: &CapState) -> Vec<Concern> {
+        let mut concerns = vec![];
+        let root_equivalent: CapsHashSet = state
+            .permitted
+            .iter()
+            .filter(|c| c.risk() == Risk::RootEquivalent)
+            .cloned()
+            .collect();
+        if !root_equivalent.is_empty() {
+            concerns.push(Concern::RootEquivalent(sorted(&root_equivalent)));
+        }
+        if super::all().is_subset(&state.bounding) {
+            concerns.push(Concern::FullBounding);
+        }
+        if euid != 0 && !state.permitted.is_empty() {
+            concerns.push(Concern::NonRootCaps(sorted(&state.permitted)));
+        }
+        concerns
+    }
+}
+
+/// Over-privileged process.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Flagged {
+    /// Flagged process.
+    pub process: Holder,
+    /// Effective UID of the process.
+    pub euid: u32,
+    /// Reasons for flagging this process.
+    pub concerns: Vec<Concern>,
+}
+
+/// Over-privileged processes of the host.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct HostReport {
+    /// Number of scanned processes.
+    pub scanned: usize,
+    /// Flagged processes, by PID.
+    pub flagged: Vec<Flagged>,
+}
+
+/// Scan all processes and flag over-privileged ones (see `Concern`).
+pub fn host_report() -> HostReport {
+    let mut report = HostReport::default();
+    for (pid, state) in all_processes() {
+        let euid = match euid(pid) {
+            Some(euid) => euid,
+            None => continue,
+        };
+        report.scanned += 1;
+        let concerns = Concern::check(euid, &state);
+        if !concerns.is_empty() {
+            report.flagged.push(Flagged {
+                process: Holder::new(pid),
+                euid,
+                concerns,
+            });
+        }
+    }
+    report.flagged.sort_by_key(|f| f.process.pid);
+    report
+}
+
+/// Capabilities gained in a set, compared to an ancestor.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Gain {
+    /// Set where capabilities were gained.
+    pub set: CapSet,
+    /// Gained capabilities, sorted.
+    pub caps: Vec<Capability>,
+}
+
+/// Descendant process which gained capabilities compared to an ancestor.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Leak {
+    /// Descendant process.
+    pub process: Holder,
+    /// Parent process ID of the descendant.
+    pub ppid: libc::pid_t,
+    /// Capabilities gained, per set.
+    pub gains: Vec<Gain>,
+}
+
+/// Compute capabilities held in `state` but not in `ancestor`, per set.
+pub fn gains(ancestor: &CapState, state: &CapState) -> Vec<Gain> {
+    let mut res = vec![];
+    for cset in &[
+        CapSet::Ambient,
+        CapSet::Bounding,
+        CapSet::Effective,
+        CapSet::Inheritable,
+        CapSet::Permitted,
+    ] {
+        let gained: CapsHashSet = state
+            .get(*cset)
+            .difference(ancestor.get(*cset))
+            .cloned()
+            .collect();
+        if !gained.is_empty() {
+            res.push(Gain {
+                set: *cset,
+                caps: sorted(&gained),
+            });
+        }
+    }
+    res
+}
+
+/// Find descendants of process `parent` which gained capabilities.
+///
+/// The process tree is built from `/proc`, and each descendant (at any
+/// depth) is compared with `parent`, so that for example ambient
+/// capabilities leaking down a supervision tree are detected.
+pub fn leaks(parent: libc::pid_t) -> Result<Vec<Leak>> {
+    let ancestor = CapState::from_proc(parent, parent)?;
+    let mut children: HashMap<libc::pid_t, Vec<libc::pid_t>> = HashMap::new();
+    for pid in pids() {
+        if let Some(ppid) = status_field(pid, "PPid:", 0) {
+            children.entry(ppid as libc::pid_t).or_default().push(pid);
+        }
+    }
+    let mut res = vec![];
+    let mut queue = vec![parent];
+    while let Some(ppid) = queue.pop() {
+        for &pid in children.get(&ppid).map(|c| c.as_slice()).unwrap_or(&[]) {
+            queue.push(pid);
+            let state = match CapState::from_proc(pid, pid) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let gains = gains(&ancestor, &state);
+            if !gains.is_empty() {
+                res.push(Leak {
+                    process: Holder::new(pid),
+                    ppid,
+                    gains,
+                });
+            }
+        }
+    }
+    res.sort_by_key(|l| l.process.pid);
+    Ok(res)
+}
+
+// Read the effective UID of process `pid`.
+fn euid(pid: libc::pid_t) -> Option<u32> {
+    status_field(pid, "Uid:", 1)
+}
+
+// Read the `n`-th numeric value of line `key` in the status of process `pid`.
+pub(crate) fn status_field(pid: libc::pid_t, key: &str, n: usize) -> Option<u32> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find(|l| l.starts_with(key))
+        .and_then(|l| l[key.len()..].split_whitespace().nth(n))
+        .and_then(|v| v.parse().ok())
+}
+
+// Iterate over PIDs of all processes.
+pub(crate) fn pids() -> impl Iterator<Item = libc::pid_t> {
+    fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().and_then(|s| s.parse().ok()))
+}
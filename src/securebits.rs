@@ -4,20 +4,97 @@
 //! flags, which can be used to disable special handling of capabilities
 //! for UID 0 (root).
 
-use errno;
-use libc;
-
 use errors::*;
 use nr;
+use {sys, trace};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::BitOr;
+use std::os::raw::c_ulong;
+
+/// Per-thread securebits flags, see `capabilities(7)`.
+#[derive(PartialEq, Eq, Hash, Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SecureBits(u32);
+
+impl SecureBits {
+    /// Disable special handling of capabilities for UID 0 (root).
+    pub const NOROOT: SecureBits = SecureBits(1 << 0);
+    /// Lock the `NOROOT` flag.
+    pub const NOROOT_LOCKED: SecureBits = SecureBits(1 << 1);
+    /// Do not adjust capabilities when UIDs switch between zero and non-zero.
+    pub const NO_SETUID_FIXUP: SecureBits = SecureBits(1 << 2);
+    /// Lock the `NO_SETUID_FIXUP` flag.
+    pub const NO_SETUID_FIXUP_LOCKED: SecureBits = SecureBits(1 << 3);
+    /// Retain permitted capabilities when all UIDs switch to non-zero.
+    pub const KEEP_CAPS: SecureBits = SecureBits(1 << 4);
+    /// Lock the `KEEP_CAPS` flag.
+    pub const KEEP_CAPS_LOCKED: SecureBits = SecureBits(1 << 5);
+    /// Disallow raising ambient capabilities.
+    pub const NO_CAP_AMBIENT_RAISE: SecureBits = SecureBits(1 << 6);
+    /// Lock the `NO_CAP_AMBIENT_RAISE` flag.
+    pub const NO_CAP_AMBIENT_RAISE_LOCKED: SecureBits = SecureBits(1 << 7);
+    /// All flags known to this crate.
+    pub const ALL: SecureBits = SecureBits(0xff);
+    /// All flags but `KEEP_CAPS`, all locked.
+    pub const LOCKDOWN: SecureBits = SecureBits(0xef);
+
+    /// Build flags from their raw kernel representation.
+    pub fn from_bits(bits: u32) -> SecureBits {
+        SecureBits(bits)
+    }
+
+    /// Return the raw kernel representation of these flags.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Check whether all flags in `other` are set.
+    pub fn contains(self, other: SecureBits) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for SecureBits {
+    type Output = SecureBits;
+
+    fn bitor(self, rhs: SecureBits) -> SecureBits {
+        SecureBits(self.0 | rhs.0)
+    }
+}
+
+/// Return the securebits flags of the current thread.
+pub fn get_securebits() -> Result<SecureBits> {
+    let ret = sys::prctl(nr::PR_GET_SECUREBITS, 0, 0);
+    trace::syscall("PR_GET_SECUREBITS", ret.into());
+    match ret {
+        r if r >= 0 => Ok(SecureBits(r as u32)),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
+            .chain_err(|| "PR_GET_SECUREBITS error")),
+    }
+}
+
+/// Return whether the current thread's "no new privileges" flag is set.
+pub fn has_no_new_privs() -> Result<bool> {
+    let ret = sys::prctl(nr::PR_GET_NO_NEW_PRIVS, 0, 0);
+    trace::syscall("PR_GET_NO_NEW_PRIVS", ret.into());
+    match ret {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
+            .chain_err(|| "PR_GET_NO_NEW_PRIVS error")),
+    }
+}
 
 /// Return whether the current thread's "keep capabilities" flag is set.
 pub fn has_keepcaps() -> Result<bool> {
-    let ret = unsafe { libc::prctl(nr::PR_GET_KEEPCAPS, 0, 0, 0) };
+    let ret = sys::prctl(nr::PR_GET_KEEPCAPS, 0, 0);
+    trace::syscall("PR_GET_KEEPCAPS", ret.into());
     match ret {
         0 => Ok(false),
         1 => Ok(true),
         _ => Err(
-            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "PR_GET_KEEPCAPS error")
+            Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "PR_GET_KEEPCAPS error")
         ),
     }
 }
@@ -25,11 +102,34 @@ pub fn has_keepcaps() -> Result<bool> {
 /// Set the value of the current thread's "keep capabilities" flag.
 pub fn set_keepcaps(keep_caps: bool) -> Result<()> {
     let flag = if keep_caps { 1 } else { 0 };
-    let ret = unsafe { libc::prctl(nr::PR_SET_KEEPCAPS, flag, 0, 0) };
+    let ret = sys::prctl(nr::PR_SET_KEEPCAPS, flag, 0);
+    trace::syscall("PR_SET_KEEPCAPS", ret.into());
     match ret {
         0 => Ok(()),
         _ => Err(
-            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "PR_SET_KEEPCAPS error")
+            Error::from_kind(ErrorKind::Sys(sys::errno())).chain_err(|| "PR_SET_KEEPCAPS error")
         ),
     }
 }
+
+/// Set the securebits flags of the current thread.
+pub fn set_securebits(bits: SecureBits) -> Result<()> {
+    let ret = sys::prctl(nr::PR_SET_SECUREBITS, bits.0 as c_ulong, 0);
+    trace::syscall("PR_SET_SECUREBITS", ret.into());
+    match ret {
+        0 => Ok(()),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
+            .chain_err(|| "PR_SET_SECUREBITS error")),
+    }
+}
+
+/// Set the current thread's "no new privileges" flag, which can not be unset.
+pub fn set_no_new_privs() -> Result<()> {
+    let ret = sys::prctl(nr::PR_SET_NO_NEW_PRIVS, 1, 0);
+    trace::syscall("PR_SET_NO_NEW_PRIVS", ret.into());
+    match ret {
+        0 => Ok(()),
+        _ => Err(Error::from_kind(ErrorKind::Sys(sys::errno()))
+            .chain_err(|| "PR_SET_NO_NEW_PRIVS error")),
+    }
+}
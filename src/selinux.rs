@@ -0,0 +1,86 @@
+//! SELinux names of capabilities.
+//!
+//! SELinux checks capabilities through permissions of the `capability`
+//! class (capabilities 0 to 31) and the `capability2` class (32 to 63),
+//! or of the `cap_userns` and `cap2_userns` classes for capabilities
+//! used inside non-initial user namespaces. Permission names are the
+//! capability names, in lowercase and without `cap_` prefix:
+//!
+//! ```rust
+//! use caps::selinux;
+//! use caps::Capability;
+//!
+//! let perm = selinux::permission(Capability::CAP_NET_RAW);
+//! assert_eq!((perm.class, perm.name.as_str()), ("capability", "net_raw"));
+//! // e.g. from `avc: denied { net_raw } ... tclass=capability`
+//! let cap = selinux::from_permission("capability", "net_raw");
+//! assert_eq!(cap, Some(Capability::CAP_NET_RAW));
+//! ```
+
+use super::{to_canonical, Capability};
+
+/// Class of capabilities 0 to 31.
+pub const CAPABILITY: &str = "capability";
+/// Class of capabilities 32 to 63.
+pub const CAPABILITY2: &str = "capability2";
+/// Class of capabilities 0 to 31, in non-initial user namespaces.
+pub const CAP_USERNS: &str = "cap_userns";
+/// Class of capabilities 32 to 63, in non-initial user namespaces.
+pub const CAP2_USERNS: &str = "cap2_userns";
+
+/// SELinux permission checked for a capability.
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Permission {
+    /// Security class (e.g. `capability`).
+    pub class: &'static str,
+    /// Permission name (e.g. `net_raw`).
+    pub name: String,
+}
+
+/// Return the permission checked for `cap` in the initial user namespace.
+pub fn permission(cap: Capability) -> Permission {
+    let class = if cap.index() < 32 {
+        CAPABILITY
+    } else {
+        CAPABILITY2
+    };
+    Permission {
+        class,
+        name: name(cap),
+    }
+}
+
+/// Return the permission checked for `cap` in a non-initial user namespace.
+pub fn userns_permission(cap: Capability) -> Permission {
+    let class = if cap.index() < 32 {
+        CAP_USERNS
+    } else {
+        CAP2_USERNS
+    };
+    Permission {
+        class,
+        name: name(cap),
+    }
+}
+
+/// Return the capability checked by permission `perm` of class `class`.
+///
+/// `None` is returned for unknown permissions, and for permissions which
+/// do not belong to `class`.
+pub fn from_permission(class: &str, perm: &str) -> Option<Capability> {
+    let cap: Capability = to_canonical(perm).parse().ok()?;
+    let in_class = match class {
+        CAPABILITY | CAP_USERNS => cap.index() < 32,
+        CAPABILITY2 | CAP2_USERNS => cap.index() >= 32,
+        _ => false,
+    };
+    if in_class && perm == name(cap) {
+        Some(cap)
+    } else {
+        None
+    }
+}
+
+fn name(cap: Capability) -> String {
+    cap.to_string().to_lowercase()[4..].to_string()
+}
@@ -0,0 +1,33 @@
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+use super::{to_canonical, Capability};
+
+impl Serialize for Capability {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Capability {
+    /// Deserialize from a capability name, in any case and with an
+    /// optional `CAP_` prefix (e.g. `"net_bind_service"`).
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        deserializer.deserialize_str(CapabilityVisitor)
+    }
+}
+
+struct CapabilityVisitor;
+
+impl<'de> de::Visitor<'de> for CapabilityVisitor {
+    type Value = Capability;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a capability name")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> ::std::result::Result<Capability, E> {
+        to_canonical(s).parse().map_err(E::custom)
+    }
+}
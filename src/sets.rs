@@ -0,0 +1,175 @@
+//! Predefined groups of capabilities.
+//!
+//! This module exposes ready-made groups of related capabilities,
+//! which can be combined with each other or with a `CapsHashSet`
+//! (e.g. as built by the `caps!` macro) via the `|` operator.
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::sets::NETWORK;
+//!
+//! fn main() {
+//!     let keep = NETWORK | caps![CAP_KILL];
+//!     assert!(keep.contains(&caps::Capability::CAP_NET_RAW));
+//!     assert!(keep.contains(&caps::Capability::CAP_KILL));
+//! }
+//! ```
+
+use super::{Capability, CapsHashSet};
+use std::ops::BitOr;
+
+/// A static group of capabilities.
+#[derive(Debug, Clone, Copy)]
+pub struct Group(&'static [Capability]);
+
+impl Group {
+    /// Return all capabilities in this group, as a slice.
+    pub fn as_slice(&self) -> &'static [Capability] {
+        self.0
+    }
+
+    /// Iterate over all capabilities in this group.
+    pub fn iter(&self) -> std::slice::Iter<'static, Capability> {
+        self.0.iter()
+    }
+
+    /// Check whether this group contains capability `cap`.
+    pub fn contains(&self, cap: Capability) -> bool {
+        self.0.contains(&cap)
+    }
+
+    /// Return an `HashSet` with all capabilities in this group.
+    pub fn to_set(&self) -> CapsHashSet {
+        self.0.iter().cloned().collect()
+    }
+}
+
+impl From<Group> for CapsHashSet {
+    fn from(group: Group) -> Self {
+        group.to_set()
+    }
+}
+
+impl BitOr for Group {
+    type Output = CapsHashSet;
+
+    fn bitor(self, rhs: Group) -> CapsHashSet {
+        self.iter().chain(rhs.iter()).cloned().collect()
+    }
+}
+
+impl BitOr<CapsHashSet> for Group {
+    type Output = CapsHashSet;
+
+    fn bitor(self, mut rhs: CapsHashSet) -> CapsHashSet {
+        rhs.extend(self.iter().cloned());
+        rhs
+    }
+}
+
+impl BitOr<Group> for CapsHashSet {
+    type Output = CapsHashSet;
+
+    fn bitor(mut self, rhs: Group) -> CapsHashSet {
+        self.extend(rhs.iter().cloned());
+        self
+    }
+}
+
+/// Capabilities related to networking.
+pub const NETWORK: Group = Group(&[
+    Capability::CAP_NET_BIND_SERVICE,
+    Capability::CAP_NET_BROADCAST,
+    Capability::CAP_NET_ADMIN,
+    Capability::CAP_NET_RAW,
+]);
+
+/// Capabilities related to files ownership, permissions and attributes.
+pub const FILESYSTEM: Group = Group(&[
+    Capability::CAP_CHOWN,
+    Capability::CAP_DAC_OVERRIDE,
+    Capability::CAP_DAC_READ_SEARCH,
+    Capability::CAP_FOWNER,
+    Capability::CAP_FSETID,
+    Capability::CAP_LINUX_IMMUTABLE,
+    Capability::CAP_MKNOD,
+    Capability::CAP_LEASE,
+    Capability::CAP_SETFCAP,
+]);
+
+/// Capabilities related to processes, credentials and IPC.
+pub const PROCESS: Group = Group(&[
+    Capability::CAP_KILL,
+    Capability::CAP_SETGID,
+    Capability::CAP_SETUID,
+    Capability::CAP_SETPCAP,
+    Capability::CAP_IPC_LOCK,
+    Capability::CAP_IPC_OWNER,
+    Capability::CAP_SYS_PTRACE,
+    Capability::CAP_SYS_NICE,
+    Capability::CAP_SYS_RESOURCE,
+]);
+
+/// Capabilities related to system-wide administration.
+pub const SYSTEM: Group = Group(&[
+    Capability::CAP_SYS_MODULE,
+    Capability::CAP_SYS_RAWIO,
+    Capability::CAP_SYS_CHROOT,
+    Capability::CAP_SYS_PACCT,
+    Capability::CAP_SYS_ADMIN,
+    Capability::CAP_SYS_BOOT,
+    Capability::CAP_SYS_TIME,
+    Capability::CAP_SYS_TTY_CONFIG,
+    Capability::CAP_SYSLOG,
+    Capability::CAP_WAKE_ALARM,
+    Capability::CAP_BLOCK_SUSPEND,
+]);
+
+/// Capabilities related to the kernel audit subsystem.
+pub const AUDIT: Group = Group(&[
+    Capability::CAP_AUDIT_WRITE,
+    Capability::CAP_AUDIT_CONTROL,
+    Capability::CAP_AUDIT_READ,
+]);
+
+/// Capabilities related to Mandatory Access Control.
+pub const MAC: Group = Group(&[Capability::CAP_MAC_OVERRIDE, Capability::CAP_MAC_ADMIN]);
+
+/// Capabilities which can be escalated into full root access.
+///
+/// This contains all capabilities whose `risk()` is `Risk::RootEquivalent`.
+pub const ROOT_EQUIVALENT: Group = Group(&[
+    Capability::CAP_CHOWN,
+    Capability::CAP_DAC_OVERRIDE,
+    Capability::CAP_DAC_READ_SEARCH,
+    Capability::CAP_FOWNER,
+    Capability::CAP_SETGID,
+    Capability::CAP_SETUID,
+    Capability::CAP_SETFCAP,
+    Capability::CAP_SYS_MODULE,
+    Capability::CAP_SYS_RAWIO,
+    Capability::CAP_SYS_PTRACE,
+    Capability::CAP_SYS_ADMIN,
+    Capability::CAP_SYS_BOOT,
+    Capability::CAP_MKNOD,
+    Capability::CAP_MAC_ADMIN,
+]);
+
+/// Capabilities granted to containers by default by Docker (and Moby).
+pub const DOCKER_DEFAULT: Group = Group(&[
+    Capability::CAP_CHOWN,
+    Capability::CAP_DAC_OVERRIDE,
+    Capability::CAP_FOWNER,
+    Capability::CAP_FSETID,
+    Capability::CAP_KILL,
+    Capability::CAP_SETGID,
+    Capability::CAP_SETUID,
+    Capability::CAP_SETPCAP,
+    Capability::CAP_NET_BIND_SERVICE,
+    Capability::CAP_NET_RAW,
+    Capability::CAP_SYS_CHROOT,
+    Capability::CAP_MKNOD,
+    Capability::CAP_AUDIT_WRITE,
+    Capability::CAP_SETFCAP,
+]);
@@ -0,0 +1,204 @@
+//! Simplified API in the style of libcap-ng.
+//!
+//! Daemons written against libcap-ng build a desired capability state
+//! step by step, then apply it in one go:
+//!
+//! ```rust,no_run
+//! use caps::simple::{self, ADD, BOUNDING, CAPS, EFFECTIVE, PERMITTED};
+//! use caps::Capability;
+//!
+//! simple::clear(CAPS | BOUNDING).unwrap();
+//! simple::update(ADD, EFFECTIVE | PERMITTED, Capability::CAP_NET_BIND_SERVICE).unwrap();
+//! simple::apply(CAPS | BOUNDING).unwrap();
+//! ```
+//!
+//! This module mirrors that workflow. Each thread holds a working copy of
+//! its capability state, initialized from the thread on first use (or
+//! reloaded with `get_caps_process`). `clear`, `fill` and `update` only
+//! alter the working copy, and `apply` makes the selected sets effective.
+
+use super::{CapSet, CapState, Capability};
+use errors::*;
+use runtime;
+use std::cell::RefCell;
+use std::ops::BitOr;
+
+/// Selection of capability sets, as libcap-ng's `capng_type_t`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Sets(u8);
+
+/// Effective set.
+pub const EFFECTIVE: Sets = Sets(1 << 0);
+/// Permitted set.
+pub const PERMITTED: Sets = Sets(1 << 1);
+/// Inheritable set.
+pub const INHERITABLE: Sets = Sets(1 << 2);
+/// Bounding set.
+pub const BOUNDING: Sets = Sets(1 << 3);
+/// Ambient set.
+pub const AMBIENT: Sets = Sets(1 << 4);
+/// Effective, Permitted and Inheritable sets, as `CAPNG_SELECT_CAPS`.
+pub const CAPS: Sets = Sets(0x7);
+/// All sets, as `CAPNG_SELECT_ALL`.
+pub const ALL: Sets = Sets(0x1f);
+
+// Flags of each set.
+const FLAGS: [(Sets, CapSet); 5] = [
+    (EFFECTIVE, CapSet::Effective),
+    (PERMITTED, CapSet::Permitted),
+    (INHERITABLE, CapSet::Inheritable),
+    (BOUNDING, CapSet::Bounding),
+    (AMBIENT, CapSet::Ambient),
+];
+
+impl Sets {
+    /// Check whether all sets in `other` are selected.
+    pub fn contains(self, other: Sets) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    // Iterate over selected sets.
+    fn iter(self) -> impl Iterator<Item = CapSet> {
+        FLAGS
+            .iter()
+            .filter(move |&&(flag, _)| self.contains(flag))
+            .map(|&(_, cset)| cset)
+    }
+}
+
+impl BitOr for Sets {
+    type Output = Sets;
+
+    fn bitor(self, rhs: Sets) -> Sets {
+        Sets(self.0 | rhs.0)
+    }
+}
+
+/// Update action, as libcap-ng's `capng_act_t`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Action {
+    /// Add capabilities to sets.
+    Add,
+    /// Drop capabilities from sets.
+    Drop,
+}
+
+/// Add capabilities, as `CAPNG_ADD`.
+pub const ADD: Action = Action::Add;
+/// Drop capabilities, as `CAPNG_DROP`.
+pub const DROP: Action = Action::Drop;
+
+/// Summary of held capabilities, as libcap-ng's `capng_results_t`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Summary {
+    /// No capabilities, as `CAPNG_NONE`.
+    None,
+    /// Some capabilities, as `CAPNG_PARTIAL`.
+    Partial,
+    /// All known capabilities, as `CAPNG_FULL`.
+    Full,
+}
+
+thread_local! {
+    static WORKING: RefCell<Option<CapState>> = const { RefCell::new(None) };
+}
+
+// Run `f` on the working copy, initializing it if needed.
+fn with_working<F, R>(f: F) -> Result<R>
+where
+    F: FnOnce(&mut CapState) -> R,
+{
+    WORKING.with(|w| {
+        let mut working = w.borrow_mut();
+        if working.is_none() {
+            *working = Some(CapState::current()?);
+        }
+        Ok(f(working.as_mut().unwrap()))
+    })
+}
+
+/// Reload the working copy from the current thread.
+pub fn get_caps_process() -> Result<()> {
+    let state = CapState::current()?;
+    WORKING.with(|w| *w.borrow_mut() = Some(state));
+    Ok(())
+}
+
+/// Empty the selected sets of the working copy.
+pub fn clear(select: Sets) -> Result<()> {
+    with_working(|state| {
+        for cset in select.iter() {
+            state.get_mut(cset).clear();
+        }
+    })
+}
+
+/// Fill the selected sets of the working copy with all capabilities
+/// supported by the running kernel.
+pub fn fill(select: Sets) -> Result<()> {
+    let supported = runtime::all_supported();
+    with_working(|state| {
+        for cset in select.iter() {
+            *state.get_mut(cset) = supported.clone();
+        }
+    })
+}
+
+/// Add or drop `cap` to/from the given sets of the working copy.
+pub fn update(action: Action, sets: Sets, cap: Capability) -> Result<()> {
+    updatev(action, sets, &[cap])
+}
+
+/// Add or drop all of `caps` to/from the given sets of the working copy.
+pub fn updatev(action: Action, sets: Sets, caps: &[Capability]) -> Result<()> {
+    with_working(|state| {
+        for cset in sets.iter() {
+            let set = state.get_mut(cset);
+            for cap in caps {
+                match action {
+                    Action::Add => set.insert(*cap),
+                    Action::Drop => set.remove(cap),
+                };
+            }
+        }
+    })
+}
+
+/// Check whether `cap` is in all the given sets of the working copy.
+pub fn have_capability(sets: Sets, cap: Capability) -> Result<bool> {
+    with_working(|state| sets.iter().all(|cset| state.get(cset).contains(&cap)))
+}
+
+/// Summarize capabilities in the selected sets of the working copy.
+///
+/// As `capng_have_capabilities`, this returns `Summary::None` if all the
+/// selected sets are empty, `Summary::Full` if they all hold every
+/// capability supported by the running kernel, and `Summary::Partial`
+/// otherwise. This allows programs to decide between running fully
+/// privileged, degrading features or refusing to start.
+pub fn have_capabilities(select: Sets) -> Result<Summary> {
+    let supported = runtime::all_supported();
+    with_working(|state| {
+        if select.iter().all(|cset| state.get(cset).is_empty()) {
+            Summary::None
+        } else if select.iter().all(|cset| state.get(cset).is_superset(&supported)) {
+            Summary::Full
+        } else {
+            Summary::Partial
+        }
+    })
+}
+
+/// Apply the selected sets of the working copy to the current thread.
+///
+/// Unselected sets are left untouched. As usual, the Bounding and
+/// Permitted sets can only be lowered.
+pub fn apply(select: Sets) -> Result<()> {
+    let mut state = CapState::current()?;
+    with_working(|working| {
+        for cset in select.iter() {
+            *state.get_mut(cset) = working.get(cset).clone();
+        }
+    })?;
+    state.apply()
+}
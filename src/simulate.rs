@@ -0,0 +1,184 @@
+//! Simulate capability changes across a chain of processes.
+//!
+//! This module allows to describe a sequence of `fork(2)`, `execve(2)`
+//! and `setuid(2)` steps starting from a given process, and to compute
+//! the capability state at each step without actually performing them.
+
+use libc;
+
+use super::{CapState, Capability};
+use errors::*;
+use exec::{self, ExecParams};
+use file::FileCaps;
+use securebits::{self, SecureBits};
+
+/// Simulated process credentials and capabilities.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Process {
+    /// Capability state.
+    pub state: CapState,
+    /// Real UID.
+    pub uid: u32,
+    /// Effective UID.
+    pub euid: u32,
+    /// Saved set-user-ID.
+    pub suid: u32,
+    /// Securebits flags.
+    pub securebits: SecureBits,
+    /// Whether the "no new privileges" flag is set.
+    pub no_new_privs: bool,
+}
+
+impl Process {
+    /// Capture the current thread as a starting point.
+    pub fn current() -> Result<Process> {
+        let (mut uid, mut euid, mut suid) = (0, 0, 0);
+        if unsafe { libc::getresuid(&mut uid, &mut euid, &mut suid) } != 0 {
+            bail!("getresuid error");
+        }
+        Ok(Process {
+            state: CapState::current()?,
+            uid,
+            euid,
+            suid,
+            securebits: securebits::get_securebits()?,
+            no_new_privs: securebits::has_no_new_privs()?,
+        })
+    }
+}
+
+/// An executed file.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct ExecFile {
+    /// Capabilities attached to the file, if any.
+    pub file_caps: Option<FileCaps>,
+    /// Owner of the file, if it has the set-user-ID bit.
+    pub setuid_owner: Option<u32>,
+    /// Whether the file changes the effective GID (set-group-ID bit).
+    pub setgid: bool,
+}
+
+/// A single step in a chain.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Step {
+    /// Fork a child process, which inherits the parent state.
+    Fork,
+    /// Execute a file.
+    Exec(ExecFile),
+    /// Switch to a new UID, as per `setuid(2)`.
+    SetUid(u32),
+}
+
+/// A chain of steps, starting from a process.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Chain {
+    start: Process,
+    steps: Vec<Step>,
+}
+
+impl Chain {
+    /// Start a new chain from process `start`.
+    pub fn new(start: Process) -> Chain {
+        Chain {
+            start,
+            steps: vec![],
+        }
+    }
+
+    /// Append a `fork(2)` step.
+    pub fn fork(mut self) -> Chain {
+        self.steps.push(Step::Fork);
+        self
+    }
+
+    /// Append an `execve(2)` step.
+    pub fn exec(mut self, file: ExecFile) -> Chain {
+        self.steps.push(Step::Exec(file));
+        self
+    }
+
+    /// Append a `setuid(2)` step.
+    pub fn setuid(mut self, uid: u32) -> Chain {
+        self.steps.push(Step::SetUid(uid));
+        self
+    }
+
+    /// Return all steps in this chain.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Simulate this chain, returning the process after each step.
+    ///
+    /// If a step would fail (e.g. denied exec or setuid), an error
+    /// is returned.
+    pub fn simulate(&self) -> Result<Vec<Process>> {
+        let mut cur = self.start.clone();
+        let mut res = Vec::with_capacity(self.steps.len());
+        for (i, step) in self.steps.iter().enumerate() {
+            cur = apply(&cur, step).chain_err(|| format!("step {} ({:?}) failed", i, step))?;
+            res.push(cur.clone());
+        }
+        Ok(res)
+    }
+}
+
+fn apply(p: &Process, step: &Step) -> Result<Process> {
+    match *step {
+        Step::Fork => Ok(p.clone()),
+        Step::Exec(ref file) => {
+            let params = ExecParams {
+                uid: p.uid,
+                euid: p.euid,
+                setuid_owner: file.setuid_owner,
+                setgid: file.setgid,
+                securebits: p.securebits,
+                no_new_privs: p.no_new_privs,
+            };
+            let (state, euid) = exec::execve(&p.state, file.file_caps.as_ref(), &params)?;
+            let securebits = SecureBits::from_bits(p.securebits.bits() & !SecureBits::KEEP_CAPS.bits());
+            Ok(Process {
+                state,
+                uid: p.uid,
+                euid,
+                suid: euid,
+                securebits,
+                no_new_privs: p.no_new_privs,
+            })
+        }
+        Step::SetUid(uid) => setuid(p, uid),
+    }
+}
+
+fn setuid(p: &Process, uid: u32) -> Result<Process> {
+    let mut res = p.clone();
+    if p.state.effective.contains(&Capability::CAP_SETUID) {
+        res.uid = uid;
+        res.euid = uid;
+        res.suid = uid;
+    } else if uid == p.uid || uid == p.suid {
+        res.euid = uid;
+    } else {
+        bail!("setuid to {} denied without CAP_SETUID", uid);
+    }
+    if p.securebits.contains(SecureBits::NO_SETUID_FIXUP) {
+        return Ok(res);
+    }
+
+    let was_root = p.uid == 0 || p.euid == 0 || p.suid == 0;
+    let is_root = res.uid == 0 || res.euid == 0 || res.suid == 0;
+    if was_root && !is_root {
+        if !p.securebits.contains(SecureBits::KEEP_CAPS) {
+            res.state.permitted.clear();
+            res.state.effective.clear();
+        }
+        res.state.ambient.clear();
+    }
+    if p.euid == 0 && res.euid != 0 {
+        res.state.effective.clear();
+    }
+    if p.euid != 0 && res.euid == 0 {
+        res.state.effective = res.state.permitted.clone();
+    }
+    Ok(res)
+}
@@ -0,0 +1,333 @@
+use super::{ambient, base, bounding, from_bitmask, sorted, CapSet, Capability, CapsHashSet, Target};
+use compare::Report;
+use lowlevel::Masks;
+use observe::{self, CapsDiff};
+use errors::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use {runtime, trace};
+
+/// Capability state of a thread, across all five sets.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CapState {
+    /// Ambient capabilities set.
+    pub ambient: CapsHashSet,
+    /// Bounding capabilities set.
+    pub bounding: CapsHashSet,
+    /// Effective capabilities set.
+    pub effective: CapsHashSet,
+    /// Inheritable capabilities set.
+    pub inheritable: CapsHashSet,
+    /// Permitted capabilities set.
+    pub permitted: CapsHashSet,
+}
+
+impl CapState {
+    /// Start building a capability state, from empty sets.
+    pub fn builder() -> CapStateBuilder {
+        CapStateBuilder::default()
+    }
+
+    /// Read the capability state of the current thread.
+    pub fn current() -> Result<CapState> {
+        Ok(CapState {
+            ambient: ambient::read()?,
+            bounding: bounding::read()?,
+            effective: base::read(0, CapSet::Effective)?,
+            inheritable: base::read(0, CapSet::Inheritable)?,
+            permitted: base::read(0, CapSet::Permitted)?,
+        })
+    }
+
+    /// Return the content of set `cset`.
+    pub fn get(&self, cset: CapSet) -> &CapsHashSet {
+        match cset {
+            CapSet::Ambient => &self.ambient,
+            CapSet::Bounding => &self.bounding,
+            CapSet::Effective => &self.effective,
+            CapSet::Inheritable => &self.inheritable,
+            CapSet::Permitted => &self.permitted,
+        }
+    }
+
+    /// Return a mutable reference to the content of set `cset`.
+    pub fn get_mut(&mut self, cset: CapSet) -> &mut CapsHashSet {
+        match cset {
+            CapSet::Ambient => &mut self.ambient,
+            CapSet::Bounding => &mut self.bounding,
+            CapSet::Effective => &mut self.effective,
+            CapSet::Inheritable => &mut self.inheritable,
+            CapSet::Permitted => &mut self.permitted,
+        }
+    }
+
+    /// Apply this capability state to the current thread.
+    ///
+    /// Sets are changed in an order compatible with kernel invariants:
+    /// Bounding, Inheritable, Effective, Permitted and finally Ambient.
+    /// Capabilities can only be dropped from Bounding and Permitted sets.
+    pub fn apply(&self) -> Result<()> {
+        trace::change("apply", Target::CurrentThread, None, || self.apply_notify())
+    }
+
+    fn apply_notify(&self) -> Result<()> {
+        if !observe::is_active() {
+            return self.apply_sets();
+        }
+        let before = CapState::current()?;
+        self.apply_sets()?;
+        let after = CapState::current()?;
+        for cset in &[
+            CapSet::Bounding,
+            CapSet::Inheritable,
+            CapSet::Effective,
+            CapSet::Permitted,
+            CapSet::Ambient,
+        ] {
+            observe::notify(&CapsDiff::new(
+                Target::CurrentThread,
+                *cset,
+                before.get(*cset),
+                after.get(*cset),
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_sets(&self) -> Result<()> {
+        let bset = bounding::read()?;
+        if !self.bounding.is_subset(&bset) {
+            bail!("capabilities cannot be raised in bounding set");
+        }
+        for c in bset.difference(&self.bounding) {
+            bounding::drop(*c)?;
+        }
+        ambient::clear()?;
+        base::set(0, CapSet::Inheritable, self.inheritable.clone())?;
+        base::set(0, CapSet::Effective, self.effective.clone())?;
+        base::set(0, CapSet::Permitted, self.permitted.clone())?;
+        ambient::set(&self.ambient)
+    }
+
+    /// Apply this state to the current thread, then verify the result.
+    ///
+    /// The state is read back after being applied, and an
+    /// `ErrorKind::StateMismatch` error is returned if it does not exactly
+    /// match.
+    pub fn apply_strict(&self) -> Result<()> {
+        self.apply()?;
+        match CapState::current()?.diff(self) {
+            Some(mismatch) => bail!(ErrorKind::StateMismatch(mismatch)),
+            None => Ok(()),
+        }
+    }
+
+    /// Compare this state with the `expected` one.
+    ///
+    /// See `Report::between` for a comparison in both directions.
+    pub fn diff(&self, expected: &CapState) -> Option<StateMismatch> {
+        let sets: Vec<_> = Report::between(self, expected)
+            .sets
+            .into_iter()
+            .map(|s| SetMismatch {
+                set: s.set,
+                missing: s.added,
+                unexpected: s.removed,
+            })
+            .collect();
+        if sets.is_empty() {
+            None
+        } else {
+            Some(StateMismatch { sets })
+        }
+    }
+
+    /// Read the capability state of thread `tid` in process `pid`, via procfs.
+    ///
+    /// Unlike `current`, this can also retrieve Ambient and Bounding sets
+    /// of other threads.
+    pub fn from_proc(pid: i32, tid: i32) -> Result<CapState> {
+        let path = format!("/proc/{}/task/{}/status", pid, tid);
+        let status = fs::read_to_string(&path).chain_err(|| format!("failed to read {}", path))?;
+        CapState::from_proc_status(&status)
+    }
+
+    /// Parse a capability state from the content of a `/proc/<pid>/status` file.
+    pub fn from_proc_status(status: &str) -> Result<CapState> {
+        let masks = proc_status_masks(status)?;
+        Ok(CapState {
+            ambient: from_bitmask(masks.ambient),
+            bounding: from_bitmask(masks.bounding),
+            effective: from_bitmask(masks.effective),
+            inheritable: from_bitmask(masks.inheritable),
+            permitted: from_bitmask(masks.permitted),
+        })
+    }
+}
+
+// Parse raw capability masks from the content of a `/proc/<pid>/status`
+// file, keeping capabilities unknown to this crate.
+pub(crate) fn proc_status_masks(status: &str) -> Result<Masks> {
+    let mut masks = Masks::default();
+    let mut found = 0;
+    for line in status.lines() {
+        let mut parts = line.splitn(2, ':');
+        let mask = match parts.next() {
+            Some("CapInh") => &mut masks.inheritable,
+            Some("CapPrm") => &mut masks.permitted,
+            Some("CapEff") => &mut masks.effective,
+            Some("CapBnd") => &mut masks.bounding,
+            Some("CapAmb") => &mut masks.ambient,
+            _ => continue,
+        };
+        let value = parts.next().unwrap_or("").trim();
+        *mask = match u64::from_str_radix(value, 16) {
+            Ok(m) => m,
+            Err(_) => bail!("invalid capability mask '{}'", value),
+        };
+        found += 1;
+    }
+    // Ambient set is only reported since Linux 4.3.
+    if found < 4 {
+        bail!("missing capabilities in process status");
+    }
+    Ok(masks)
+}
+
+/// Builder of capability states, validating kernel invariants.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate caps;
+/// use caps::CapState;
+///
+/// # fn main() {
+/// let state = CapState::builder()
+///     .permitted(caps![CAP_NET_BIND_SERVICE, CAP_NET_RAW])
+///     .effective(caps![CAP_NET_BIND_SERVICE])
+///     .inheritable(caps![CAP_NET_BIND_SERVICE])
+///     .ambient(caps![CAP_NET_BIND_SERVICE])
+///     .bounding_all_supported()
+///     .build()
+///     .unwrap();
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct CapStateBuilder {
+    state: CapState,
+}
+
+impl CapStateBuilder {
+    /// Set the Ambient set.
+    pub fn ambient(mut self, caps: CapsHashSet) -> CapStateBuilder {
+        self.state.ambient = caps;
+        self
+    }
+
+    /// Set the Bounding set.
+    pub fn bounding(mut self, caps: CapsHashSet) -> CapStateBuilder {
+        self.state.bounding = caps;
+        self
+    }
+
+    /// Set the Bounding set to all capabilities supported by the running
+    /// kernel.
+    pub fn bounding_all_supported(mut self) -> CapStateBuilder {
+        self.state.bounding = runtime::all_supported();
+        self
+    }
+
+    /// Set the Effective set.
+    pub fn effective(mut self, caps: CapsHashSet) -> CapStateBuilder {
+        self.state.effective = caps;
+        self
+    }
+
+    /// Set the Inheritable set.
+    pub fn inheritable(mut self, caps: CapsHashSet) -> CapStateBuilder {
+        self.state.inheritable = caps;
+        self
+    }
+
+    /// Set the Permitted set.
+    pub fn permitted(mut self, caps: CapsHashSet) -> CapStateBuilder {
+        self.state.permitted = caps;
+        self
+    }
+
+    /// Validate and return the built state.
+    ///
+    /// As enforced by the kernel, the Effective set must be a subset of
+    /// the Permitted set, and the Ambient set a subset of both the
+    /// Permitted and Inheritable sets.
+    pub fn build(self) -> Result<CapState> {
+        let state = self.state;
+        let outside = sorted(state.effective.difference(&state.permitted));
+        if !outside.is_empty() {
+            bail!("effective capabilities {:?} are not permitted", outside);
+        }
+        let outside = sorted(
+            state
+                .ambient
+                .iter()
+                .filter(|c| !state.permitted.contains(c) || !state.inheritable.contains(c)),
+        );
+        if !outside.is_empty() {
+            bail!(
+                "ambient capabilities {:?} are not both permitted and inheritable",
+                outside
+            );
+        }
+        Ok(state)
+    }
+}
+
+/// Differences in a single capability set.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SetMismatch {
+    /// Differing set.
+    pub set: CapSet,
+    /// Expected capabilities which are not held, sorted.
+    pub missing: Vec<Capability>,
+    /// Held capabilities which are not expected, sorted.
+    pub unexpected: Vec<Capability>,
+}
+
+/// Differences between an actual and an expected capability state.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StateMismatch {
+    /// Differing sets.
+    pub sets: Vec<SetMismatch>,
+}
+
+impl fmt::Display for StateMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "capability state mismatch")?;
+        for m in &self.sets {
+            write!(
+                f,
+                "; {:?} set missing {:?}, unexpected {:?}",
+                m.set, m.missing, m.unexpected
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ::std::error::Error for StateMismatch {}
+
+/// Verify that the current thread runs with exactly capability state `expected`.
+///
+/// On mismatch, an `ErrorKind::StateMismatch` error details which sets and
+/// capabilities differ. Failing to read the current state is reported as
+/// such, not as a mismatch.
+pub fn verify_state(expected: &CapState) -> Result<()> {
+    if let Some(mismatch) = CapState::current()?.diff(expected) {
+        bail!(ErrorKind::StateMismatch(mismatch));
+    }
+    Ok(())
+}
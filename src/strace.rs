@@ -0,0 +1,151 @@
+//! Inference of required capabilities from strace logs.
+//!
+//! Where neither audit nor eBPF is available, running a program under
+//! `strace -f` unprivileged still reveals the syscalls failing for lack of
+//! privileges. Using the `knowledge` base, this module suggests which
+//! capabilities would let them succeed:
+//!
+//! ```rust
+//! use caps::strace;
+//! use caps::Capability;
+//!
+//! let log = "[pid  42] setuid(0) = -1 EPERM (Operation not permitted)\n";
+//! let suggestions = strace::infer(log);
+//! assert_eq!(suggestions[0].capability, Capability::CAP_SETUID);
+//! ```
+//!
+//! Only calls failing with `EPERM` or `EACCES` are considered, and these
+//! may have other causes (e.g. file modes or seccomp filters), so the
+//! result is a starting point rather than an exact set.
+
+use super::{sorted, Capability};
+use knowledge;
+
+/// Syscall completion, as logged by strace.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Call {
+    /// Calling process, if logged (e.g. with `-f`).
+    pub pid: Option<i32>,
+    /// Syscall name.
+    pub syscall: String,
+    /// Error name (e.g. `"EPERM"`), if the call failed.
+    pub errno: Option<String>,
+}
+
+impl Call {
+    /// Check whether the call failed with a permission error.
+    pub fn denied(&self) -> bool {
+        matches!(self.errno.as_deref(), Some("EPERM") | Some("EACCES"))
+    }
+}
+
+/// Capability likely needed by a traced program.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Suggestion {
+    /// Suggested capability.
+    pub capability: Capability,
+    /// Denied syscalls it may allow, sorted and deduplicated.
+    pub syscalls: Vec<String>,
+    /// Number of denied calls it may allow.
+    pub failures: usize,
+}
+
+/// Parse a syscall completion from strace output line `line`.
+///
+/// Lines with a PID prefix (`[pid N]` or `N`), timestamps, and resumed
+/// calls are supported. Unfinished calls, signals and exits yield `None`.
+pub fn parse_line(line: &str) -> Option<Call> {
+    let mut rest = line.trim_start();
+    let mut pid = None;
+    if rest.starts_with("[pid") {
+        let end = rest.find(']')?;
+        pid = rest[4..end].trim().parse().ok();
+        rest = rest[end + 1..].trim_start();
+    }
+    // Leading PID (with `-o`) and timestamps.
+    while let Some(token) = rest.split_whitespace().next() {
+        if !token.starts_with(|c: char| c.is_ascii_digit())
+            || !token
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ':' || c == '.')
+        {
+            break;
+        }
+        if pid.is_none() && token.chars().all(|c| c.is_ascii_digit()) {
+            pid = token.parse().ok();
+        }
+        rest = rest[token.len()..].trim_start();
+    }
+    let syscall = if let Some(resumed) = rest.strip_prefix("<... ") {
+        &resumed[..resumed.find(" resumed>")?]
+    } else {
+        &rest[..rest.find('(')?]
+    };
+    if syscall.is_empty()
+        || !syscall
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    let result = &rest[rest.rfind(") = ")? + 4..];
+    let mut tokens = result.split_whitespace();
+    let errno = match tokens.next() {
+        Some(r) if r.starts_with('-') => tokens.next().map(|e| e.to_string()),
+        _ => None,
+    };
+    Some(Call {
+        pid,
+        syscall: syscall.to_string(),
+        errno,
+    })
+}
+
+/// Parse all syscall completions from strace output `log`.
+pub fn parse(log: &str) -> Vec<Call> {
+    log.lines().filter_map(parse_line).collect()
+}
+
+/// Suggest capabilities allowing the denied calls among `calls`.
+///
+/// A call yields one suggestion for each capability its syscall may need.
+/// Suggestions are sorted by capability index. Legacy 32-bit syscall names
+/// (e.g. `setuid32`) are treated as their modern counterparts.
+pub fn suggest(calls: &[Call]) -> Vec<Suggestion> {
+    let mut res: Vec<Suggestion> = vec![];
+    for call in calls.iter().filter(|c| c.denied()) {
+        let mut ops = knowledge::by_syscall(&call.syscall);
+        if ops.is_empty() && call.syscall.ends_with("32") {
+            ops = knowledge::by_syscall(&call.syscall[..call.syscall.len() - 2]);
+        }
+        let mut caps = sorted(ops.iter().map(|op| &op.capability));
+        caps.dedup();
+        for cap in caps {
+            let i = match res.iter().position(|s| s.capability == cap) {
+                Some(i) => i,
+                None => {
+                    res.push(Suggestion {
+                        capability: cap,
+                        syscalls: vec![],
+                        failures: 0,
+                    });
+                    res.len() - 1
+                }
+            };
+            res[i].failures += 1;
+            if !res[i].syscalls.contains(&call.syscall) {
+                res[i].syscalls.push(call.syscall.clone());
+            }
+        }
+    }
+    for s in &mut res {
+        s.syscalls.sort();
+    }
+    res.sort_by_key(|s| s.capability.index());
+    res
+}
+
+/// Suggest capabilities from strace output `log`.
+pub fn infer(log: &str) -> Vec<Suggestion> {
+    suggest(&parse(log))
+}
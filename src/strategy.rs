@@ -0,0 +1,95 @@
+//! Proptest strategies for capability values.
+//!
+//! These strategies allow downstream crates to property-test their policy
+//! logic against arbitrary capabilities, sets and thread states:
+//!
+//! ```rust
+//! extern crate caps;
+//! extern crate proptest;
+//! use caps::strategy;
+//! use proptest::test_runner::TestRunner;
+//!
+//! # fn main() {
+//! let set = (strategy::caps_set(), strategy::caps_set());
+//! TestRunner::default()
+//!     .run(&set, |(a, b)| {
+//!         assert!(a.is_subset(&a.union(&b).cloned().collect()));
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! Generated sets favor edge cases: empty and full sets are produced
+//! more often than a uniform distribution would.
+//!
+//! This module requires the `proptest` feature.
+
+use super::{all, all_sorted, CapSet, CapState, Capability, CapsHashSet};
+use proptest::prelude::*;
+use proptest::sample;
+
+/// Strategy generating known capabilities.
+pub fn capability() -> impl Strategy<Value = Capability> {
+    sample::select(all_sorted())
+}
+
+/// Strategy generating capability sets.
+pub fn cap_set() -> impl Strategy<Value = CapSet> {
+    sample::select(vec![
+        CapSet::Ambient,
+        CapSet::Bounding,
+        CapSet::Effective,
+        CapSet::Inheritable,
+        CapSet::Permitted,
+    ])
+}
+
+/// Strategy generating sets of capabilities, including empty and full ones.
+pub fn caps_set() -> impl Strategy<Value = CapsHashSet> {
+    let caps = all_sorted();
+    let len = caps.len();
+    prop_oneof![
+        1 => Just(CapsHashSet::new()),
+        1 => Just(all()),
+        8 => sample::subsequence(caps, 0..=len).prop_map(|v| v.into_iter().collect()),
+    ]
+}
+
+/// Strategy generating arbitrary capability states.
+///
+/// Sets are independent, so states may be unreachable by a real thread
+/// (e.g. with effective capabilities outside the permitted set). Use
+/// `consistent_cap_state` for states honoring kernel invariants.
+pub fn cap_state() -> impl Strategy<Value = CapState> {
+    (caps_set(), caps_set(), caps_set(), caps_set(), caps_set()).prop_map(
+        |(ambient, bounding, effective, inheritable, permitted)| CapState {
+            ambient,
+            bounding,
+            effective,
+            inheritable,
+            permitted,
+        },
+    )
+}
+
+/// Strategy generating capability states honoring kernel invariants.
+///
+/// The Effective set is a subset of the Permitted set, and the Ambient set
+/// a subset of both the Permitted and Inheritable sets.
+pub fn consistent_cap_state() -> impl Strategy<Value = CapState> {
+    cap_state().prop_map(|mut state| {
+        state.effective = state
+            .effective
+            .intersection(&state.permitted)
+            .cloned()
+            .collect();
+        state.ambient = state
+            .ambient
+            .iter()
+            .filter(|c| state.permitted.contains(c) && state.inheritable.contains(c))
+            .cloned()
+            .collect();
+        state
+    })
+}
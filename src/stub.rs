@@ -0,0 +1,72 @@
+//! Stub for unsupported platforms.
+//!
+//! Capabilities are Linux-specific. On other platforms, only types and
+//! parsing are available, while operations on threads always fail with
+//! `ErrorKind::Unsupported`. This lets cross-platform crates depend on
+//! this one unconditionally, and branch at runtime.
+
+use super::{CapSet, Capability, CapsHashSet, Target};
+use errors::*;
+
+/// Check if a thread contains a capability in a set.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn has_cap<T: Into<Target>>(_target: T, _cset: CapSet, _cap: Capability) -> Result<bool> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Return all capabilities in a set for a thread.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn read<T: Into<Target>>(_target: T, _cset: CapSet) -> Result<CapsHashSet> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Set a capability set for a thread to a new value.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn set<T: Into<Target>>(_target: T, _cset: CapSet, _value: CapsHashSet) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Clear all capabilities in a set for a thread.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn clear<T: Into<Target>>(_target: T, _cset: CapSet) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Raise a single capability in a set for a thread.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn raise<T: Into<Target>>(_target: T, _cset: CapSet, _cap: Capability) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Drop a single capability from a set for a thread.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn drop<T: Into<Target>>(_target: T, _cset: CapSet, _cap: Capability) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Set a capability set for a thread, then verify the result.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn set_strict<T: Into<Target>>(_target: T, _cset: CapSet, _value: CapsHashSet) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Converge a capability set for a thread to a desired value.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn ensure<T: Into<Target>>(_target: T, _cset: CapSet, _desired: &CapsHashSet) -> Result<bool> {
+    bail!(ErrorKind::Unsupported)
+}
+
+/// Drop all capabilities except the ones in `keep`, for current thread.
+///
+/// Always fails with `ErrorKind::Unsupported` on this platform.
+pub fn drop_all_except(_keep: &CapsHashSet) -> Result<()> {
+    bail!(ErrorKind::Unsupported)
+}
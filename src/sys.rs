@@ -0,0 +1,332 @@
+// Raw syscalls and prctls of the core API.
+//
+// These are issued via libc by default (using bionic's capget/capset
+// wrappers on Android) or, with the `rustix` feature, via rustix's typed
+// wrappers. Either way, they return -1 and set errno on
+// failure, so that callers handle errors uniformly. Without the `libc`
+// feature, there is no C errno: the error is then kept in a thread-local
+// variable, read back with `errno`. Other modules (such as `lowlevel`,
+// which must stay async-signal-safe) still call libc directly, and are
+// only available with the `libc` feature.
+
+#[cfg(feature = "libc")]
+use errno as errno_crate;
+use std::os::raw::{c_int, c_long, c_ulong};
+use std::os::unix::io::RawFd;
+
+use base::{CapUserData, CapUserHeader};
+use errors::Errno;
+
+// Error numbers checked by callers.
+#[cfg(feature = "libc")]
+pub(crate) use libc::{EACCES, EINVAL, ENOSYS, EPERM};
+#[cfg(not(feature = "libc"))]
+pub(crate) use self::rustix_impl::{EACCES, EINVAL, ENOSYS, EPERM};
+
+// Error of the last failed call on the calling thread.
+#[cfg(feature = "libc")]
+pub(crate) fn errno() -> Errno {
+    errno_crate::errno()
+}
+
+#[cfg(all(feature = "libc", any(feature = "rustix", feature = "tracing")))]
+pub(crate) fn set_errno(e: Errno) {
+    errno_crate::set_errno(e)
+}
+
+#[cfg(not(feature = "libc"))]
+thread_local! {
+    static ERRNO: ::std::cell::Cell<i32> = const { ::std::cell::Cell::new(0) };
+}
+
+#[cfg(not(feature = "libc"))]
+pub(crate) fn errno() -> Errno {
+    Errno(ERRNO.with(|e| e.get()))
+}
+
+#[cfg(not(feature = "libc"))]
+pub(crate) fn set_errno(e: Errno) {
+    ERRNO.with(|c| c.set(e.0))
+}
+
+// The kernel is passed an array of two data units, copied back into
+// `data` on success.
+#[cfg(all(not(feature = "rustix"), not(target_os = "android")))]
+pub(crate) fn capget(hdr: &mut CapUserHeader, data: &mut CapUserData) -> c_long {
+    let mut units = data.units();
+    let r = unsafe { ::libc::syscall(::nr::CAPGET, hdr, units.as_mut_ptr()) };
+    if r == 0 {
+        *data = CapUserData::from_units(&units);
+    }
+    r
+}
+
+#[cfg(all(not(feature = "rustix"), not(target_os = "android")))]
+pub(crate) fn capset(hdr: &mut CapUserHeader, data: &CapUserData) -> c_long {
+    let units = data.units();
+    unsafe { ::libc::syscall(::nr::CAPSET, hdr, units.as_ptr()) }
+}
+
+#[cfg(all(not(feature = "rustix"), target_os = "android"))]
+pub(crate) fn capget(hdr: &mut CapUserHeader, data: &mut CapUserData) -> c_long {
+    let mut units = data.units();
+    let r = unsafe { bionic::capget(hdr, units.as_mut_ptr()) as c_long };
+    if r == 0 {
+        *data = CapUserData::from_units(&units);
+    }
+    r
+}
+
+#[cfg(all(not(feature = "rustix"), target_os = "android"))]
+pub(crate) fn capset(hdr: &mut CapUserHeader, data: &CapUserData) -> c_long {
+    let units = data.units();
+    unsafe { bionic::capset(hdr, units.as_ptr()) as c_long }
+}
+
+// Wrappers exported by bionic, but not bound by the libc crate.
+#[cfg(all(not(feature = "rustix"), target_os = "android"))]
+mod bionic {
+    use libc::c_int;
+
+    use base::{CapUserDataUnit, CapUserHeader};
+
+    extern "C" {
+        pub(super) fn capget(hdr: *mut CapUserHeader, data: *mut CapUserDataUnit) -> c_int;
+        pub(super) fn capset(hdr: *mut CapUserHeader, data: *const CapUserDataUnit) -> c_int;
+    }
+}
+
+#[cfg(not(feature = "rustix"))]
+pub(crate) fn prctl(option: c_int, arg2: c_ulong, arg3: c_ulong) -> c_int {
+    unsafe { ::libc::prctl(option, arg2, arg3, 0, 0) }
+}
+
+// `caps_core` shim, issuing syscalls through the selected backend.
+//
+// This is async-signal-safe, as long as the backend is.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Core;
+
+impl caps_core::Syscalls for Core {
+    fn capget(&self, hdr: &mut CapUserHeader, data: &mut CapUserData) -> caps_core::Result<()> {
+        match capget(hdr, data) {
+            0 => Ok(()),
+            _ => Err(caps_core::Errno(errno().0)),
+        }
+    }
+
+    fn capset(&self, hdr: &mut CapUserHeader, data: &CapUserData) -> caps_core::Result<()> {
+        match capset(hdr, data) {
+            0 => Ok(()),
+            _ => Err(caps_core::Errno(errno().0)),
+        }
+    }
+
+    fn prctl(&self, option: i32, arg2: u64, arg3: u64) -> caps_core::Result<i32> {
+        match prctl(option, arg2 as c_ulong, arg3 as c_ulong) {
+            -1 => Err(caps_core::Errno(errno().0)),
+            r => Ok(r),
+        }
+    }
+}
+
+// Kernel identifier of the calling thread.
+#[cfg(not(feature = "rustix"))]
+pub(crate) fn gettid() -> i32 {
+    unsafe { ::libc::syscall(::libc::SYS_gettid) as i32 }
+}
+
+// Open a pidfd referring to process `pid`, returning it or -1.
+#[cfg(not(all(feature = "rustix", target_os = "linux")))]
+pub(crate) fn pidfd_open(pid: i32) -> c_long {
+    unsafe { ::libc::syscall(::libc::SYS_pidfd_open, pid, 0) }
+}
+
+// Check whether the process referred to by pidfd `fd` is alive, returning
+// 1 if so, 0 if it exited, or -1.
+//
+// The pidfd is polled, as it becomes readable once the process exited
+// (zombies included): unlike signaling it, this needs no permission over
+// the process.
+#[cfg(not(all(feature = "rustix", target_os = "linux")))]
+pub(crate) fn pidfd_alive(fd: RawFd) -> c_int {
+    let mut pfd = ::libc::pollfd {
+        fd,
+        events: ::libc::POLLIN,
+        revents: 0,
+    };
+    if unsafe { ::libc::poll(&mut pfd, 1, 0) } < 0 {
+        return -1;
+    }
+    if (pfd.revents & ::libc::POLLNVAL) != 0 {
+        ::errno::set_errno(::errno::Errno(::libc::EBADF));
+        return -1;
+    }
+    c_int::from((pfd.revents & ::libc::POLLIN) == 0)
+}
+
+// rustix always issues version 3 of the interface and ignores
+// `hdr.version`, hence no version is negotiated with this backend.
+#[cfg(feature = "rustix")]
+pub(crate) fn capget(hdr: &mut CapUserHeader, data: &mut CapUserData) -> c_long {
+    let sets = rustix_impl::capabilities(hdr.pid);
+    rustix_impl::ret(sets.map(|sets| {
+        let (eff, prm, inh) = (
+            sets.effective.bits(),
+            sets.permitted.bits(),
+            sets.inheritable.bits(),
+        );
+        *data = CapUserData {
+            effective_s0: eff as u32,
+            permitted_s0: prm as u32,
+            inheritable_s0: inh as u32,
+            effective_s1: (eff >> 32) as u32,
+            permitted_s1: (prm >> 32) as u32,
+            inheritable_s1: (inh >> 32) as u32,
+        };
+        0
+    })) as c_long
+}
+
+#[cfg(feature = "rustix")]
+pub(crate) fn capset(hdr: &mut CapUserHeader, data: &CapUserData) -> c_long {
+    let join = |s1: u32, s0: u32| (u64::from(s1) << 32) | u64::from(s0);
+    let res = rustix_impl::set_capabilities(
+        hdr.pid,
+        join(data.effective_s1, data.effective_s0),
+        join(data.permitted_s1, data.permitted_s0),
+        join(data.inheritable_s1, data.inheritable_s0),
+    );
+    rustix_impl::ret(res.map(|_| 0)) as c_long
+}
+
+#[cfg(feature = "rustix")]
+pub(crate) fn prctl(option: c_int, arg2: c_ulong, arg3: c_ulong) -> c_int {
+    rustix_impl::prctl(option, arg2, arg3)
+}
+
+#[cfg(feature = "rustix")]
+pub(crate) fn gettid() -> i32 {
+    rustix_impl::gettid()
+}
+
+#[cfg(all(feature = "rustix", target_os = "linux"))]
+pub(crate) fn pidfd_open(pid: i32) -> c_long {
+    rustix_impl::ret(rustix_impl::pidfd_open(pid)) as c_long
+}
+
+// Same as the libc backend, polling the pidfd.
+#[cfg(all(feature = "rustix", target_os = "linux"))]
+pub(crate) fn pidfd_alive(fd: RawFd) -> c_int {
+    rustix_impl::ret(rustix_impl::pidfd_alive(fd))
+}
+
+#[cfg(feature = "rustix")]
+mod rustix_impl {
+    #[cfg(target_os = "linux")]
+    use rustix::event::{self, PollFd, PollFlags, Timespec};
+    #[cfg(target_os = "linux")]
+    use rustix::fd::{BorrowedFd, IntoRawFd, RawFd};
+    use rustix::io;
+    #[cfg(target_os = "linux")]
+    use rustix::process::{self, PidfdFlags};
+    use std::os::raw::{c_int, c_ulong};
+    use rustix::thread::{self, CapabilitiesSecureBits, CapabilitySet, CapabilitySets, Pid};
+
+    use nr;
+
+    // Convert a rustix result, setting errno on failure.
+    pub(super) fn ret(res: io::Result<c_int>) -> c_int {
+        match res {
+            Ok(r) => r,
+            Err(e) => {
+                super::set_errno(super::Errno(e.raw_os_error()));
+                -1
+            }
+        }
+    }
+
+    #[cfg(not(feature = "libc"))]
+    pub(crate) const EACCES: c_int = io::Errno::ACCESS.raw_os_error();
+    #[cfg(not(feature = "libc"))]
+    pub(crate) const EINVAL: c_int = io::Errno::INVAL.raw_os_error();
+    #[cfg(not(feature = "libc"))]
+    pub(crate) const ENOSYS: c_int = io::Errno::NOSYS.raw_os_error();
+    #[cfg(not(feature = "libc"))]
+    pub(crate) const EPERM: c_int = io::Errno::PERM.raw_os_error();
+
+    pub(super) fn gettid() -> i32 {
+        thread::gettid().as_raw_nonzero().get()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn pidfd_open(pid: i32) -> io::Result<c_int> {
+        let pid = Pid::from_raw(pid).ok_or(io::Errno::INVAL)?;
+        process::pidfd_open(pid, PidfdFlags::empty()).map(|fd| fd.into_raw_fd())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn pidfd_alive(fd: RawFd) -> io::Result<c_int> {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+        let mut fds = [PollFd::new(&fd, PollFlags::IN)];
+        let now = Timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        event::poll(&mut fds, Some(&now))?;
+        let revents = fds[0].revents();
+        if revents.contains(PollFlags::NVAL) {
+            return Err(io::Errno::BADF);
+        }
+        Ok(c_int::from(!revents.contains(PollFlags::IN)))
+    }
+
+    pub(super) fn capabilities(tid: i32) -> io::Result<CapabilitySets> {
+        thread::capabilities(Pid::from_raw(tid))
+    }
+
+    pub(super) fn set_capabilities(tid: i32, eff: u64, prm: u64, inh: u64) -> io::Result<()> {
+        let sets = CapabilitySets {
+            effective: CapabilitySet::from_bits_retain(eff),
+            permitted: CapabilitySet::from_bits_retain(prm),
+            inheritable: CapabilitySet::from_bits_retain(inh),
+        };
+        thread::set_capabilities(Pid::from_raw(tid), sets)
+    }
+
+    pub(super) fn prctl(option: c_int, arg2: c_ulong, arg3: c_ulong) -> c_int {
+        let cap = |index: c_ulong| CapabilitySet::from_bits_retain(1u64 << (index & 63));
+        let res = match option {
+            nr::PR_CAPBSET_READ => {
+                thread::capability_is_in_bounding_set(cap(arg2)).map(c_int::from)
+            }
+            nr::PR_CAPBSET_DROP => {
+                thread::remove_capability_from_bounding_set(cap(arg2)).map(|_| 0)
+            }
+            nr::PR_CAP_AMBIENT => match arg2 as c_int {
+                nr::PR_CAP_AMBIENT_IS_SET => {
+                    thread::capability_is_in_ambient_set(cap(arg3)).map(c_int::from)
+                }
+                nr::PR_CAP_AMBIENT_RAISE => {
+                    thread::configure_capability_in_ambient_set(cap(arg3), true).map(|_| 0)
+                }
+                nr::PR_CAP_AMBIENT_LOWER => {
+                    thread::configure_capability_in_ambient_set(cap(arg3), false).map(|_| 0)
+                }
+                nr::PR_CAP_AMBIENT_CLEAR_ALL => thread::clear_ambient_capability_set().map(|_| 0),
+                _ => Err(io::Errno::INVAL),
+            },
+            nr::PR_GET_SECUREBITS => thread::capabilities_secure_bits().map(|b| b.bits() as c_int),
+            nr::PR_SET_SECUREBITS => thread::set_capabilities_secure_bits(
+                CapabilitiesSecureBits::from_bits_retain(arg2 as _),
+            )
+            .map(|_| 0),
+            nr::PR_GET_KEEPCAPS => thread::get_keep_capabilities().map(c_int::from),
+            nr::PR_SET_KEEPCAPS => thread::set_keep_capabilities(arg2 != 0).map(|_| 0),
+            nr::PR_GET_NO_NEW_PRIVS => thread::no_new_privs().map(c_int::from),
+            nr::PR_SET_NO_NEW_PRIVS => thread::set_no_new_privs(arg2 != 0).map(|_| 0),
+            _ => Err(io::Errno::INVAL),
+        };
+        ret(res)
+    }
+}
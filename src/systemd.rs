@@ -0,0 +1,138 @@
+//! Generation of systemd unit hardening settings.
+//!
+//! A privilege posture determined programmatically (from the current
+//! thread, a `CapState` or a `Policy`) can be moved into a unit file with
+//! the settings generated here:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::systemd::Hardening;
+//! use caps::CapState;
+//!
+//! # fn main() {
+//! let state = CapState {
+//!     bounding: caps![CAP_NET_BIND_SERVICE],
+//!     ambient: caps![CAP_NET_BIND_SERVICE],
+//!     ..Default::default()
+//! };
+//! let mut hardening = Hardening::from_state(&state);
+//! hardening.no_new_privs = true;
+//! assert_eq!(
+//!     hardening.to_unit(),
+//!     "CapabilityBoundingSet=CAP_NET_BIND_SERVICE\n\
+//!      AmbientCapabilities=CAP_NET_BIND_SERVICE\n\
+//!      NoNewPrivileges=yes\n"
+//! );
+//! # }
+//! ```
+
+use super::{all, sorted, CapSet, CapState, CapsHashSet};
+use errors::*;
+use policy::Policy;
+use securebits::{self, SecureBits};
+
+// Names of securebits flags, as expected by `SecureBits=`.
+const SECUREBITS: &[(SecureBits, &str)] = &[
+    (SecureBits::KEEP_CAPS, "keep-caps"),
+    (SecureBits::KEEP_CAPS_LOCKED, "keep-caps-locked"),
+    (SecureBits::NO_SETUID_FIXUP, "no-setuid-fixup"),
+    (SecureBits::NO_SETUID_FIXUP_LOCKED, "no-setuid-fixup-locked"),
+    (SecureBits::NOROOT, "noroot"),
+    (SecureBits::NOROOT_LOCKED, "noroot-locked"),
+];
+
+/// Capability-related settings of a systemd service.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Hardening {
+    /// Capabilities of `CapabilityBoundingSet=`.
+    pub bounding: CapsHashSet,
+    /// Capabilities of `AmbientCapabilities=`.
+    pub ambient: CapsHashSet,
+    /// Value of `NoNewPrivileges=`.
+    pub no_new_privs: bool,
+    /// Flags of `SecureBits=`.
+    ///
+    /// `SecureBits::NO_CAP_AMBIENT_RAISE` has no systemd equivalent, and
+    /// is not emitted.
+    pub securebits: SecureBits,
+}
+
+impl Hardening {
+    /// Take Bounding and Ambient sets from `state`.
+    pub fn from_state(state: &CapState) -> Hardening {
+        Hardening {
+            bounding: state.bounding.clone(),
+            ambient: state.ambient.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Take settings from the current thread.
+    pub fn current() -> Result<Hardening> {
+        Ok(Hardening {
+            no_new_privs: securebits::has_no_new_privs()?,
+            securebits: securebits::get_securebits()?,
+            ..Hardening::from_state(&CapState::current()?)
+        })
+    }
+
+    /// Take settings from `policy`.
+    ///
+    /// The bounding set holds all capabilities allowed by `policy` in it,
+    /// while ambient capabilities are only granted if `policy` constrains
+    /// the Ambient set explicitly.
+    pub fn from_policy(policy: &Policy) -> Hardening {
+        let allowed = |cset: CapSet, caps: &CapsHashSet| -> CapsHashSet {
+            caps.iter()
+                .filter(|c| policy.allows(cset, **c))
+                .cloned()
+                .collect()
+        };
+        let bounding = allowed(CapSet::Bounding, &all());
+        let ambient = match policy.max.get(&CapSet::Ambient) {
+            Some(max) => allowed(CapSet::Ambient, max)
+                .intersection(&bounding)
+                .cloned()
+                .collect(),
+            None => CapsHashSet::new(),
+        };
+        Hardening {
+            bounding,
+            ambient,
+            no_new_privs: policy.no_new_privs,
+            securebits: policy.securebits,
+        }
+    }
+
+    /// Format as lines of the `[Service]` section of a unit file.
+    ///
+    /// An empty set is emitted as an empty assignment, which systemd
+    /// treats as resetting the set to empty. `SecureBits=` is omitted when
+    /// no flag is set.
+    pub fn to_unit(&self) -> String {
+        let mut unit = String::new();
+        unit.push_str(&format!(
+            "CapabilityBoundingSet={}\n",
+            names(&self.bounding)
+        ));
+        unit.push_str(&format!("AmbientCapabilities={}\n", names(&self.ambient)));
+        let nnp = if self.no_new_privs { "yes" } else { "no" };
+        unit.push_str(&format!("NoNewPrivileges={}\n", nnp));
+        let flags: Vec<_> = SECUREBITS
+            .iter()
+            .filter(|&&(bit, _)| self.securebits.contains(bit))
+            .map(|&(_, name)| name)
+            .collect();
+        if !flags.is_empty() {
+            unit.push_str(&format!("SecureBits={}\n", flags.join(" ")));
+        }
+        unit
+    }
+}
+
+// Space-separated capability names, sorted by index.
+fn names(caps: &CapsHashSet) -> String {
+    let names: Vec<_> = sorted(caps).iter().map(|c| c.to_string()).collect();
+    names.join(" ")
+}
@@ -0,0 +1,132 @@
+//! Helpers for tests changing capabilities.
+//!
+//! Tests exercising privileged code paths need specific capabilities,
+//! which are usually only available when running as root. These helpers
+//! apply a capability state around a test body, and skip it (with a
+//! message on stderr) when the test process lacks the needed
+//! capabilities, instead of failing:
+//!
+//! ```rust,no_run
+//! #[macro_use]
+//! extern crate caps;
+//! use caps::test_util;
+//! use caps::CapState;
+//!
+//! #[test]
+//! fn binds_privileged_port() {
+//!     let state = CapState {
+//!         effective: caps![CAP_NET_BIND_SERVICE],
+//!         permitted: caps![CAP_NET_BIND_SERVICE],
+//!         ..CapState::current().unwrap()
+//!     };
+//!     test_util::with_caps(&state, || {
+//!         // bind to port 80...
+//!     });
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! This module requires the `test-util` feature.
+
+use super::{sorted, CapSet, CapState, Capability, CapsHashSet};
+use errors::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+/// Run `f` with capability state `state`, then restore the previous one.
+///
+/// The current state is snapshotted, `state` is applied, `f` runs, and
+/// the snapshot is restored (even if `f` panics). If `state` drops
+/// capabilities from the Permitted or Bounding sets, which can never be
+/// regained, `f` runs on a helper thread instead, leaving the calling
+/// thread untouched.
+///
+/// If `state` cannot be applied for lack of privileges, `f` is skipped
+/// with a message on stderr and `None` is returned. Other errors panic.
+pub fn with_caps<F, T>(state: &CapState, f: F) -> Option<T>
+where
+    F: FnOnce() -> T + Send,
+    T: Send,
+{
+    let snapshot = CapState::current().expect("failed to read capabilities");
+    let missing = missing(state, &snapshot);
+    if !missing.is_empty() {
+        skip(&format!("missing capabilities {:?}", missing));
+        return None;
+    }
+    let reversible = snapshot.permitted.is_subset(&state.permitted)
+        && snapshot.bounding.is_subset(&state.bounding);
+    if reversible {
+        return run(state, Some(&snapshot), f);
+    }
+    thread::scope(|s| match s.spawn(|| run(state, None, f)).join() {
+        Ok(res) => res,
+        Err(e) => panic::resume_unwind(e),
+    })
+}
+
+/// Check whether the current thread holds all of `caps` in set `cset`.
+///
+/// Otherwise, a message is printed on stderr, so that tests can skip
+/// themselves with `if !require(..) { return; }`.
+pub fn require(cset: CapSet, caps: &CapsHashSet) -> bool {
+    let held = ::read(None, cset).expect("failed to read capabilities");
+    let missing = sorted(caps.difference(&held));
+    if missing.is_empty() {
+        return true;
+    }
+    skip(&format!("missing {:?} capabilities {:?}", cset, missing));
+    false
+}
+
+// Apply `state`, run `f`, then restore `snapshot` if any.
+fn run<F, T>(state: &CapState, snapshot: Option<&CapState>, f: F) -> Option<T>
+where
+    F: FnOnce() -> T,
+{
+    if let Err(e) = state.apply() {
+        if let Some(snapshot) = snapshot {
+            let _ = snapshot.apply();
+        }
+        if is_permission(&e) {
+            skip(&format!("cannot apply capabilities ({})", e));
+            return None;
+        }
+        panic!("failed to apply capabilities: {}", e);
+    }
+    let res = panic::catch_unwind(AssertUnwindSafe(f));
+    if let Some(snapshot) = snapshot {
+        snapshot.apply().expect("failed to restore capabilities");
+    }
+    match res {
+        Ok(res) => Some(res),
+        Err(e) => panic::resume_unwind(e),
+    }
+}
+
+// Capabilities of `state` which cannot be gained from `snapshot`, as
+// the Permitted and Bounding sets can only be lowered.
+fn missing(state: &CapState, snapshot: &CapState) -> Vec<Capability> {
+    let mut missing = sorted(
+        state
+            .permitted
+            .difference(&snapshot.permitted)
+            .chain(state.bounding.difference(&snapshot.bounding)),
+    );
+    missing.dedup();
+    missing
+}
+
+fn is_permission(err: &Error) -> bool {
+    err.errno().map(|e| e.0) == Some(::libc::EPERM)
+}
+
+fn skip(reason: &str) {
+    let name = thread::current()
+        .name()
+        .map_or_else(|| "test".to_string(), |n| n.to_string());
+    eprintln!(
+        "skipping {}: {}; run with more privileges to enable it",
+        name, reason
+    );
+}
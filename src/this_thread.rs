@@ -0,0 +1,51 @@
+//! Operate on capabilities of the current thread.
+//!
+//! Capabilities are a per-thread attribute: changes performed through
+//! this module only affect the calling thread, while other threads of
+//! the same process keep their own capabilities.
+//! These functions are equivalent to the top-level ones with a
+//! `Target::CurrentThread` target.
+
+use super::{CapSet, CapState, Capability, CapsHashSet, Target};
+use errors::*;
+use sys;
+
+/// Return the kernel thread ID (TID) of the current thread.
+pub fn gettid() -> i32 {
+    sys::gettid()
+}
+
+/// Check if set `cset` of current thread contains capability `cap`.
+pub fn has_cap(cset: CapSet, cap: Capability) -> Result<bool> {
+    super::has_cap(Target::CurrentThread, cset, cap)
+}
+
+/// Return all capabilities in set `cset` of current thread.
+pub fn read(cset: CapSet) -> Result<CapsHashSet> {
+    super::read(Target::CurrentThread, cset)
+}
+
+/// Return the capability state of current thread, across all sets.
+pub fn state() -> Result<CapState> {
+    CapState::current()
+}
+
+/// Set all and only capabilities in `value` in set `cset` of current thread.
+pub fn set(cset: CapSet, value: CapsHashSet) -> Result<()> {
+    super::set(Target::CurrentThread, cset, value)
+}
+
+/// Clear all capabilities in set `cset` of current thread.
+pub fn clear(cset: CapSet) -> Result<()> {
+    super::clear(Target::CurrentThread, cset)
+}
+
+/// Raise capability `cap` in set `cset` of current thread.
+pub fn raise(cset: CapSet, cap: Capability) -> Result<()> {
+    super::raise(Target::CurrentThread, cset, cap)
+}
+
+/// Drop capability `cap` from set `cset` of current thread.
+pub fn drop(cset: CapSet, cap: Capability) -> Result<()> {
+    super::drop(Target::CurrentThread, cset, cap)
+}
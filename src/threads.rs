@@ -0,0 +1,176 @@
+//! Inspect and manage capabilities across threads.
+//!
+//! Capabilities are a per-thread attribute, and threads of the same
+//! process can diverge (e.g. when capabilities are dropped after other
+//! threads have been spawned). This module helps detecting and avoiding
+//! such situations.
+
+use libc;
+
+use super::CapState;
+use errors::*;
+use lowlevel::{AtomicMasks, Masks};
+use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Once;
+use std::thread::{Builder, JoinHandle};
+
+// Capability policy re-applied in children after fork.
+static FORK_POLICY: AtomicMasks = AtomicMasks::new();
+static FORK_POLICY_SET: AtomicBool = AtomicBool::new(false);
+static ATFORK: Once = Once::new();
+static ATFORK_ERR: AtomicI32 = AtomicI32::new(0);
+
+/// Report on the consistency of capabilities across threads of a process.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ConsistencyReport {
+    /// Inspected process.
+    pub pid: i32,
+    /// Capability state of each thread, the reference one first.
+    pub threads: Vec<(i32, CapState)>,
+    /// Threads whose capability state differs from the reference one.
+    pub divergent: Vec<i32>,
+}
+
+impl ConsistencyReport {
+    /// Check whether all threads share the same capability state.
+    pub fn is_consistent(&self) -> bool {
+        self.divergent.is_empty()
+    }
+}
+
+/// Check whether all threads of process `pid` share the same capabilities.
+///
+/// The main thread is used as the reference (or the first listed thread,
+/// if the main one has already exited). Threads exiting while being
+/// inspected are skipped.
+pub fn threads_consistent(pid: i32) -> Result<ConsistencyReport> {
+    let dir = format!("/proc/{}/task", pid);
+    let mut tids: Vec<i32> = vec![];
+    for entry in fs::read_dir(&dir).chain_err(|| format!("failed to list {}", dir))? {
+        let entry = entry.chain_err(|| format!("failed to list {}", dir))?;
+        if let Some(tid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            tids.push(tid);
+        }
+    }
+    tids.sort_by_key(|t| (*t != pid, *t));
+
+    let mut threads = vec![];
+    for tid in tids {
+        match CapState::from_proc(pid, tid) {
+            Ok(s) => threads.push((tid, s)),
+            Err(_) if !task_exists(pid, tid) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    let divergent = match threads.first() {
+        Some((_, reference)) => threads
+            .iter()
+            .filter(|&(_, s)| s != reference)
+            .map(|&(tid, _)| tid)
+            .collect(),
+        None => bail!("no threads found for process {}", pid),
+    };
+    Ok(ConsistencyReport {
+        pid,
+        threads,
+        divergent,
+    })
+}
+
+fn task_exists(pid: i32, tid: i32) -> bool {
+    fs::metadata(format!("/proc/{}/task/{}", pid, tid)).is_ok()
+}
+
+/// Re-apply capability state `state` in every child forked from now on.
+///
+/// A `pthread_atfork` child handler is registered (once), which applies
+/// `state` to the forked child before `fork` returns, so that forked
+/// workers can not inherit more privilege than this policy allows. If the
+/// state can not be applied, the child is aborted.
+///
+/// Only `fork(2)` runs atfork handlers: children created via `vfork`,
+/// `posix_spawn` or a raw `clone` (as `std::process::Command` may do) are
+/// not covered.
+pub fn set_fork_policy(state: &CapState) -> Result<()> {
+    ATFORK.call_once(|| {
+        let err = unsafe { libc::pthread_atfork(None, None, Some(atfork_child)) };
+        ATFORK_ERR.store(err, Ordering::SeqCst);
+    });
+    let err = ATFORK_ERR.load(Ordering::SeqCst);
+    if err != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(::errno::Errno(err)))
+            .chain_err(|| "pthread_atfork error"));
+    }
+    FORK_POLICY.store(Masks::from(state));
+    FORK_POLICY_SET.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Stop re-applying a capability policy in forked children.
+pub fn clear_fork_policy() {
+    FORK_POLICY_SET.store(false, Ordering::SeqCst);
+}
+
+extern "C" fn atfork_child() {
+    if FORK_POLICY_SET.load(Ordering::SeqCst) && FORK_POLICY.load().apply().is_err() {
+        unsafe { libc::abort() };
+    }
+}
+
+/// Spawn a thread which runs `f` with capability state `state`.
+///
+/// New threads inherit the capabilities of their creator, while worker
+/// threads rarely need them. The thread is spawned from `builder`, and
+/// applies `state` before running `f`; if that fails, `f` is not run and
+/// the error is returned when joining the thread.
+pub fn spawn_with_caps<F, T>(
+    builder: Builder,
+    state: CapState,
+    f: F,
+) -> io::Result<JoinHandle<Result<T>>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    builder.spawn(move || {
+        state.apply()?;
+        Ok(f())
+    })
+}
+
+/// Build a thread-pool start hook which applies capability state `state`.
+///
+/// The returned closure is suitable for hooks run in each worker thread
+/// at pool construction time (e.g. rayon's `start_handler`). As workers
+/// can not report errors from there, the process is aborted if `state`
+/// can not be applied.
+pub fn start_handler(state: CapState) -> impl Fn(usize) + Send + Sync + 'static {
+    move |_| {
+        if state.apply().is_err() {
+            ::std::process::abort();
+        }
+    }
+}
+
+/// Build a rayon thread-pool whose workers run with capability state `state`.
+#[cfg(feature = "rayon")]
+pub fn rayon_pool_builder(state: CapState) -> ::rayon::ThreadPoolBuilder {
+    ::rayon::ThreadPoolBuilder::new().start_handler(start_handler(state))
+}
+
+/// Build a multi-threaded tokio runtime whose threads run with capability
+/// state `state`.
+///
+/// The state is applied via `on_thread_start` to every worker and blocking
+/// thread. The thread driving the runtime (e.g. calling `block_on`) is not
+/// affected. As for `start_handler`, the process is aborted if `state` can
+/// not be applied.
+#[cfg(feature = "tokio")]
+pub fn tokio_runtime_builder(state: CapState) -> ::tokio::runtime::Builder {
+    let handler = start_handler(state);
+    let mut builder = ::tokio::runtime::Builder::new_multi_thread();
+    builder.on_thread_start(move || handler(0));
+    builder
+}
@@ -0,0 +1,239 @@
+//! Reusable logic of the `caps` command-line tool.
+//!
+//! Each subcommand of the `caps` binary is backed by a function of this
+//! module returning a structured result (serializable with the `serde`
+//! feature), so that other tools can embed the same inspect, diff, watch
+//! and predict logic without parsing text output.
+
+use libc;
+
+use super::{sorted, CapSet, CapState, Capability};
+use compare::Report;
+use errors::*;
+use exec;
+use observe::CapsDiff;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::env;
+use std::ffi::CString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Content of all capability sets, each sorted by index.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Sets {
+    /// Ambient capabilities.
+    pub ambient: Vec<Capability>,
+    /// Bounding capabilities.
+    pub bounding: Vec<Capability>,
+    /// Effective capabilities.
+    pub effective: Vec<Capability>,
+    /// Inheritable capabilities.
+    pub inheritable: Vec<Capability>,
+    /// Permitted capabilities.
+    pub permitted: Vec<Capability>,
+}
+
+impl Sets {
+    /// Sort the sets of `state`.
+    pub fn from_state(state: &CapState) -> Sets {
+        Sets {
+            ambient: sorted(&state.ambient),
+            bounding: sorted(&state.bounding),
+            effective: sorted(&state.effective),
+            inheritable: sorted(&state.inheritable),
+            permitted: sorted(&state.permitted),
+        }
+    }
+
+    /// Return the content of set `cset`.
+    pub fn get(&self, cset: CapSet) -> &[Capability] {
+        match cset {
+            CapSet::Ambient => &self.ambient,
+            CapSet::Bounding => &self.bounding,
+            CapSet::Effective => &self.effective,
+            CapSet::Inheritable => &self.inheritable,
+            CapSet::Permitted => &self.permitted,
+        }
+    }
+}
+
+/// Full capability state of a process (`caps inspect`).
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Inspection {
+    /// Process ID.
+    pub pid: libc::pid_t,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    /// Capability sets of the main thread.
+    pub sets: Sets,
+    /// Securebits flags, only readable for the current process.
+    pub securebits: Option<u32>,
+    /// "No new privileges" flag, only reported since Linux 4.10.
+    pub no_new_privs: Option<bool>,
+}
+
+/// Inspect the capability state of process `pid`.
+pub fn inspect(pid: libc::pid_t) -> Result<Inspection> {
+    let state = CapState::from_proc(pid, pid)?;
+    let securebits = if pid == unsafe { libc::getpid() } {
+        Some(::securebits::get_securebits()?.bits())
+    } else {
+        None
+    };
+    let path = format!("/proc/{}/status", pid);
+    let status = fs::read_to_string(&path).chain_err(|| format!("failed to read {}", path))?;
+    let no_new_privs = status
+        .lines()
+        .find(|l| l.starts_with("NoNewPrivs:"))
+        .map(|l| l.trim_end().ends_with('1'));
+    Ok(Inspection {
+        pid,
+        sets: Sets::from_state(&state),
+        securebits,
+        no_new_privs,
+    })
+}
+
+/// Differences in a single set between two states (`caps diff`).
+pub use compare::SetChange as SetDiff;
+
+/// Compare states `a` and `b`, returning differing sets.
+pub fn diff(a: &CapState, b: &CapState) -> Vec<SetDiff> {
+    Report::between(a, b).sets
+}
+
+/// Timestamped capability change of a process (`caps watch`).
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Change {
+    /// Seconds since the Unix epoch.
+    pub timestamp: f64,
+    /// Process ID.
+    pub pid: libc::pid_t,
+    /// Changed set.
+    pub set: CapSet,
+    /// Capabilities added to the set, sorted.
+    pub added: Vec<Capability>,
+    /// Capabilities removed from the set, sorted.
+    pub removed: Vec<Capability>,
+}
+
+impl Change {
+    /// Timestamp `diff`, a change of process `pid`, with the current time.
+    pub fn now(pid: libc::pid_t, diff: &CapsDiff) -> Change {
+        Change {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0),
+            pid,
+            set: diff.set,
+            added: sorted(&diff.added),
+            removed: sorted(&diff.removed),
+        }
+    }
+}
+
+/// Predicted outcome of executing a program (`caps predict`).
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Outcome {
+    /// Resolved path of the program.
+    pub program: PathBuf,
+    /// File capabilities effective bit, if the file has capabilities.
+    pub file_effective: Option<bool>,
+    /// File-permitted capabilities, if the file has capabilities.
+    pub file_permitted: Option<Vec<Capability>>,
+    /// File-inheritable capabilities, if the file has capabilities.
+    pub file_inheritable: Option<Vec<Capability>>,
+    /// Namespace root owning the file capabilities, if any.
+    pub file_rootid: Option<u32>,
+    /// Real UID of the calling thread.
+    pub uid: u32,
+    /// Effective UID of the calling thread.
+    pub euid: u32,
+    /// Owner of the file, if it is setuid.
+    pub setuid_owner: Option<u32>,
+    /// Securebits flags of the calling thread.
+    pub securebits: u32,
+    /// "No new privileges" flag of the calling thread.
+    pub no_new_privs: bool,
+    /// Resulting state, or `None` if exec would fail.
+    pub state: Option<Sets>,
+    /// File-permitted capabilities which can not be obtained.
+    pub missing: Vec<Capability>,
+}
+
+/// Predict the outcome of executing `program`, resolved via `find_program`.
+pub fn predict(program: &str) -> Result<Outcome> {
+    let program = find_program(program)?;
+    let p = exec::predict_exec(&program)?;
+    let fcaps = p.file_caps.as_ref();
+    Ok(Outcome {
+        file_effective: fcaps.map(|f| f.effective),
+        file_permitted: fcaps.map(|f| sorted(&f.permitted)),
+        file_inheritable: fcaps.map(|f| sorted(&f.inheritable)),
+        file_rootid: fcaps.and_then(|f| f.rootid),
+        program,
+        uid: p.params.uid,
+        euid: p.params.euid,
+        setuid_owner: p.params.setuid_owner,
+        securebits: p.params.securebits.bits(),
+        no_new_privs: p.params.no_new_privs,
+        state: p.state.as_ref().map(Sets::from_state),
+        missing: p.missing,
+    })
+}
+
+/// Resolve `program` through `PATH`, unless it contains a slash.
+pub fn find_program(program: &str) -> Result<PathBuf> {
+    if program.contains('/') {
+        return Ok(PathBuf::from(program));
+    }
+    let path = env::var_os("PATH").unwrap_or_default();
+    match env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|p| Path::is_file(p))
+    {
+        Some(p) => Ok(p),
+        None => bail!("{}: command not found", program),
+    }
+}
+
+/// Resolve a user name or numeric id into its uid and primary gid.
+pub fn lookup_user(user: &str) -> Result<(u32, u32)> {
+    let name = CString::new(user).chain_err(|| format!("invalid user {:?}", user))?;
+    let pw = unsafe { libc::getpwnam(name.as_ptr()) };
+    if !pw.is_null() {
+        return Ok(unsafe { ((*pw).pw_uid, (*pw).pw_gid) });
+    }
+    match user.parse() {
+        Ok(uid) => {
+            let pw = unsafe { libc::getpwuid(uid) };
+            let gid = if pw.is_null() {
+                uid
+            } else {
+                unsafe { (*pw).pw_gid }
+            };
+            Ok((uid, gid))
+        }
+        Err(_) => bail!("unknown user {}", user),
+    }
+}
+
+/// Resolve a group name or numeric id into its gid.
+pub fn lookup_group(group: &str) -> Result<u32> {
+    let name = CString::new(group).chain_err(|| format!("invalid group {:?}", group))?;
+    let gr = unsafe { libc::getgrnam(name.as_ptr()) };
+    if !gr.is_null() {
+        return Ok(unsafe { (*gr).gr_gid });
+    }
+    match group.parse() {
+        Ok(gid) => Ok(gid),
+        Err(_) => bail!("unknown group {}", group),
+    }
+}
+
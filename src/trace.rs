@@ -0,0 +1,136 @@
+// Instrumentation through the `tracing` facade.
+//
+// With the `tracing` feature, every capability-related syscall emits a
+// TRACE event and every set change runs in a DEBUG span, emitting an
+// event with the changed set, added and removed capabilities and the
+// result. All events use the "caps" target. With the `log` feature,
+// events are also forwarded to the `log` facade.
+//
+// Without those features, everything here compiles down to nothing.
+
+use super::CapSet;
+#[cfg(feature = "tracing")]
+use super::CapsHashSet;
+use errors::*;
+use observe::CapsDiff;
+#[cfg(all(feature = "libc", feature = "tracing"))]
+use watchdog::Event;
+use Target;
+
+#[cfg(feature = "tracing")]
+use sys;
+#[cfg(feature = "tracing")]
+use tracing;
+
+// Whether set changes are being traced.
+#[cfg(feature = "tracing")]
+pub(crate) fn enabled() -> bool {
+    tracing::enabled!(target: "caps", tracing::Level::DEBUG)
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn enabled() -> bool {
+    false
+}
+
+// Record `ret`, the return value of syscall `name`.
+//
+// This must be called right after the syscall, before `errno` can be
+// clobbered. `errno` is preserved for the caller.
+#[cfg(feature = "tracing")]
+pub(crate) fn syscall(name: &'static str, ret: i64) {
+    let saved = sys::errno();
+    if ret < 0 {
+        tracing::trace!(target: "caps", syscall = name, ret, errno = saved.0);
+    } else {
+        tracing::trace!(target: "caps", syscall = name, ret);
+    }
+    sys::set_errno(saved);
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn syscall(_name: &'static str, _ret: i64) {}
+
+// Run change `f`, named `op`, of set `cset` of `target` in a span.
+#[cfg(feature = "tracing")]
+pub(crate) fn change<R, F>(
+    op: &'static str,
+    target: Target,
+    cset: Option<CapSet>,
+    f: F,
+) -> Result<R>
+where
+    F: FnOnce() -> Result<R>,
+{
+    let span = tracing::debug_span!(target: "caps", "change", op, thread = ?target, set = ?cset);
+    let _enter = span.enter();
+    let res = f();
+    if let Err(ref e) = res {
+        tracing::debug!(target: "caps", result = "error", error = %e);
+    }
+    res
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn change<R, F>(
+    _op: &'static str,
+    _target: Target,
+    _cset: Option<CapSet>,
+    f: F,
+) -> Result<R>
+where
+    F: FnOnce() -> Result<R>,
+{
+    f()
+}
+
+// Record a successful, non-empty change.
+#[cfg(feature = "tracing")]
+pub(crate) fn changed(diff: &CapsDiff) {
+    tracing::debug!(
+        target: "caps",
+        result = "ok",
+        thread = ?diff.target,
+        set = ?diff.set,
+        added = %names(&diff.added),
+        removed = %names(&diff.removed),
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn changed(_diff: &CapsDiff) {}
+
+// Record an event reported by the watchdog.
+#[cfg(all(feature = "libc", feature = "tracing"))]
+pub(crate) fn watchdog(event: &Event) {
+    match *event {
+        Event::Corrected(ref drifts) => {
+            for drift in drifts {
+                for v in &drift.violations {
+                    tracing::warn!(
+                        target: "caps",
+                        thread = drift.tid,
+                        violation = %v,
+                        "drift corrected"
+                    );
+                }
+            }
+        }
+        Event::Failed(ref e) => tracing::error!(target: "caps", error = %e, "watchdog failed"),
+    }
+}
+
+#[cfg(all(feature = "libc", not(feature = "tracing")))]
+#[inline(always)]
+pub(crate) fn watchdog<E>(_event: &E) {}
+
+// Sorted, comma-separated names of `caps`.
+#[cfg(feature = "tracing")]
+fn names(caps: &CapsHashSet) -> String {
+    let mut names: Vec<String> = caps.iter().map(|c| c.to_string()).collect();
+    names.sort();
+    names.join(",")
+}
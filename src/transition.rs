@@ -0,0 +1,350 @@
+//! Combined privilege transitions.
+//!
+//! A `Transition` performs in one go what util-linux's `setpriv` does:
+//! set securebits, restrict the bounding set, set inheritable and ambient
+//! capabilities, switch groups and uid, and set "no new privileges". The
+//! whole transition is validated against the current state before any
+//! change is made.
+//!
+//! Capabilities and securebits only change for the calling thread, while
+//! the C library usually switches ids for all threads of the process.
+
+use libc;
+
+use super::{CapSet, CapState, Capability, CapsHashSet};
+use errno;
+use errors::*;
+use securebits::{self, SecureBits};
+
+/// A single action performed during a transition.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Action {
+    /// Securebits were set.
+    SecureBits(SecureBits),
+    /// Capabilities were dropped from the bounding set.
+    Bounding(CapsHashSet),
+    /// Supplementary groups were set.
+    Groups(Vec<u32>),
+    /// Real, effective and saved gids were switched.
+    Gid(u32),
+    /// Real, effective and saved uids were switched.
+    Uid(u32),
+    /// The inheritable set was set.
+    Inheritable(CapsHashSet),
+    /// The ambient set was set.
+    Ambient(CapsHashSet),
+    /// The "no new privileges" flag was set.
+    NoNewPrivs,
+}
+
+/// Report of a successful transition, listing actions in order.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Report {
+    pub actions: Vec<Action>,
+}
+
+/// Privilege transition of the calling thread.
+///
+/// Steps are performed in the order of `Action` variants. Permitted
+/// capabilities are retained across the uid switch, and the effective
+/// set is restored afterwards.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Transition {
+    securebits: Option<SecureBits>,
+    bounding: Option<CapsHashSet>,
+    groups: Option<Vec<u32>>,
+    keep_groups: bool,
+    gid: Option<u32>,
+    uid: Option<u32>,
+    inheritable: Option<CapsHashSet>,
+    ambient: Option<CapsHashSet>,
+    no_new_privs: bool,
+}
+
+impl Transition {
+    /// Start an empty transition.
+    pub fn new() -> Transition {
+        Transition::default()
+    }
+
+    /// Set securebits to `bits`.
+    pub fn securebits(mut self, bits: SecureBits) -> Transition {
+        self.securebits = Some(bits);
+        self
+    }
+
+    /// Restrict the bounding set to `caps`.
+    pub fn bounding(mut self, caps: &CapsHashSet) -> Transition {
+        self.bounding = Some(caps.clone());
+        self
+    }
+
+    /// Set supplementary groups to `groups`.
+    pub fn groups(mut self, groups: &[u32]) -> Transition {
+        self.groups = Some(groups.to_vec());
+        self
+    }
+
+    /// Clear supplementary groups.
+    pub fn clear_groups(self) -> Transition {
+        self.groups(&[])
+    }
+
+    /// Explicitly keep supplementary groups when switching ids.
+    ///
+    /// Switching ids otherwise requires setting supplementary groups, as
+    /// inheriting those of a privileged user is a classic vulnerability.
+    pub fn keep_groups(mut self) -> Transition {
+        self.keep_groups = true;
+        self
+    }
+
+    /// Switch real, effective and saved gids to `gid`.
+    pub fn gid(mut self, gid: u32) -> Transition {
+        self.gid = Some(gid);
+        self
+    }
+
+    /// Switch real, effective and saved uids to `uid`.
+    pub fn uid(mut self, uid: u32) -> Transition {
+        self.uid = Some(uid);
+        self
+    }
+
+    /// Set the inheritable set to `caps`.
+    pub fn inheritable(mut self, caps: &CapsHashSet) -> Transition {
+        self.inheritable = Some(caps.clone());
+        self
+    }
+
+    /// Set the ambient set to `caps`.
+    pub fn ambient(mut self, caps: &CapsHashSet) -> Transition {
+        self.ambient = Some(caps.clone());
+        self
+    }
+
+    /// Set the "no new privileges" flag.
+    pub fn no_new_privs(mut self) -> Transition {
+        self.no_new_privs = true;
+        self
+    }
+
+    /// Check that this transition can be performed from the current state.
+    pub fn validate(&self) -> Result<()> {
+        let state = CapState::current()?;
+        let current_bits = securebits::get_securebits()?;
+        self.validate_from(&state, current_bits)
+    }
+
+    fn validate_from(&self, state: &CapState, current_bits: SecureBits) -> Result<()> {
+        let has = |cap: Capability| state.effective.contains(&cap);
+        let invalid = |reason: String| Err(Error::from_kind(ErrorKind::InvalidTransition(reason)));
+
+        if (self.securebits.is_some() || self.bounding.is_some()) && !has(Capability::CAP_SETPCAP) {
+            return invalid("CAP_SETPCAP is required for securebits and bounding set".into());
+        }
+        if (self.groups.is_some() || self.gid.is_some()) && !has(Capability::CAP_SETGID) {
+            return invalid("CAP_SETGID is required to switch groups".into());
+        }
+        let switch_ids = self.uid.is_some() || self.gid.is_some();
+        if switch_ids && self.groups.is_none() && !self.keep_groups {
+            return invalid(
+                "switching ids requires setting or keeping supplementary groups".into(),
+            );
+        }
+        if self.uid.is_some() && !has(Capability::CAP_SETUID) {
+            return invalid("CAP_SETUID is required to switch uid".into());
+        }
+        if self.uid.is_some() {
+            check_keepcaps(self.securebits.unwrap_or(current_bits))?;
+        }
+        let bounding = self.bounding.as_ref().unwrap_or(&state.bounding);
+        if let Some(ref caps) = self.bounding {
+            if !caps.is_subset(&state.bounding) {
+                return invalid("bounding set can not be raised".into());
+            }
+        }
+        let inheritable = self.inheritable.as_ref().unwrap_or(&state.inheritable);
+        for cap in inheritable.difference(&state.inheritable) {
+            if !bounding.contains(cap) {
+                return invalid(format!("{} is not in the bounding set", cap));
+            }
+            if !state.permitted.contains(cap) && !has(Capability::CAP_SETPCAP) {
+                return invalid(format!("{} is not in the permitted set", cap));
+            }
+        }
+        if let Some(ref caps) = self.ambient {
+            let bits = self.securebits.unwrap_or(current_bits);
+            if !caps.is_empty() && bits.contains(SecureBits::NO_CAP_AMBIENT_RAISE) {
+                return invalid("securebits forbid raising ambient capabilities".into());
+            }
+            for cap in caps {
+                if !state.permitted.contains(cap) || !inheritable.contains(cap) {
+                    return invalid(format!(
+                        "{} is not in the permitted and inheritable sets",
+                        cap
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate then perform this transition.
+    pub fn apply(&self) -> Result<Report> {
+        let state = CapState::current()?;
+        let current_bits = securebits::get_securebits()?;
+        self.validate_from(&state, current_bits)?;
+        let mut report = Report::default();
+
+        if let Some(bits) = self.securebits {
+            securebits::set_securebits(bits)?;
+            report.actions.push(Action::SecureBits(bits));
+        }
+        if let Some(ref caps) = self.bounding {
+            let dropped: CapsHashSet = state.bounding.difference(caps).cloned().collect();
+            for cap in &dropped {
+                super::drop(None, CapSet::Bounding, *cap)?;
+            }
+            report.actions.push(Action::Bounding(dropped));
+        }
+        if let Some(ref groups) = self.groups {
+            set_groups(groups)?;
+            report.actions.push(Action::Groups(groups.clone()));
+        }
+        if let Some(gid) = self.gid {
+            setresgid(gid)?;
+            report.actions.push(Action::Gid(gid));
+        }
+        if let Some(uid) = self.uid {
+            setresuid_keepcaps(uid)?;
+            super::set(None, CapSet::Effective, state.effective.clone())?;
+            report.actions.push(Action::Uid(uid));
+        }
+        if let Some(ref caps) = self.inheritable {
+            super::set(None, CapSet::Inheritable, caps.clone())?;
+            report.actions.push(Action::Inheritable(caps.clone()));
+        }
+        if let Some(ref caps) = self.ambient {
+            super::set(None, CapSet::Ambient, caps.clone())?;
+            report.actions.push(Action::Ambient(caps.clone()));
+        }
+        if self.no_new_privs {
+            securebits::set_no_new_privs()?;
+            report.actions.push(Action::NoNewPrivs);
+        }
+        Ok(report)
+    }
+}
+
+/// Switch to `uid` and `gid`, retaining exactly capabilities `keep`.
+///
+/// This is the equivalent of libcap-ng's `capng_change_id`: supplementary
+/// groups are cleared and permitted capabilities are retained across the
+/// switch, then `keep` is set as both the permitted and effective sets,
+/// while inheritable and ambient sets are cleared. The bounding set is
+/// left untouched.
+pub fn change_id(uid: u32, gid: u32, keep: &CapsHashSet) -> Result<()> {
+    change_id_with_groups(uid, gid, &[], keep)
+}
+
+/// Switch ids as `change_id`, setting supplementary groups to `groups`.
+///
+/// Nothing is changed if permitted capabilities cannot be retained, e.g.
+/// because securebits lock keepcaps off.
+pub fn change_id_with_groups(uid: u32, gid: u32, groups: &[u32], keep: &CapsHashSet) -> Result<()> {
+    let state = CapState::current()?;
+    for cap in &[Capability::CAP_SETUID, Capability::CAP_SETGID] {
+        if !state.effective.contains(cap) {
+            let reason = format!("{} is required to switch ids", cap);
+            return Err(Error::from_kind(ErrorKind::InvalidTransition(reason)));
+        }
+    }
+    if let Some(cap) = keep.difference(&state.permitted).next() {
+        let reason = format!("{} is not in the permitted set", cap);
+        return Err(Error::from_kind(ErrorKind::InvalidTransition(reason)));
+    }
+    check_keepcaps(securebits::get_securebits()?)?;
+
+    set_groups(groups)?;
+    setresgid(gid)?;
+    setresuid_keepcaps(uid)?;
+    let target = CapState {
+        ambient: CapsHashSet::new(),
+        bounding: state.bounding,
+        effective: keep.clone(),
+        inheritable: CapsHashSet::new(),
+        permitted: keep.clone(),
+    };
+    target.apply()
+}
+
+// Set supplementary groups, making sure they were actually changed.
+fn set_groups(groups: &[u32]) -> Result<()> {
+    if unsafe { libc::setgroups(groups.len(), groups.as_ptr()) } != 0 {
+        return Err(
+            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "setgroups error")
+        );
+    }
+    let mut current = vec![0; groups.len() + 1];
+    let n = unsafe { libc::getgroups(current.len() as libc::c_int, current.as_mut_ptr()) };
+    if n < 0 {
+        return Err(
+            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "getgroups error")
+        );
+    }
+    current.truncate(n as usize);
+    current.sort_unstable();
+    let mut expected = groups.to_vec();
+    expected.sort_unstable();
+    expected.dedup();
+    if current != expected {
+        bail!("supplementary groups were not changed to {:?}", groups);
+    }
+    Ok(())
+}
+
+fn setresgid(gid: u32) -> Result<()> {
+    if unsafe { libc::setresgid(gid, gid, gid) } != 0 {
+        return Err(
+            Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "setresgid error")
+        );
+    }
+    Ok(())
+}
+
+// Whether securebits `bits` already retain permitted capabilities across
+// uid switches, making keepcaps unnecessary.
+fn retains_caps(bits: SecureBits) -> bool {
+    bits.contains(SecureBits::NO_SETUID_FIXUP) || bits.contains(SecureBits::KEEP_CAPS)
+}
+
+// Check that permitted capabilities can be retained across a uid switch
+// under securebits `bits`.
+fn check_keepcaps(bits: SecureBits) -> Result<()> {
+    if !retains_caps(bits) && bits.contains(SecureBits::KEEP_CAPS_LOCKED) {
+        let reason = "securebits lock keepcaps off, capabilities would be lost".to_string();
+        return Err(Error::from_kind(ErrorKind::InvalidTransition(reason)));
+    }
+    Ok(())
+}
+
+// Switch uids, retaining permitted capabilities.
+//
+// As for `launch`, keepcaps is left alone when securebits already retain
+// capabilities, as it may be locked.
+fn setresuid_keepcaps(uid: u32) -> Result<()> {
+    let set_keepcaps = !retains_caps(securebits::get_securebits()?);
+    if set_keepcaps {
+        securebits::set_keepcaps(true)?;
+    }
+    let r = unsafe { libc::setresuid(uid, uid, uid) };
+    let err = errno::errno();
+    if set_keepcaps {
+        securebits::set_keepcaps(false)?;
+    }
+    if r != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(err)).chain_err(|| "setresuid error"));
+    }
+    Ok(())
+}
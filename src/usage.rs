@@ -0,0 +1,202 @@
+//! Discovery of exercised capabilities from existing kernel audit records.
+//!
+//! Computing a least-privilege set needs empirical input: which
+//! capabilities a workload actually exercises. With SELinux, every
+//! capability check can be audited (e.g. via `auditallow` rules on the
+//! `capability` and `capability2` classes, or by running the workload in
+//! a permissive domain where its checks are denied and logged), and ends
+//! up as AVC records in the audit log.
+//!
+//! This module only parses such AVC records: it does not enable auditing,
+//! which must already be configured by the SELinux policy. On hosts
+//! without it, no records are produced and nothing is discovered.
+//!
+//! A `Recorder` collects the records appended to the audit log while a
+//! workload runs, and `record` runs a command in between:
+//!
+//! ```rust,no_run
+//! use caps::usage;
+//! use std::path::Path;
+//! use std::process::Command;
+//!
+//! let log = Path::new(usage::AUDIT_LOG);
+//! let discovery = usage::record(Command::new("ping").args(&["-c1", "::1"]), log).unwrap();
+//! println!("exercised: {:?}", discovery.used);
+//! ```
+//!
+//! Reading the audit log usually requires root privileges.
+
+use super::{Capability, CapsHashSet};
+use errors::*;
+use selinux;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default location of the audit log written by `auditd`.
+pub const AUDIT_LOG: &str = "/var/log/audit/audit.log";
+
+// Once the workload exited, the log is considered complete after it
+// stopped growing for `QUIET`, checked every `POLL`, or after `SETTLE`.
+const POLL: Duration = Duration::from_millis(20);
+const QUIET: Duration = Duration::from_millis(200);
+const SETTLE: Duration = Duration::from_secs(5);
+
+/// Use of a capability, as reported by an AVC record.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Usage {
+    /// Process which exercised the capability.
+    pub pid: i32,
+    /// Command name of the process, if reported.
+    pub comm: Option<String>,
+    /// Exercised capability.
+    pub capability: Capability,
+    /// Whether the check was denied (rather than granted and audited).
+    pub denied: bool,
+}
+
+/// Parse capability uses from an audit log line.
+///
+/// Lines which are not AVC records of a capability class yield nothing,
+/// while records listing several permissions yield one use for each.
+pub fn parse_line(line: &str) -> Vec<Usage> {
+    let avc = match line.find("avc:") {
+        Some(i) => &line[i + 4..],
+        None => return vec![],
+    };
+    let denied = match avc.split_whitespace().next() {
+        Some("denied") => true,
+        Some("granted") => false,
+        _ => return vec![],
+    };
+    let perms = match (avc.find('{'), avc.find('}')) {
+        (Some(start), Some(end)) if start < end => &avc[start + 1..end],
+        _ => return vec![],
+    };
+    let (mut pid, mut comm, mut class) = (None, None, None);
+    for field in avc.split_whitespace() {
+        let mut kv = field.splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some("pid"), Some(v)) => pid = v.parse().ok(),
+            (Some("comm"), Some(v)) => comm = Some(v.trim_matches('"').to_string()),
+            (Some("tclass"), Some(v)) => class = Some(v),
+            _ => {}
+        }
+    }
+    let (pid, class) = match (pid, class) {
+        (Some(pid), Some(class)) => (pid, class),
+        _ => return vec![],
+    };
+    perms
+        .split_whitespace()
+        .filter_map(|perm| selinux::from_permission(class, perm))
+        .map(|capability| Usage {
+            pid,
+            comm: comm.clone(),
+            capability,
+            denied,
+        })
+        .collect()
+}
+
+/// Collector of audit records appended to a log.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Recorder {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Recorder {
+    /// Start recording records appended to the audit log at `path`.
+    pub fn start<P: AsRef<Path>>(path: P) -> Result<Recorder> {
+        let path = path.as_ref().to_path_buf();
+        let offset = len(&path)?;
+        Ok(Recorder { path, offset })
+    }
+
+    /// Wait for pending records to be written, until the log stopped
+    /// growing for a while.
+    ///
+    /// This is a heuristic: auditd gives no completion signal, and a
+    /// record delayed for longer than this can still be missed.
+    pub fn settle(&self) -> Result<()> {
+        let start = Instant::now();
+        let (mut last, mut quiet) = (len(&self.path)?, Instant::now());
+        while quiet.elapsed() < QUIET && start.elapsed() < SETTLE {
+            thread::sleep(POLL);
+            let current = len(&self.path)?;
+            if current != last {
+                last = current;
+                quiet = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Return capability uses recorded since `start`.
+    ///
+    /// If the log was rotated meanwhile, it is read from its beginning.
+    pub fn collect(&self) -> Result<Vec<Usage>> {
+        let err = || format!("failed to read {}", self.path.display());
+        let mut file = File::open(&self.path).chain_err(err)?;
+        let len = file.metadata().chain_err(err)?.len();
+        let offset = if len < self.offset { 0 } else { self.offset };
+        file.seek(SeekFrom::Start(offset)).chain_err(err)?;
+        let mut buf = vec![];
+        file.read_to_end(&mut buf).chain_err(err)?;
+        Ok(String::from_utf8_lossy(&buf)
+            .lines()
+            .flat_map(parse_line)
+            .collect())
+    }
+}
+
+// Size of the file at `path`.
+fn len(path: &Path) -> Result<u64> {
+    let meta = path
+        .metadata()
+        .chain_err(|| format!("failed to read {}", path.display()))?;
+    Ok(meta.len())
+}
+
+/// Outcome of a recorded workload.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Discovery {
+    /// Exit status of the workload.
+    pub status: ExitStatus,
+    /// Process ID of the workload.
+    pub pid: u32,
+    /// All capability uses recorded while the workload ran, by any process.
+    pub records: Vec<Usage>,
+    /// Capabilities exercised by the workload process itself.
+    pub used: CapsHashSet,
+}
+
+/// Run `cmd` to completion, and report capabilities it exercised
+/// according to the audit log at `log`.
+///
+/// Records are collected once the log settled (see `Recorder::settle`).
+/// Processes spawned by the workload are not tracked: their uses are only
+/// part of `records`.
+pub fn record(cmd: &mut Command, log: &Path) -> Result<Discovery> {
+    let recorder = Recorder::start(log)?;
+    let mut child = cmd.spawn().chain_err(|| "failed to spawn workload")?;
+    let pid = child.id();
+    let status = child.wait().chain_err(|| "failed to wait for workload")?;
+    recorder.settle()?;
+    let records = recorder.collect()?;
+    let used = records
+        .iter()
+        .filter(|u| u.pid as u32 == pid)
+        .map(|u| u.capability)
+        .collect();
+    Ok(Discovery {
+        status,
+        pid,
+        records,
+        used,
+    })
+}
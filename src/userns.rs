@@ -0,0 +1,219 @@
+//! User namespaces.
+//!
+//! Capabilities are relative to user namespaces: a process holding a
+//! capability in its effective set is only privileged over resources
+//! governed by its own user namespace and the ones nested below it.
+//! A raw `has_cap` is thus misleading in containerized environments.
+//!
+//! `ns_capable` mirrors the kernel rules instead. A process has
+//! capability `cap` in user namespace `ns` if either:
+//!  * `ns` is its own user namespace or nested below it, and `cap` is in
+//!    its effective set;
+//!  * `ns` is nested below its own user namespace, and its effective UID
+//!    owns the ancestor of `ns` (possibly `ns` itself) which is a direct
+//!    child of its own user namespace.
+
+use errno;
+use libc;
+
+use super::{CapSet, CapState, Capability, CapsHashSet};
+use errors::*;
+use nr;
+use runtime;
+use scan;
+use std::fs::{self, File};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+
+/// A user namespace, with its ancestors.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct UserNamespace {
+    /// Inode number identifying this namespace.
+    pub inode: u64,
+    /// Effective UID of the namespace creator.
+    pub owner: libc::uid_t,
+    /// Parent namespace, or `None` for the outermost visible namespace.
+    pub parent: Option<Box<UserNamespace>>,
+}
+
+impl UserNamespace {
+    /// Return the user namespace of process `pid`.
+    pub fn of(pid: libc::pid_t) -> Result<UserNamespace> {
+        let path = format!("/proc/{}/ns/user", pid);
+        let file = File::open(&path).chain_err(|| format!("failed to open {}", path))?;
+        UserNamespace::from_file(&file)
+    }
+
+    /// Return the user namespace of the current process.
+    pub fn current() -> Result<UserNamespace> {
+        UserNamespace::of(unsafe { libc::getpid() })
+    }
+
+    /// Return the user namespace referred to by `file` (e.g. `/proc/<pid>/ns/user`).
+    pub fn from_file(file: &File) -> Result<UserNamespace> {
+        let inode = file
+            .metadata()
+            .chain_err(|| "failed to inspect user namespace")?
+            .ino();
+        let mut owner: libc::uid_t = 0;
+        let fd = file.as_raw_fd();
+        if unsafe { libc::ioctl(fd, nr::NS_GET_OWNER_UID as _, &mut owner) } != 0 {
+            return Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+                .chain_err(|| "NS_GET_OWNER_UID error"));
+        }
+        let parent = match unsafe { libc::ioctl(fd, nr::NS_GET_PARENT as _) } {
+            p if p >= 0 => {
+                let parent = unsafe { File::from_raw_fd(p) };
+                Some(Box::new(UserNamespace::from_file(&parent)?))
+            }
+            // The parent is outside of the caller namespace, or this is the
+            // initial namespace.
+            _ if errno::errno().0 == libc::EPERM => None,
+            _ => {
+                return Err(Error::from_kind(ErrorKind::Sys(errno::errno()))
+                    .chain_err(|| "NS_GET_PARENT error"))
+            }
+        };
+        Ok(UserNamespace {
+            inode,
+            owner,
+            parent,
+        })
+    }
+
+    /// Return whether this namespace is `ns` or one of its ancestors.
+    pub fn contains(&self, ns: &UserNamespace) -> bool {
+        let mut cur = Some(ns);
+        while let Some(n) = cur {
+            if n.inode == self.inode {
+                return true;
+            }
+            cur = n.parent.as_deref();
+        }
+        false
+    }
+}
+
+/// A UID mapping of a user namespace, from `/proc/<pid>/uid_map`.
+///
+/// Each entry maps a range of UIDs inside the namespace to a range of
+/// "outside" UIDs, as seen from the user namespace of the reader.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct UidMap {
+    /// Mapped ranges, as `(inside, outside, count)`.
+    pub entries: Vec<(u32, u32, u32)>,
+}
+
+impl UidMap {
+    /// Read the UID mapping of the user namespace of process `pid`.
+    pub fn read(pid: libc::pid_t) -> Result<UidMap> {
+        let path = format!("/proc/{}/uid_map", pid);
+        let content = fs::read_to_string(&path).chain_err(|| format!("failed to read {}", path))?;
+        UidMap::parse(&content).chain_err(|| format!("invalid {}", path))
+    }
+
+    /// Parse a UID mapping, in the format of `/proc/<pid>/uid_map`.
+    pub fn parse(s: &str) -> Result<UidMap> {
+        let mut entries = vec![];
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<u32> = match line.split_whitespace().map(|f| f.parse()).collect() {
+                Ok(f) => f,
+                Err(_) => bail!("invalid mapping '{}'", line),
+            };
+            if fields.len() != 3 {
+                bail!("invalid mapping '{}'", line);
+            }
+            entries.push((fields[0], fields[1], fields[2]));
+        }
+        Ok(UidMap { entries })
+    }
+
+    /// Translate UID `inside` of the namespace into the reader's view.
+    ///
+    /// Ranges overflowing the UID space, which the kernel does not accept,
+    /// are ignored.
+    pub fn to_outside(&self, inside: u32) -> Option<u32> {
+        self.entries
+            .iter()
+            .filter(|&&(i, _, n)| inside >= i && inside - i < n)
+            .find_map(|&(i, o, _)| o.checked_add(inside - i))
+    }
+
+    /// Translate UID `outside`, in the reader's view, into the namespace.
+    ///
+    /// Ranges overflowing the UID space are ignored, as in `to_outside`.
+    pub fn to_inside(&self, outside: u32) -> Option<u32> {
+        self.entries
+            .iter()
+            .filter(|&&(_, o, n)| outside >= o && outside - o < n)
+            .find_map(|&(i, o, _)| i.checked_add(outside - o))
+    }
+}
+
+/// Decide whether credentials have capability `cap` in user namespace `target`.
+///
+/// Credentials are made of the user namespace `cred_ns`, effective UID
+/// `euid` and effective set `effective`.
+pub fn capable_in(
+    cred_ns: &UserNamespace,
+    euid: libc::uid_t,
+    effective: &CapsHashSet,
+    cap: Capability,
+    target: &UserNamespace,
+) -> bool {
+    let mut ns = target;
+    loop {
+        if ns.inode == cred_ns.inode {
+            return effective.contains(&cap);
+        }
+        let parent = match ns.parent {
+            Some(ref p) => p,
+            None => return false,
+        };
+        // The owner of a child namespace has all capabilities in it.
+        if parent.inode == cred_ns.inode && ns.owner == euid {
+            return true;
+        }
+        ns = parent;
+    }
+}
+
+/// Return whether process `pid` has capability `cap` in user namespace `ns`.
+pub fn ns_capable(pid: libc::pid_t, cap: Capability, ns: &UserNamespace) -> Result<bool> {
+    let cred_ns = UserNamespace::of(pid)?;
+    let state = CapState::from_proc(pid, pid)?;
+    let euid = match scan::status_field(pid, "Uid:", 1) {
+        Some(euid) => euid,
+        None => bail!("failed to read effective UID of process {}", pid),
+    };
+    Ok(capable_in(&cred_ns, euid, &state.effective, cap, ns))
+}
+
+/// Enter a new user namespace, mapped to root, with full capabilities.
+///
+/// The effective UID and GID of the caller are mapped to root in the new
+/// namespace, after denying `setgroups(2)` as required for unprivileged
+/// callers. This returns once the calling thread holds all capabilities
+/// supported by the running kernel in the new namespace.
+///
+/// The kernel refuses to move multi-threaded processes into a new user
+/// namespace, thus this must be called before spawning any thread (e.g.
+/// right after `fork`).
+pub fn unshare_and_map_root() -> Result<()> {
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER) } != 0 {
+        return Err(Error::from_kind(ErrorKind::Sys(errno::errno())).chain_err(|| "unshare error"));
+    }
+    write_map("/proc/self/uid_map", &format!("0 {} 1\n", uid))?;
+    write_map("/proc/self/setgroups", "deny")?;
+    write_map("/proc/self/gid_map", &format!("0 {} 1\n", gid))?;
+    let effective = super::read(None, CapSet::Effective)?;
+    if !runtime::all_supported().is_subset(&effective) {
+        bail!("capabilities not gained in new user namespace");
+    }
+    Ok(())
+}
+
+fn write_map(path: &str, content: &str) -> Result<()> {
+    fs::write(path, content).chain_err(|| format!("failed to write {}", path))
+}
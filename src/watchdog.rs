@@ -0,0 +1,141 @@
+//! Continuous enforcement of a capability policy.
+//!
+//! As defense-in-depth against code which quietly re-acquires privileges,
+//! a `Watchdog` thread periodically re-reads the capabilities of all
+//! threads of the process and, if any of them drifted from the policy,
+//! restricts all threads back to it via `psx::restrict_all_threads`.
+//!
+//! Only capability sets are watched, not securebits nor "no new
+//! privileges", which can not be unset anyway once locked.
+
+use super::CapState;
+use errors::*;
+use policy::{Policy, Violation};
+use psx;
+use securebits::SecureBits;
+use std::fs;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use trace;
+
+/// Policy violations found on a thread.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Drift {
+    /// Drifting thread.
+    pub tid: i32,
+    /// Violations found on the thread.
+    pub violations: Vec<Violation>,
+}
+
+/// Event reported by the watchdog.
+#[derive(Debug)]
+pub enum Event {
+    /// Drift was found and corrected.
+    Corrected(Vec<Drift>),
+    /// Inspecting or correcting threads failed.
+    Failed(Error),
+}
+
+/// Handle to a running watchdog thread, which is stopped on drop.
+#[derive(Debug)]
+pub struct Watchdog {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Check threads against `policy` every `interval`, reporting drift
+    /// through the `tracing` facade, if enabled.
+    ///
+    /// Events are emitted with the "caps" target, at the WARN level for
+    /// corrected drift and the ERROR level for failures. Use `start_with`
+    /// to handle them otherwise.
+    pub fn start(policy: Policy, interval: Duration) -> Result<Watchdog> {
+        Watchdog::start_with(policy, interval, trace::watchdog)
+    }
+
+    /// Check threads against `policy` every `interval`, reporting drift to
+    /// `on_event`.
+    pub fn start_with<F>(policy: Policy, interval: Duration, on_event: F) -> Result<Watchdog>
+    where
+        F: Fn(&Event) + Send + 'static,
+    {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = stop.clone();
+        let handle = thread::Builder::new()
+            .name("caps-watchdog".into())
+            .spawn(move || loop {
+                {
+                    let (ref lock, ref cvar) = *thread_stop;
+                    let stopped = lock.lock().unwrap_or_else(|e| e.into_inner());
+                    let (stopped, _) = cvar
+                        .wait_timeout(stopped, interval)
+                        .unwrap_or_else(|e| e.into_inner());
+                    if *stopped {
+                        return;
+                    }
+                }
+                match check(&policy) {
+                    Ok(ref drifts) if drifts.is_empty() => {}
+                    Ok(drifts) => {
+                        let event = match psx::restrict_all_threads(&policy.allowed_masks()) {
+                            Ok(()) => Event::Corrected(drifts),
+                            Err(e) => Event::Failed(e),
+                        };
+                        on_event(&event);
+                    }
+                    Err(e) => on_event(&Event::Failed(e)),
+                }
+            })
+            .chain_err(|| "failed to spawn watchdog thread")?;
+        Ok(Watchdog {
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stop the watchdog thread, waiting for it to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let (ref lock, ref cvar) = *self.stop;
+        *lock.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        cvar.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Check all threads of the current process against `policy` once.
+pub fn check(policy: &Policy) -> Result<Vec<Drift>> {
+    let pid = ::std::process::id() as i32;
+    let mut res = vec![];
+    for entry in fs::read_dir("/proc/self/task").chain_err(|| "failed to list threads")? {
+        let entry = entry.chain_err(|| "failed to list threads")?;
+        let tid = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(tid) => tid,
+            None => continue,
+        };
+        // Threads may exit while being inspected.
+        let state = match CapState::from_proc(pid, tid) {
+            Ok(state) => state,
+            Err(_) => continue,
+        };
+        // Only capability sets are checked.
+        let violations = policy.check(&state, SecureBits::ALL, true);
+        if !violations.is_empty() {
+            res.push(Drift { tid, violations });
+        }
+    }
+    Ok(res)
+}
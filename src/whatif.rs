@@ -0,0 +1,61 @@
+//! What-if analysis for dropping capabilities.
+//!
+//! This module helps assessing the consequences of dropping a capability,
+//! before actually doing it.
+
+use super::{CapSet, Capability};
+use errors::*;
+use file;
+use knowledge::{self, Operation};
+use profiles::{Profile, PROFILES};
+use std::path::{Path, PathBuf};
+
+/// Impact of dropping a capability.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Impact {
+    /// Analyzed capability.
+    pub capability: Capability,
+    /// Sets of the current thread which currently contain the capability.
+    pub held: Vec<CapSet>,
+    /// Known operations which would start failing.
+    pub operations: Vec<&'static Operation>,
+    /// Files whose capabilities depend on it.
+    pub files: Vec<PathBuf>,
+    /// Registered profiles (see `profiles`) which keep it.
+    pub profiles: Vec<&'static Profile>,
+}
+
+/// Analyze the impact of dropping capability `cap` from the current thread.
+///
+/// Each of the given `files` is checked for file capabilities which
+/// require `cap` (either as permitted or inheritable), and registered
+/// profiles are checked for keeping `cap`.
+pub fn drop_impact<P: AsRef<Path>>(cap: Capability, files: &[P]) -> Result<Impact> {
+    let mut held = vec![];
+    for cset in &[
+        CapSet::Ambient,
+        CapSet::Bounding,
+        CapSet::Effective,
+        CapSet::Inheritable,
+        CapSet::Permitted,
+    ] {
+        if super::has_cap(None, *cset, cap)? {
+            held.push(*cset);
+        }
+    }
+    let mut dependents = vec![];
+    for f in files {
+        if let Some(fcaps) = file::read(f)? {
+            if fcaps.permitted.contains(&cap) || fcaps.inheritable.contains(&cap) {
+                dependents.push(f.as_ref().to_path_buf());
+            }
+        }
+    }
+    Ok(Impact {
+        capability: cap,
+        held,
+        operations: knowledge::operations(cap),
+        files: dependents,
+        profiles: PROFILES.iter().filter(|p| p.caps.contains(&cap)).collect(),
+    })
+}
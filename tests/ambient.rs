@@ -21,7 +21,7 @@ fn test_ambient_clear() {
 fn test_ambient_drop() {
     caps::drop(None, caps::CapSet::Ambient, caps::Capability::CAP_CHOWN).unwrap();
     let no_cap = caps::has_cap(None, caps::CapSet::Ambient, caps::Capability::CAP_CHOWN).unwrap();
-    assert_eq!(no_cap, false);
+    assert!(!no_cap);
 }
 
 #[test]
@@ -54,3 +54,10 @@ fn test_ambient_set() {
     caps::drop(None, caps::CapSet::Ambient, caps::Capability::CAP_CHOWN).unwrap();
     assert!(caps::set(None, caps::CapSet::Ambient, v).is_err());
 }
+
+#[test]
+fn test_ambient_errno() {
+    caps::drop(None, caps::CapSet::Inheritable, caps::Capability::CAP_CHOWN).unwrap();
+    let e = caps::raise(None, caps::CapSet::Ambient, caps::Capability::CAP_CHOWN).unwrap_err();
+    assert_eq!(e.errno().map(|e| e.0), Some(1));
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "arbitrary")]
+
+extern crate arbitrary;
+extern crate caps;
+use arbitrary::{Arbitrary, Unstructured};
+use caps::file::FileCaps;
+use caps::{CapState, Capability};
+
+// Deterministic pseudo-random input.
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 37 + 11) as u8).collect()
+}
+
+#[test]
+fn test_arbitrary_capability() {
+    let data = input(256);
+    let mut u = Unstructured::new(&data);
+    while !u.is_empty() {
+        let cap = Capability::arbitrary(&mut u).unwrap();
+        assert!(caps::all().contains(&cap));
+    }
+}
+
+#[test]
+fn test_arbitrary_file_caps_roundtrip() {
+    let data = input(1024);
+    let mut u = Unstructured::new(&data);
+    for _ in 0..16 {
+        let fcaps = FileCaps::arbitrary(&mut u).unwrap();
+        assert_eq!(FileCaps::from_xattr(&fcaps.to_xattr()).unwrap(), fcaps);
+    }
+}
+
+#[test]
+fn test_arbitrary_cap_state_edges() {
+    let empty = CapState::arbitrary(&mut Unstructured::new(&[0; 40])).unwrap();
+    assert_eq!(empty, CapState::default());
+    let full = CapState::arbitrary(&mut Unstructured::new(&[0xff; 40])).unwrap();
+    assert_eq!(full.permitted, caps::all());
+    assert_eq!(full.bounding, caps::all());
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "libc")]
+
+// Fork policies affect every fork of the process, thus they are tested in
+// their own binary.
+extern crate caps;
+extern crate libc;
+use caps::{this_thread, threads, CapSet, Capability};
+
+fn fork_and_check(cap: Capability) -> bool {
+    match unsafe { libc::fork() } {
+        0 => {
+            let held = this_thread::has_cap(CapSet::Effective, cap).unwrap_or(true);
+            unsafe { libc::_exit(held as i32) };
+        }
+        pid => {
+            assert!(pid > 0);
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            libc::WEXITSTATUS(status) == 1
+        }
+    }
+}
+
+#[test]
+fn test_fork_policy() {
+    let cap = Capability::CAP_SYS_PACCT;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let mut policy = this_thread::state().unwrap();
+    policy.effective.remove(&cap);
+    threads::set_fork_policy(&policy).unwrap();
+    assert!(!fork_and_check(cap));
+    assert!(this_thread::has_cap(CapSet::Effective, cap).unwrap());
+
+    threads::clear_fork_policy();
+    assert!(fork_and_check(cap));
+}
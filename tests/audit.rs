@@ -0,0 +1,40 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::audit::{self, Report, Severity};
+use caps::securebits::SecureBits;
+use caps::CapState;
+
+#[test]
+fn test_audit_evaluate() {
+    let root = CapState {
+        bounding: caps::all(),
+        effective: caps::all(),
+        permitted: caps::all(),
+        ..CapState::default()
+    };
+    let report = Report::evaluate(0, 0, root, SecureBits::default(), false);
+    assert_eq!(report.max_severity(), Some(Severity::Critical));
+    assert_eq!(report.findings[0].check, "root-equivalent-caps");
+    assert!(!report.passes(Severity::Medium));
+
+    let hardened = CapState {
+        bounding: caps::caps![CAP_NET_BIND_SERVICE],
+        effective: caps::caps![CAP_NET_BIND_SERVICE],
+        permitted: caps::caps![CAP_NET_BIND_SERVICE],
+        ..CapState::default()
+    };
+    let report = Report::evaluate(1000, 1000, hardened, SecureBits::LOCKDOWN, true);
+    assert!(report.findings.is_empty());
+    assert!(report.passes(Severity::Low));
+}
+
+#[test]
+fn test_audit_report() {
+    let report = audit::report().unwrap();
+    let sorted = report
+        .findings
+        .windows(2)
+        .all(|w| w[0].severity >= w[1].severity);
+    assert!(sorted);
+}
@@ -0,0 +1,50 @@
+extern crate caps;
+
+use caps::backend::{self, Linux, PrivilegeBackend, Procfs};
+use caps::errors::ErrorKind;
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_backend_native() {
+    let backend = backend::native().unwrap();
+    assert_eq!(backend.name(), "linux");
+    let effective = caps::read(None, CapSet::Effective).unwrap();
+    assert_eq!(backend.effective().unwrap().len(), effective.len());
+    assert_eq!(
+        backend.has("chown").unwrap(),
+        effective.contains(&Capability::CAP_CHOWN)
+    );
+    assert!(backend.has("cap_foo").is_err());
+}
+
+#[test]
+fn test_backend_procfs() {
+    assert_eq!(Procfs.effective().unwrap(), Linux.effective().unwrap());
+    assert_eq!(Procfs.permitted().unwrap(), Linux.permitted().unwrap());
+    let err = Procfs.drop("CAP_CHOWN").unwrap_err();
+    match *err.kind() {
+        ErrorKind::Unsupported => {}
+        ref k => panic!("unexpected error: {:?}", k),
+    }
+}
+
+#[test]
+fn test_backend_linux_changes() {
+    if !caps::has_cap(None, CapSet::Permitted, Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    thread::spawn(|| {
+        Linux.lower("CAP_CHOWN").unwrap();
+        assert!(!Linux.has("CAP_CHOWN").unwrap());
+        Linux.raise("CAP_CHOWN").unwrap();
+        assert!(Linux.has("CAP_CHOWN").unwrap());
+        Linux.drop("CAP_KILL").unwrap();
+        assert!(!Linux.permitted().unwrap().contains(&"CAP_KILL".to_string()));
+        Linux.restrict(&["cap_chown", "setpcap"]).unwrap();
+        assert_eq!(Linux.permitted().unwrap(), vec!["CAP_CHOWN", "CAP_SETPCAP"]);
+        assert_eq!(caps::read(None, CapSet::Bounding).unwrap().len(), 2);
+    })
+    .join()
+    .unwrap();
+}
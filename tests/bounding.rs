@@ -6,7 +6,8 @@ fn test_bounding_has_cap() {
         None,
         caps::CapSet::Bounding,
         caps::Capability::CAP_SYS_CHROOT,
-    ).unwrap();
+    )
+    .unwrap();
 }
 
 #[test]
@@ -57,3 +58,25 @@ fn test_bounding_set() {
     let v = caps::CapsHashSet::new();
     assert!(caps::set(None, caps::CapSet::Bounding, v).is_err());
 }
+
+#[test]
+fn test_bounding_ensure() {
+    std::thread::spawn(|| {
+        let cur = caps::read(None, caps::CapSet::Bounding).unwrap();
+        assert!(!caps::ensure(None, caps::CapSet::Bounding, &cur).unwrap());
+        if !cur.contains(&caps::Capability::CAP_SYS_CHROOT) {
+            assert!(caps::ensure(None, caps::CapSet::Bounding, &caps::all()).is_err());
+            return;
+        }
+        if !caps::has_cap(None, caps::CapSet::Effective, caps::Capability::CAP_SETPCAP).unwrap() {
+            return;
+        }
+        let mut lower = cur.clone();
+        lower.remove(&caps::Capability::CAP_SYS_CHROOT);
+        assert!(caps::ensure(None, caps::CapSet::Bounding, &lower).unwrap());
+        assert_eq!(caps::read(None, caps::CapSet::Bounding).unwrap(), lower);
+        assert!(caps::ensure(None, caps::CapSet::Bounding, &cur).is_err());
+    })
+    .join()
+    .unwrap();
+}
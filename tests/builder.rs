@@ -0,0 +1,32 @@
+#[macro_use]
+extern crate caps;
+use caps::{runtime, CapState};
+
+#[test]
+fn test_builder() {
+    let state = CapState::builder()
+        .permitted(caps![CAP_CHOWN, CAP_KILL])
+        .effective(caps![CAP_CHOWN])
+        .inheritable(caps![CAP_KILL])
+        .ambient(caps![CAP_KILL])
+        .bounding_all_supported()
+        .build()
+        .unwrap();
+    assert_eq!(state.effective, caps![CAP_CHOWN]);
+    assert_eq!(state.bounding, runtime::all_supported());
+    assert_eq!(CapState::builder().build().unwrap(), CapState::default());
+}
+
+#[test]
+fn test_builder_invalid() {
+    let err = CapState::builder()
+        .effective(caps![CAP_CHOWN])
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("CAP_CHOWN"));
+    let res = CapState::builder()
+        .permitted(caps![CAP_KILL])
+        .ambient(caps![CAP_KILL])
+        .build();
+    assert!(res.is_err());
+}
@@ -0,0 +1,34 @@
+extern crate caps;
+use caps::cache::CachedCaps;
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_cached_caps() {
+    thread::spawn(|| {
+        let mut cached = CachedCaps::for_current_thread().unwrap();
+        for cset in &[
+            CapSet::Ambient,
+            CapSet::Bounding,
+            CapSet::Effective,
+            CapSet::Inheritable,
+            CapSet::Permitted,
+        ] {
+            for cap in caps::all() {
+                let has = caps::has_cap(None, *cset, cap).unwrap();
+                assert_eq!(cached.has_cap(*cset, cap), has);
+            }
+        }
+        let chown = Capability::CAP_CHOWN;
+        if !cached.has_cap(CapSet::Effective, chown) {
+            return;
+        }
+        caps::drop(None, CapSet::Effective, chown).unwrap();
+        // Stale until refreshed.
+        assert!(cached.has_cap(CapSet::Effective, chown));
+        cached.refresh().unwrap();
+        assert!(!cached.has_cap(CapSet::Effective, chown));
+    })
+    .join()
+    .unwrap();
+}
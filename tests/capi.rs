@@ -0,0 +1,49 @@
+#![cfg(feature = "capi")]
+
+extern crate caps;
+extern crate errno;
+extern crate libc;
+use caps::capi::{self, CAPS_BOUNDING, CAPS_EFFECTIVE, CAPS_PERMITTED};
+use caps::{CapSet, Capability};
+use std::ffi::CStr;
+
+#[test]
+fn test_capi_get_set() {
+    let mut mask = 0;
+    assert_eq!(unsafe { capi::caps_get(0, CAPS_PERMITTED, &mut mask) }, 0);
+    let permitted = caps::read(None, CapSet::Permitted).unwrap();
+    assert_eq!(mask.count_ones() as usize, permitted.len());
+    assert_eq!(capi::caps_set(0, CAPS_EFFECTIVE, 0), 0);
+    assert!(caps::read(None, CapSet::Effective).unwrap().is_empty());
+    assert_eq!(capi::caps_set(0, CAPS_EFFECTIVE, 1 << 63), -1);
+    assert_eq!(errno::errno().0, libc::EINVAL);
+    assert_eq!(unsafe { capi::caps_get(0, 42, &mut mask) }, -1);
+}
+
+#[test]
+fn test_capi_has_raise_drop() {
+    let chown = Capability::CAP_CHOWN.index().into();
+    let bounding = caps::has_cap(None, CapSet::Bounding, Capability::CAP_CHOWN).unwrap();
+    assert_eq!(capi::caps_has(0, CAPS_BOUNDING, chown), bounding as i32);
+    assert_eq!(capi::caps_drop(0, CAPS_EFFECTIVE, chown), 0);
+    assert_eq!(capi::caps_has(0, CAPS_EFFECTIVE, chown), 0);
+    let permitted = caps::has_cap(None, CapSet::Permitted, Capability::CAP_CHOWN).unwrap();
+    let r = capi::caps_raise(0, CAPS_EFFECTIVE, chown);
+    assert_eq!(r == 0, permitted);
+    assert_eq!(capi::caps_has(0, CAPS_EFFECTIVE, 1000), -1);
+}
+
+#[test]
+fn test_capi_names() {
+    let idx = unsafe { capi::caps_from_name(b"net_raw\0".as_ptr() as *const _) };
+    assert_eq!(idx, i32::from(Capability::CAP_NET_RAW.index()));
+    let idx = unsafe { capi::caps_from_name(b"CAP_NET_RAW\0".as_ptr() as *const _) };
+    assert_eq!(idx, i32::from(Capability::CAP_NET_RAW.index()));
+    assert_eq!(
+        unsafe { capi::caps_from_name(b"foo\0".as_ptr() as *const _) },
+        -1
+    );
+    let name = unsafe { CStr::from_ptr(capi::caps_to_name(idx)) };
+    assert_eq!(name.to_str().unwrap(), "cap_net_raw");
+    assert!(capi::caps_to_name(-1).is_null());
+}
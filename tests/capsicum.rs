@@ -0,0 +1,29 @@
+#![cfg(target_os = "freebsd")]
+
+extern crate caps;
+
+use caps::capsicum::{self, Right, Rights};
+use std::fs::File;
+
+#[test]
+fn test_rights_builder() {
+    let rights = Rights::new().allow(Right::READ).allow(Right::FSTAT);
+    assert!(rights.contains(Right::READ));
+    assert!(rights.contains(Right::FSTAT));
+    assert!(!rights.contains(Right::WRITE));
+    let rights = rights.remove(Right::FSTAT);
+    assert!(!rights.contains(Right::FSTAT));
+    assert_eq!(rights, Rights::new().allow(Right::READ));
+}
+
+#[test]
+fn test_rights_limit() {
+    assert!(!capsicum::in_capability_mode().unwrap());
+    let file = File::open("/dev/null").unwrap();
+    let rights = Rights::new().allow(Right::READ).allow(Right::FSTAT);
+    rights.limit(&file).unwrap();
+    assert_eq!(Rights::of(&file).unwrap(), rights);
+    // Rights cannot be regained.
+    let more = rights.allow(Right::WRITE);
+    assert!(more.limit(&file).is_err());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "libc")]
+
+// Restoring a checkpoint signals other threads, thus it is tested in its
+// own binary.
+extern crate caps;
+use caps::checkpoint::Checkpoint;
+use caps::lowlevel::Masks;
+use caps::{this_thread, CapSet, Capability};
+use std::sync::{mpsc, Arc, Barrier};
+use std::thread;
+
+#[test]
+fn test_checkpoint_restore() {
+    let cap = Capability::CAP_SYS_NICE;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let barrier = Arc::new(Barrier::new(2));
+    let (tx, rx) = mpsc::channel();
+    let b = barrier.clone();
+    let worker = thread::spawn(move || {
+        this_thread::drop(CapSet::Effective, cap).unwrap();
+        tx.send(this_thread::gettid()).unwrap();
+        b.wait();
+        this_thread::raise(CapSet::Effective, cap).unwrap();
+        b.wait();
+        b.wait();
+        this_thread::has_cap(CapSet::Effective, cap).unwrap()
+    });
+    let tid = rx.recv().unwrap();
+
+    let checkpoint = Checkpoint::capture_self().unwrap();
+    assert_eq!(checkpoint.get(tid).unwrap().effective & cap.bitmask(), 0);
+    let own = *checkpoint.get(this_thread::gettid()).unwrap();
+    assert_eq!(own, Masks::current().unwrap());
+    // Drop capabilities unknown to this crate, to be restored below.
+    let known = caps::all().iter().fold(0, |m, c| m | c.bitmask());
+    Masks {
+        effective: own.effective & known,
+        ..own
+    }
+    .apply()
+    .unwrap();
+    barrier.wait();
+    barrier.wait();
+
+    checkpoint.restore().unwrap();
+    barrier.wait();
+    assert!(!worker.join().unwrap());
+    assert!(this_thread::has_cap(CapSet::Effective, cap).unwrap());
+    // Capabilities unknown to this crate are restored too.
+    assert_eq!(Masks::current().unwrap(), own);
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "clap")]
+
+extern crate caps;
+extern crate clap;
+use caps::{CapSet, Capability, CapsListParser};
+use clap::{value_parser, Arg, Command};
+
+fn command() -> Command {
+    Command::new("test")
+        .arg(
+            Arg::new("cap")
+                .long("cap")
+                .value_parser(value_parser!(Capability)),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_parser(value_parser!(CapSet)),
+        )
+        .arg(Arg::new("keep").long("keep").value_parser(CapsListParser))
+}
+
+#[test]
+fn test_capability() {
+    for name in &["net_raw", "CAP_NET_RAW", "Cap_Net_Raw"] {
+        let m = command()
+            .try_get_matches_from(["test", "--cap", name])
+            .unwrap();
+        assert_eq!(
+            m.get_one::<Capability>("cap"),
+            Some(&Capability::CAP_NET_RAW)
+        );
+    }
+    let err = command()
+        .try_get_matches_from(["test", "--cap", "foo"])
+        .unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+    assert!(err.to_string().contains("'foo'"));
+}
+
+#[test]
+fn test_capset() {
+    let m = command()
+        .try_get_matches_from(["test", "--set", "permitted"])
+        .unwrap();
+    assert_eq!(m.get_one::<CapSet>("set"), Some(&CapSet::Permitted));
+    assert!(command()
+        .try_get_matches_from(["test", "--set", "other"])
+        .is_err());
+}
+
+#[test]
+fn test_list() {
+    let m = command()
+        .try_get_matches_from(["test", "--keep", "chown, cap_kill"])
+        .unwrap();
+    let keep = m.get_one::<caps::CapsHashSet>("keep").unwrap();
+    assert_eq!(keep.len(), 2);
+    assert!(keep.contains(&Capability::CAP_CHOWN));
+    assert!(keep.contains(&Capability::CAP_KILL));
+    let err = command()
+        .try_get_matches_from(["test", "--keep", "chown,foo"])
+        .unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+}
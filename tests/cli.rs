@@ -0,0 +1,174 @@
+#![cfg(feature = "cli")]
+
+extern crate caps;
+extern crate libc;
+extern crate serde_json;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Output, Stdio};
+
+fn caps(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_caps"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_inspect() {
+    let pid = unsafe { libc::getpid() }.to_string();
+    let out = caps(&["inspect", &pid]);
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.starts_with(&format!("Process {}\n", pid)));
+    assert!(text.contains("  Bounding:"));
+
+    let out = caps(&["inspect", "--json", &pid]);
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["pid"].to_string(), pid);
+    assert!(json["effective"].is_array());
+    assert!(json["no_new_privs"].is_boolean());
+
+    assert!(!caps(&["inspect"]).status.success());
+    assert!(!caps(&["inspect", "0"]).status.success());
+    assert!(!caps(&["bogus"]).status.success());
+}
+
+#[test]
+fn test_run() {
+    if !caps::has_cap(None, caps::CapSet::Effective, caps::Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    let out = caps(&[
+        "run",
+        "--keep",
+        "cap_kill",
+        "--",
+        "grep",
+        "CapBnd",
+        "/proc/self/status",
+    ]);
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(text.trim(), "CapBnd:\t0000000000000020");
+
+    assert_eq!(
+        caps(&["run", "--", "sh", "-c", "exit 3"]).status.code(),
+        Some(3)
+    );
+    assert!(!caps(&["run", "--keep", "bogus", "--", "true"])
+        .status
+        .success());
+    assert!(!caps(&["run", "--", "/nonexistent"]).status.success());
+    assert!(!caps(&["run"]).status.success());
+}
+
+#[test]
+fn test_diff() {
+    let pid = unsafe { libc::getpid() }.to_string();
+    let out = caps(&["diff", &pid, &pid]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "No differences\n");
+
+    let snapshot = std::env::temp_dir().join(format!("caps-diff-{}.json", pid));
+    std::fs::write(&snapshot, r#"{"effective": ["CAP_CHOWN"]}"#).unwrap();
+    assert!(!caps(&["diff", &pid, "/nonexistent"]).status.success());
+    let out = caps(&[
+        "diff",
+        snapshot.to_str().unwrap(),
+        snapshot.to_str().unwrap(),
+    ]);
+    assert!(out.status.success());
+    let out = caps(&["diff", &pid, snapshot.to_str().unwrap()]);
+    std::fs::remove_file(&snapshot).unwrap();
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    assert!(text.contains("Bounding:\n  - "));
+}
+
+#[test]
+fn test_watch() {
+    let cap = caps::Capability::CAP_CHOWN;
+    if !caps::has_cap(None, caps::CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    // The child drops `cap` once `go` is readable, and exits once `done`
+    // is closed.
+    let (mut go, mut done) = ([0; 2], [0; 2]);
+    unsafe {
+        assert_eq!(libc::pipe2(go.as_mut_ptr(), libc::O_CLOEXEC), 0);
+        assert_eq!(libc::pipe2(done.as_mut_ptr(), libc::O_CLOEXEC), 0);
+    }
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        // Only async-signal-safe operations are allowed in the child.
+        let mut byte = 0u8;
+        unsafe {
+            libc::close(go[1]);
+            libc::close(done[1]);
+            libc::read(go[0], &mut byte as *mut u8 as *mut libc::c_void, 1);
+        }
+        let (e, p, i) = caps::lowlevel::capget().unwrap();
+        caps::lowlevel::capset(e & !cap.bitmask(), p, i).unwrap();
+        unsafe {
+            libc::read(done[0], &mut byte as *mut u8 as *mut libc::c_void, 1);
+            libc::_exit(0)
+        };
+    }
+    unsafe {
+        libc::close(go[0]);
+        libc::close(done[0]);
+    }
+    let mut watcher = Command::new(env!("CARGO_BIN_EXE_caps"))
+        .args(["watch", "--json", "--interval", "5", &pid.to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(watcher.stderr.take().unwrap());
+    let mut stdout = BufReader::new(watcher.stdout.take().unwrap());
+
+    // Wait for the watcher to be ready before changing capabilities.
+    let mut line = String::new();
+    stderr.read_line(&mut line).unwrap();
+    assert_eq!(line, format!("caps: watching process {}\n", pid));
+    assert_eq!(
+        unsafe { libc::write(go[1], b"x".as_ptr() as *const libc::c_void, 1) },
+        1
+    );
+    line.clear();
+    stdout.read_line(&mut line).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(json["set"], "Effective");
+    assert_eq!(json["removed"][0], "CAP_CHOWN");
+    assert!(json["timestamp"].as_f64().unwrap() > 0.0);
+
+    // Nothing else is reported until the child exits.
+    unsafe {
+        libc::close(go[1]);
+        libc::close(done[1]);
+        libc::waitpid(pid, std::ptr::null_mut(), 0);
+    }
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "");
+    assert!(watcher.wait().unwrap().success());
+}
+
+#[test]
+fn test_predict() {
+    let out = caps(&["predict", "true"]);
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.contains("After exec:\n"));
+
+    let out = caps(&["predict", "--json", "true"]);
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(json["state"]["effective"].is_array());
+    assert!(json["missing"].as_array().unwrap().is_empty());
+
+    assert!(!caps(&["predict", "/nonexistent"]).status.success());
+    assert!(!caps(&["predict"]).status.success());
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::command::CommandCapsExt;
+use caps::{CapSet, CapState, Capability};
+use std::process::Command;
+
+fn child_state(cmd: &mut Command) -> CapState {
+    let out = cmd.arg("/proc/self/status").output().unwrap();
+    assert!(out.status.success());
+    CapState::from_proc_status(&String::from_utf8(out.stdout).unwrap()).unwrap()
+}
+
+#[test]
+fn test_command_ambient_caps() {
+    let cap = Capability::CAP_NET_RAW;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    let state = child_state(Command::new("cat").ambient_caps(&caps::caps![CAP_NET_RAW]));
+    assert!(state.ambient.contains(&cap));
+    assert!(state.inheritable.contains(&cap));
+}
+
+#[test]
+fn test_command_drop_bounding() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    let state = child_state(Command::new("cat").drop_bounding(&caps::caps![CAP_SYS_BOOT]));
+    assert!(!state.bounding.contains(&cap));
+    assert!(caps::has_cap(None, CapSet::Bounding, cap).unwrap());
+}
+
+#[test]
+fn test_command_clear_inheritable() {
+    let cap = Capability::CAP_NET_RAW;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    let state = child_state(
+        Command::new("cat")
+            .ambient_caps(&caps::caps![CAP_NET_RAW])
+            .clear_inheritable(),
+    );
+    assert!(state.ambient.is_empty());
+    assert!(state.inheritable.is_empty());
+}
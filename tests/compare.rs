@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate caps;
+use caps::compare::Report;
+use caps::securebits::SecureBits;
+use caps::{CapSet, CapState, Capability};
+
+#[test]
+fn test_report_between() {
+    let a = CapState {
+        permitted: caps![CAP_CHOWN, CAP_KILL],
+        effective: caps![CAP_CHOWN],
+        ..Default::default()
+    };
+    assert!(Report::between(&a, &a).is_empty());
+    assert_eq!(Report::between(&a, &a).to_string(), "No differences");
+    let b = CapState {
+        permitted: caps![CAP_KILL, CAP_NET_RAW],
+        ..a.clone()
+    };
+    let report = Report::between(&a, &b);
+    assert_eq!(report.sets.len(), 1);
+    let prm = report.set(CapSet::Permitted).unwrap();
+    assert_eq!(prm.added, vec![Capability::CAP_NET_RAW]);
+    assert_eq!(prm.removed, vec![Capability::CAP_CHOWN]);
+    assert!(report.set(CapSet::Effective).is_none());
+    assert_eq!(
+        report.to_string(),
+        "Permitted:\n  - cap_chown\n  + cap_net_raw"
+    );
+}
+
+#[test]
+fn test_report_attributes() {
+    let state = CapState::default();
+    let report = Report::between(&state, &state)
+        .securebits(SecureBits::default(), SecureBits::NOROOT)
+        .no_new_privs(false, false);
+    assert!(!report.is_empty());
+    assert!(report.no_new_privs.is_none());
+    assert_eq!(report.to_string(), "Securebits: 0x0 -> 0x1");
+}
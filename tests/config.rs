@@ -0,0 +1,52 @@
+#![cfg(feature = "config")]
+
+extern crate caps;
+extern crate toml;
+use caps::config::{self, Config};
+use caps::{CapSet, CapState, Capability};
+use std::fs;
+use std::thread;
+
+#[test]
+fn test_config_parse() {
+    let toml = "keep = [\"net_bind_service\", \"CAP_KILL\"]\nno_new_privs = true\n";
+    let config = Config::from_toml(toml).unwrap();
+    assert_eq!(
+        config.keep,
+        Some(caps::caps![CAP_NET_BIND_SERVICE, CAP_KILL])
+    );
+    assert!(config.no_new_privs);
+    assert!(!config.lock_securebits);
+
+    let yaml = "keep: [net_bind_service, kill]\nno_new_privs: true\n";
+    assert_eq!(Config::from_yaml(yaml).unwrap(), config);
+
+    assert!(Config::from_toml("keep = [\"not_a_cap\"]").is_err());
+    assert!(Config::from_toml("unknown = true").is_err());
+}
+
+#[test]
+fn test_state_serde() {
+    let state = CapState {
+        permitted: caps::caps![CAP_CHOWN],
+        ..CapState::default()
+    };
+    let s = toml::to_string(&state).unwrap();
+    assert!(s.contains("CAP_CHOWN"));
+    assert_eq!(toml::from_str::<CapState>(&s).unwrap(), state);
+}
+
+#[test]
+fn test_apply_from_config() {
+    let path = std::env::temp_dir().join(format!("caps-test-{}.yaml", std::process::id()));
+    fs::write(&path, "deny: [sys_boot]\nno_new_privs: true\n").unwrap();
+    let p = path.clone();
+    thread::spawn(move || {
+        config::apply_from_config(&p).unwrap();
+        assert!(!caps::has_cap(None, CapSet::Permitted, Capability::CAP_SYS_BOOT).unwrap());
+        assert!(caps::securebits::has_no_new_privs().unwrap());
+    })
+    .join()
+    .unwrap();
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::diagnostics;
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_explain() {
+    let cap = Capability::CAP_NET_ADMIN;
+    let e = diagnostics::explain(cap).unwrap();
+    let eff = caps::has_cap(None, CapSet::Effective, cap).unwrap();
+    assert_eq!(e.capability, cap);
+    assert_eq!(e.in_set(CapSet::Effective), eff);
+    if e.in_set(CapSet::Ambient) {
+        assert!(e.survives_exec);
+    }
+    assert!(e.to_string().starts_with("CAP_NET_ADMIN:"));
+}
+
+#[test]
+fn test_explain_ambient() {
+    let cap = Capability::CAP_NET_ADMIN;
+    caps::drop(None, CapSet::Ambient, cap).unwrap();
+    let e = diagnostics::explain(cap).unwrap();
+    assert!(!e.in_set(CapSet::Ambient));
+    if e.uid != 0 && e.euid != 0 {
+        assert!(!e.survives_exec);
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let snapshot = diagnostics::Snapshot::current().unwrap();
+    assert_eq!(snapshot.tid, caps::this_thread::gettid());
+    let display = format!("{}", snapshot);
+    assert!(display.contains(&format!("CapBnd: {:016x}", snapshot.masks.bounding)));
+}
+
+#[test]
+fn test_am_i_privileged() {
+    let permitted = caps::read(None, CapSet::Permitted).unwrap();
+    if permitted.contains(&Capability::CAP_SYS_ADMIN) {
+        assert!(diagnostics::am_i_privileged().unwrap());
+    }
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    thread::spawn(|| {
+        // Without capabilities nor any to regain on exec, even root is
+        // unprivileged.
+        caps::clear(None, CapSet::Bounding).unwrap();
+        caps::clear(None, CapSet::Inheritable).unwrap();
+        caps::clear(None, CapSet::Permitted).unwrap();
+        assert!(!diagnostics::am_i_privileged().unwrap());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate caps;
+use caps::docker;
+use caps::sets;
+use caps::Capability;
+
+#[test]
+fn test_docker_flags_relative() {
+    assert!(docker::flags(&sets::DOCKER_DEFAULT.to_set()).is_empty());
+    let mut desired = sets::DOCKER_DEFAULT.to_set();
+    desired.remove(&Capability::CAP_NET_RAW);
+    desired.insert(Capability::CAP_SYS_PTRACE);
+    assert_eq!(
+        docker::flags(&desired),
+        ["--cap-drop=NET_RAW", "--cap-add=SYS_PTRACE"]
+    );
+}
+
+#[test]
+fn test_docker_flags_from_none() {
+    assert_eq!(docker::flags(&caps![]), ["--cap-drop=ALL"]);
+    assert_eq!(
+        docker::flags(&caps![CAP_NET_RAW, CAP_CHOWN]),
+        ["--cap-drop=ALL", "--cap-add=CHOWN", "--cap-add=NET_RAW"]
+    );
+}
+
+#[test]
+fn test_docker_flags_no_add_all() {
+    // `--cap-add=ALL` would grant capabilities unknown to this crate.
+    let mut desired = caps::all();
+    desired.remove(&Capability::CAP_SYS_ADMIN);
+    let flags = docker::flags(&desired);
+    let added = desired.difference(&sets::DOCKER_DEFAULT.to_set()).count();
+    assert_eq!(flags.len(), added);
+    assert!(flags.iter().all(|f| f.starts_with("--cap-add=")));
+    assert!(!flags.contains(&"--cap-add=ALL".to_string()));
+    assert!(!flags.contains(&"--cap-add=SYS_ADMIN".to_string()));
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "ebpf")]
+
+extern crate caps;
+use caps::ebpf;
+use caps::{CapSet, Capability};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+#[test]
+fn test_record() {
+    let path = std::env::temp_dir().join(format!("caps-ebpf-{}", std::process::id()));
+    fs::write(&path, "").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+    // Reading a file without permissions goes through a DAC override check.
+    let res = ebpf::record(Command::new("cat").arg(&path));
+    fs::remove_file(&path).unwrap();
+    let discovery = match res {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("skipping test_record: {}", e);
+            return;
+        }
+    };
+    let dac = [
+        Capability::CAP_DAC_OVERRIDE,
+        Capability::CAP_DAC_READ_SEARCH,
+    ];
+    let own: Vec<_> = discovery
+        .records
+        .iter()
+        .filter(|u| u.pid as u32 == discovery.pid && dac.contains(&u.capability))
+        .collect();
+    assert!(!own.is_empty());
+    // The workload inherited our capabilities, hence the same results.
+    for u in &own {
+        assert!(discovery.used.contains(&u.capability));
+        let held = caps::has_cap(None, CapSet::Effective, u.capability).unwrap();
+        assert_eq!(u.denied, !held);
+    }
+    assert_eq!(discovery.status.success(), own.iter().any(|u| !u.denied));
+}
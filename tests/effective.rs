@@ -21,7 +21,7 @@ fn test_effective_clear() {
 fn test_effective_drop() {
     caps::drop(None, caps::CapSet::Effective, caps::Capability::CAP_CHOWN).unwrap();
     let no_eff = caps::has_cap(None, caps::CapSet::Effective, caps::Capability::CAP_CHOWN).unwrap();
-    assert_eq!(no_eff, false);
+    assert!(!no_eff);
 }
 
 #[test]
@@ -46,3 +46,116 @@ fn test_effective_set() {
     caps::drop(None, caps::CapSet::Ambient, caps::Capability::CAP_CHOWN).unwrap();
     assert!(caps::set(None, caps::CapSet::Ambient, v).is_err());
 }
+
+#[test]
+fn test_effective_lower_resume() {
+    let before = caps::read(None, caps::CapSet::Effective).unwrap();
+    caps::effective::lower_all().unwrap();
+    assert!(caps::effective::is_lowered());
+    assert!(caps::read(None, caps::CapSet::Effective)
+        .unwrap()
+        .is_empty());
+    caps::effective::lower_all().unwrap();
+    caps::effective::resume().unwrap();
+    assert!(!caps::effective::is_lowered());
+    assert_eq!(caps::read(None, caps::CapSet::Effective).unwrap(), before);
+}
+
+#[test]
+fn test_effective_resume_permitted() {
+    let perm = caps::read(None, caps::CapSet::Permitted).unwrap();
+    caps::clear(None, caps::CapSet::Effective).unwrap();
+    caps::effective::resume().unwrap();
+    assert_eq!(caps::read(None, caps::CapSet::Effective).unwrap(), perm);
+}
+
+#[test]
+fn test_effective_lowered_guard() {
+    let before = caps::read(None, caps::CapSet::Effective).unwrap();
+    {
+        let _guard = caps::effective::lowered().unwrap();
+        assert!(caps::read(None, caps::CapSet::Effective)
+            .unwrap()
+            .is_empty());
+    }
+    assert_eq!(caps::read(None, caps::CapSet::Effective).unwrap(), before);
+}
+
+#[test]
+fn test_effective_read_target() {
+    let cur = caps::read(caps::Target::CurrentThread, caps::CapSet::Effective).unwrap();
+    assert_eq!(cur, caps::read(None, caps::CapSet::Effective).unwrap());
+    caps::read(caps::Target::Process(1), caps::CapSet::Effective).unwrap();
+    let r = caps::read(caps::Target::Thread(-1), caps::CapSet::Effective);
+    match r.unwrap_err().kind() {
+        caps::errors::ErrorKind::InvalidTarget(-1) => {}
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_effective_set_strict() {
+    let perm = caps::read(None, caps::CapSet::Permitted).unwrap();
+    caps::set_strict(None, caps::CapSet::Effective, perm.clone()).unwrap();
+    assert_eq!(caps::read(None, caps::CapSet::Effective).unwrap(), perm);
+}
+
+#[test]
+fn test_apply_strict() {
+    std::thread::spawn(|| {
+        let mut state = caps::CapState::current().unwrap();
+        state.effective.clear();
+        state.apply_strict().unwrap();
+        assert_eq!(caps::CapState::current().unwrap(), state);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_verify_state() {
+    let state = caps::CapState::current().unwrap();
+    caps::verify_state(&state).unwrap();
+
+    let mut expected = state.clone();
+    expected.effective.insert(caps::Capability::CAP_SYS_BOOT);
+    expected.ambient.insert(caps::Capability::CAP_CHOWN);
+    let err = caps::verify_state(&expected).unwrap_err();
+    match *err.kind() {
+        caps::errors::ErrorKind::StateMismatch(ref m) => assert!(!m.sets.is_empty()),
+        ref e => panic!("unexpected error {:?}", e),
+    }
+    let mut actual = state;
+    actual.effective.remove(&caps::Capability::CAP_SYS_BOOT);
+    actual.ambient.clear();
+    let mismatch = actual.diff(&expected).unwrap();
+    assert_eq!(mismatch.sets.len(), 2);
+    assert_eq!(mismatch.sets[0].set, caps::CapSet::Ambient);
+    assert_eq!(mismatch.sets[0].missing, vec![caps::Capability::CAP_CHOWN]);
+    assert_eq!(mismatch.sets[1].set, caps::CapSet::Effective);
+    assert_eq!(
+        mismatch.sets[1].missing,
+        vec![caps::Capability::CAP_SYS_BOOT]
+    );
+    assert!(mismatch.sets[1].unexpected.is_empty());
+}
+
+#[test]
+fn test_effective_ensure() {
+    std::thread::spawn(|| {
+        let perm = caps::read(None, caps::CapSet::Permitted).unwrap();
+        caps::set(None, caps::CapSet::Effective, perm.clone()).unwrap();
+        assert!(!caps::ensure(None, caps::CapSet::Effective, &perm).unwrap());
+        let empty = caps::CapsHashSet::new();
+        assert_eq!(
+            caps::ensure(None, caps::CapSet::Effective, &empty).unwrap(),
+            !perm.is_empty()
+        );
+        assert!(caps::read(None, caps::CapSet::Effective)
+            .unwrap()
+            .is_empty());
+        assert!(!caps::ensure(None, caps::CapSet::Effective, &empty).unwrap());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "libc")]
+
+#[macro_use]
+extern crate caps;
+use caps::errors::ErrorKind;
+use caps::exec::{self, ExecParams};
+use caps::file::FileCaps;
+use caps::securebits::SecureBits;
+use caps::CapState;
+
+fn user_state() -> CapState {
+    CapState {
+        ambient: caps![CAP_NET_BIND_SERVICE],
+        bounding: caps::all(),
+        effective: caps![CAP_NET_BIND_SERVICE, CAP_KILL],
+        inheritable: caps![CAP_NET_BIND_SERVICE],
+        permitted: caps![CAP_NET_BIND_SERVICE, CAP_KILL],
+    }
+}
+
+fn user_params() -> ExecParams {
+    ExecParams {
+        uid: 1000,
+        euid: 1000,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_exec_plain() {
+    let s = exec::transform(&user_state(), None, &user_params()).unwrap();
+    assert_eq!(s.permitted, caps![CAP_NET_BIND_SERVICE]);
+    assert_eq!(s.effective, caps![CAP_NET_BIND_SERVICE]);
+    assert_eq!(s.ambient, caps![CAP_NET_BIND_SERVICE]);
+    assert_eq!(s.bounding, caps::all());
+}
+
+#[test]
+fn test_exec_root() {
+    let mut params = user_params();
+    params.uid = 0;
+    params.euid = 0;
+    let s = exec::transform(&user_state(), None, &params).unwrap();
+    assert_eq!(s.permitted, caps::all());
+    assert_eq!(s.effective, caps::all());
+
+    params.securebits = SecureBits::NOROOT;
+    let s = exec::transform(&user_state(), None, &params).unwrap();
+    assert_eq!(s.permitted, caps![CAP_NET_BIND_SERVICE]);
+}
+
+#[test]
+fn test_exec_file_caps() {
+    let fcaps = FileCaps {
+        effective: true,
+        permitted: caps![CAP_NET_RAW],
+        ..Default::default()
+    };
+    let s = exec::transform(&user_state(), Some(&fcaps), &user_params()).unwrap();
+    assert_eq!(s.permitted, caps![CAP_NET_RAW]);
+    assert_eq!(s.effective, caps![CAP_NET_RAW]);
+    assert!(s.ambient.is_empty());
+}
+
+#[test]
+fn test_exec_denied() {
+    let fcaps = FileCaps {
+        effective: true,
+        permitted: caps![CAP_NET_RAW],
+        ..Default::default()
+    };
+    let mut state = user_state();
+    state.bounding.remove(&caps::Capability::CAP_NET_RAW);
+    let r = exec::transform(&state, Some(&fcaps), &user_params());
+    match r.unwrap_err().kind() {
+        ErrorKind::ExecDenied(missing) => assert_eq!(missing, &vec![caps::Capability::CAP_NET_RAW]),
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_exec_no_new_privs() {
+    let fcaps = FileCaps {
+        effective: true,
+        permitted: caps![CAP_NET_RAW, CAP_KILL],
+        ..Default::default()
+    };
+    let mut params = user_params();
+    params.no_new_privs = true;
+    let s = exec::transform(&user_state(), Some(&fcaps), &params).unwrap();
+    assert_eq!(s.permitted, caps![CAP_KILL]);
+}
+
+#[test]
+fn test_exec_setuid_clears_ambient() {
+    let mut params = user_params();
+    params.setuid_owner = Some(1001);
+    let s = exec::transform(&user_state(), None, &params).unwrap();
+    assert!(s.ambient.is_empty());
+    assert!(s.permitted.is_empty());
+}
+
+#[test]
+fn test_predict_exec() {
+    let exe = std::env::current_exe().unwrap();
+    let p = exec::predict_exec(&exe).unwrap();
+    assert!(p.succeeds());
+    assert!(p.file_caps.is_none());
+    assert!(p.missing.is_empty());
+    assert!(exec::predict_exec("/nonexistent").is_err());
+}
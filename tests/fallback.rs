@@ -0,0 +1,94 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+
+use caps::{CapSet, Capability};
+use std::thread;
+
+// Make syscall `nr` fail with `errno` in the current thread.
+fn deny_syscall(nr: libc::c_long, errno: i32) {
+    // BPF opcodes, from <linux/bpf_common.h>.
+    let load = 0x20; // BPF_LD | BPF_W | BPF_ABS
+    let jeq = 0x15; // BPF_JMP | BPF_JEQ | BPF_K
+    let ret = 0x06; // BPF_RET | BPF_K
+    let mut filter = [
+        libc::sock_filter {
+            code: load,
+            jt: 0,
+            jf: 0,
+            k: 0,
+        },
+        libc::sock_filter {
+            code: jeq,
+            jt: 0,
+            jf: 1,
+            k: nr as u32,
+        },
+        libc::sock_filter {
+            code: ret,
+            jt: 0,
+            jf: 0,
+            k: libc::SECCOMP_RET_ERRNO | (errno as u32 & libc::SECCOMP_RET_DATA),
+        },
+        libc::sock_filter {
+            code: ret,
+            jt: 0,
+            jf: 0,
+            k: libc::SECCOMP_RET_ALLOW,
+        },
+    ];
+    let prog = libc::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_mut_ptr(),
+    };
+    unsafe {
+        assert_eq!(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0), 0);
+        let mode = libc::SECCOMP_MODE_FILTER as libc::c_ulong;
+        assert_eq!(
+            libc::prctl(libc::PR_SET_SECCOMP, mode, &prog as *const _, 0, 0),
+            0
+        );
+    }
+}
+
+#[test]
+fn test_read_denied_capget() {
+    thread::spawn(|| {
+        let effective = caps::read(None, CapSet::Effective).unwrap();
+        let permitted = caps::read(None, CapSet::Permitted).unwrap();
+        deny_syscall(libc::SYS_capget, libc::EPERM);
+        let res = unsafe { libc::syscall(libc::SYS_capget, 0, 0) };
+        assert_eq!(res, -1);
+        assert_eq!(
+            std::io::Error::last_os_error().raw_os_error(),
+            Some(libc::EPERM)
+        );
+        assert_eq!(caps::read(None, CapSet::Effective).unwrap(), effective);
+        assert_eq!(caps::read(None, CapSet::Permitted).unwrap(), permitted);
+        let chown = Capability::CAP_CHOWN;
+        assert_eq!(
+            caps::has_cap(None, CapSet::Effective, chown).unwrap(),
+            effective.contains(&chown)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_capset_policy_denied() {
+    thread::spawn(|| {
+        let effective = caps::read(None, CapSet::Effective).unwrap();
+        // Security modules deny with EACCES.
+        deny_syscall(libc::SYS_capset, libc::EACCES);
+        let err = caps::set(None, CapSet::Effective, effective).unwrap_err();
+        match *err.kind() {
+            caps::errors::ErrorKind::PolicyDenied(ref op) => assert_eq!(op, "capset"),
+            ref k => panic!("unexpected error: {:?}", k),
+        }
+        assert_eq!(err.errno().map(|e| e.0), Some(libc::EACCES));
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "libc")]
+
+#[macro_use]
+extern crate caps;
+use caps::file::{self, FileCaps};
+use caps::userns::UidMap;
+
+#[test]
+fn test_file_xattr_roundtrip() {
+    let fcaps = FileCaps {
+        effective: true,
+        permitted: caps![CAP_NET_RAW, CAP_SYSLOG],
+        inheritable: caps![CAP_KILL],
+        rootid: None,
+    };
+    let data = fcaps.to_xattr();
+    assert_eq!(data.len(), 20);
+    assert_eq!(FileCaps::from_xattr(&data).unwrap(), fcaps);
+
+    let fcaps = FileCaps {
+        rootid: Some(100_000),
+        ..fcaps
+    };
+    let data = fcaps.to_xattr();
+    assert_eq!(data.len(), 24);
+    assert_eq!(FileCaps::from_xattr(&data).unwrap(), fcaps);
+}
+
+#[test]
+fn test_file_xattr_v1() {
+    let data = [
+        0x01, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let fcaps = FileCaps::from_xattr(&data).unwrap();
+    assert!(fcaps.effective);
+    assert_eq!(fcaps.permitted, caps![CAP_NET_RAW]);
+    assert!(fcaps.inheritable.is_empty());
+}
+
+#[test]
+fn test_file_xattr_invalid() {
+    assert!(FileCaps::from_xattr(&[0x00, 0x00]).is_err());
+    assert!(FileCaps::from_xattr(&[0x00, 0x00, 0x00, 0x02, 0x00]).is_err());
+}
+
+#[test]
+fn test_file_read() {
+    let exe = std::env::current_exe().unwrap();
+    assert!(file::read(&exe).unwrap().is_none());
+    assert!(file::read("/nonexistent").is_err());
+}
+
+#[test]
+fn test_file_namespace_root() {
+    let map = UidMap::parse("0 100000 65536\n").unwrap();
+    let mut fcaps = FileCaps::default();
+    assert_eq!(fcaps.namespace_rootid(&map), None);
+    fcaps.set_namespace_root(&map).unwrap();
+    assert_eq!(fcaps.rootid, Some(100_000));
+    assert_eq!(fcaps.namespace_rootid(&map), Some(0));
+    assert_eq!(FileCaps::from_xattr(&fcaps.to_xattr()).unwrap(), fcaps);
+
+    let unmapped = UidMap::parse("1000 2000 1").unwrap();
+    assert!(fcaps.set_namespace_root(&unmapped).is_err());
+    assert_eq!(fcaps.namespace_rootid(&unmapped), None);
+}
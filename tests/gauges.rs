@@ -0,0 +1,71 @@
+#![cfg(feature = "metrics")]
+
+extern crate caps;
+extern crate metrics;
+use caps::gauges::{self, Gauges};
+use caps::{CapSet, CapState};
+use metrics::{
+    Counter, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Recorder keeping the last value of each gauge, by key.
+#[derive(Default)]
+struct Values(Arc<Mutex<HashMap<String, f64>>>);
+
+struct Value(String, Arc<Mutex<HashMap<String, f64>>>);
+
+impl GaugeFn for Value {
+    fn increment(&self, _: f64) {}
+    fn decrement(&self, _: f64) {}
+    fn set(&self, value: f64) {
+        self.1.lock().unwrap().insert(self.0.clone(), value);
+    }
+}
+
+impl Recorder for Values {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn register_counter(&self, _: &Key, _: &Metadata) -> Counter {
+        Counter::noop()
+    }
+    fn register_gauge(&self, key: &Key, _: &Metadata) -> Gauge {
+        let mut name = key.name().to_string();
+        for label in key.labels() {
+            name += &format!("{{{}={}}}", label.key(), label.value());
+        }
+        Gauge::from_arc(Arc::new(Value(name, self.0.clone())))
+    }
+    fn register_histogram(&self, _: &Key, _: &Metadata) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+fn test_from_state() {
+    let state = CapState {
+        permitted: caps::caps![CAP_NET_BIND_SERVICE, CAP_SYS_ADMIN],
+        effective: caps::caps![CAP_NET_BIND_SERVICE],
+        ..Default::default()
+    };
+    let g = Gauges::from_state(&state, true);
+    assert_eq!(g.permitted, 2);
+    assert_eq!(g.effective, 1);
+    assert_eq!(g.ambient, 0);
+    assert_eq!(g.root_equivalent, 1);
+    assert!(g.no_new_privs);
+}
+
+#[test]
+fn test_record() {
+    let recorder = Values::default();
+    let values = recorder.0.clone();
+    metrics::with_local_recorder(&recorder, || gauges::record().unwrap());
+    let values = values.lock().unwrap();
+    let effective = caps::read(None, CapSet::Effective).unwrap().len();
+    assert_eq!(values["caps_capabilities{set=effective}"], effective as f64);
+    assert!(values.contains_key("caps_root_equivalent"));
+    assert!(values.contains_key("caps_no_new_privs"));
+}
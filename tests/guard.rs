@@ -0,0 +1,135 @@
+extern crate caps;
+use caps::guard::CapGuard;
+use caps::{CapSet, Capability};
+
+#[test]
+fn test_guard_raise() {
+    let cap = Capability::CAP_SYS_NICE;
+    let perm = caps::has_cap(None, CapSet::Permitted, cap).unwrap();
+    caps::drop(None, CapSet::Effective, cap).unwrap();
+    match CapGuard::raise(cap) {
+        Ok(guard) => {
+            assert!(perm);
+            assert_eq!(guard.capability(), cap);
+            assert!(caps::has_cap(None, CapSet::Effective, cap).unwrap());
+        }
+        Err(_) => assert!(!perm),
+    }
+    assert!(!caps::has_cap(None, CapSet::Effective, cap).unwrap());
+}
+
+#[test]
+fn test_guard_already_effective() {
+    let cap = Capability::CAP_SYS_NICE;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    caps::raise(None, CapSet::Effective, cap).unwrap();
+    {
+        let _guard = CapGuard::raise(cap).unwrap();
+    }
+    assert!(caps::has_cap(None, CapSet::Effective, cap).unwrap());
+}
+
+#[test]
+fn test_with_cap() {
+    let cap = Capability::CAP_SYS_NICE;
+    let perm = caps::has_cap(None, CapSet::Permitted, cap).unwrap();
+    caps::drop(None, CapSet::Effective, cap).unwrap();
+    let r = caps::guard::with_cap(cap, || caps::has_cap(None, CapSet::Effective, cap).unwrap());
+    assert_eq!(r.is_ok(), perm);
+    if perm {
+        assert!(r.unwrap());
+    }
+    assert!(!caps::has_cap(None, CapSet::Effective, cap).unwrap());
+}
+
+#[test]
+fn test_with_cap_panic() {
+    let cap = Capability::CAP_SYS_NICE;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    caps::drop(None, CapSet::Effective, cap).unwrap();
+    let r = std::panic::catch_unwind(|| {
+        caps::guard::with_cap(cap, || panic!("boom")).unwrap();
+    });
+    assert!(r.is_err());
+    assert!(!caps::has_cap(None, CapSet::Effective, cap).unwrap());
+}
+
+#[test]
+fn test_scoped_drop() {
+    let cap = Capability::CAP_SYS_NICE;
+    let before = caps::CapState::current().unwrap();
+    let mut drop = caps::CapsHashSet::new();
+    drop.insert(cap);
+    {
+        let guard = caps::guard::ScopedDrop::new(&drop).unwrap();
+        assert_eq!(guard.snapshot(), &before);
+        assert!(!caps::has_cap(None, CapSet::Effective, cap).unwrap());
+        assert!(!caps::has_cap(None, CapSet::Ambient, cap).unwrap());
+    }
+    assert_eq!(caps::CapState::current().unwrap(), before);
+}
+
+#[test]
+fn test_scoped_state_permitted() {
+    let mut state = caps::CapState::current().unwrap();
+    state.permitted.clear();
+    assert!(caps::guard::ScopedState::apply(&state).is_err());
+}
+
+#[test]
+fn test_use_then_drop() {
+    let cap = Capability::CAP_SYS_NICE;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    let r = caps::guard::use_then_drop(cap, true, || {
+        caps::has_cap(None, CapSet::Effective, cap).unwrap()
+    });
+    assert!(r.unwrap());
+    for cset in &[CapSet::Effective, CapSet::Permitted, CapSet::Bounding] {
+        assert!(!caps::has_cap(None, *cset, cap).unwrap());
+    }
+    assert!(caps::raise(None, CapSet::Effective, cap).is_err());
+}
+
+#[test]
+fn test_use_then_drop_setpcap() {
+    std::thread::spawn(|| {
+        let cap = Capability::CAP_SETPCAP;
+        if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+            return;
+        }
+        // Lowering the Bounding set needs `cap` itself, raised by the guard.
+        caps::drop(None, CapSet::Effective, cap).unwrap();
+        caps::guard::use_then_drop(cap, true, || ()).unwrap();
+        for cset in &[CapSet::Effective, CapSet::Permitted, CapSet::Bounding] {
+            assert!(!caps::has_cap(None, *cset, cap).unwrap());
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_use_then_drop_bounding_failure() {
+    std::thread::spawn(|| {
+        let cap = Capability::CAP_SYS_RESOURCE;
+        if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+            return;
+        }
+        // Without CAP_SETPCAP, the Bounding set cannot be lowered.
+        caps::drop(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap();
+        let err = caps::guard::use_then_drop(cap, true, || ()).unwrap_err();
+        assert!(err.to_string().contains("Bounding"), "{}", err);
+        for cset in &[CapSet::Effective, CapSet::Permitted] {
+            assert!(!caps::has_cap(None, *cset, cap).unwrap());
+        }
+        assert!(caps::has_cap(None, CapSet::Bounding, cap).unwrap());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::harden::{self, HardenOptions};
+use caps::lowlevel::Masks;
+use caps::securebits::{self, SecureBits};
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_harden() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    // Everything is per-thread, thus in a dedicated one.
+    thread::spawn(|| {
+        let keep = caps::caps![CAP_NET_BIND_SERVICE];
+        harden::harden(HardenOptions::new(&keep)).unwrap();
+        let masks = Masks::current().unwrap();
+        let bit = Capability::CAP_NET_BIND_SERVICE.bitmask();
+        assert_eq!(masks.bounding, bit);
+        assert_eq!(masks.permitted, bit);
+        assert_eq!(masks.effective, bit);
+        assert_eq!(masks.ambient, 0);
+        assert_eq!(securebits::get_securebits().unwrap(), SecureBits::LOCKDOWN);
+        assert!(securebits::has_no_new_privs().unwrap());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_harden_not_permitted() {
+    thread::spawn(|| {
+        caps::drop(None, CapSet::Effective, Capability::CAP_SYS_BOOT).unwrap();
+        caps::drop(None, CapSet::Permitted, Capability::CAP_SYS_BOOT).unwrap();
+        let opts = HardenOptions::new(&caps::caps![CAP_SYS_BOOT]);
+        assert!(harden::harden(opts).is_err());
+        assert!(!securebits::has_no_new_privs().unwrap());
+    })
+    .join()
+    .unwrap();
+}
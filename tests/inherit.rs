@@ -0,0 +1,49 @@
+// Expectations are passed through the process environment, thus they are
+// tested in their own binary.
+extern crate caps;
+use caps::errors::ErrorKind;
+use caps::inherit::{self, EXPECT_VAR};
+use caps::{CapSet, CapState, Capability};
+use std::env;
+use std::process::Command;
+
+#[test]
+fn test_encode_decode() {
+    let state = CapState {
+        permitted: caps::caps![CAP_CHOWN, CAP_NET_RAW],
+        effective: caps::caps![CAP_NET_RAW],
+        ..Default::default()
+    };
+    let s = inherit::encode(&state);
+    assert_eq!(s, "inh=0,prm=2001,eff=2000,bnd=0,amb=0");
+    assert_eq!(inherit::decode(&s).unwrap(), state);
+    assert!(inherit::decode("inh=0,prm=0").is_err());
+    assert!(inherit::decode("inh=0,prm=0,eff=0,bnd=0,amb=xyz").is_err());
+    assert!(inherit::decode("foo=0").is_err());
+
+    let mut cmd = Command::new("true");
+    inherit::expect(&mut cmd, &state);
+    let envs: Vec<_> = cmd.get_envs().collect();
+    assert_eq!(envs[0].0, EXPECT_VAR);
+}
+
+#[test]
+fn test_verify_inherited() {
+    env::remove_var(EXPECT_VAR);
+    assert!(!inherit::verify_inherited().unwrap());
+
+    let mut state = CapState::current().unwrap();
+    env::set_var(EXPECT_VAR, inherit::encode(&state));
+    assert!(inherit::verify_inherited().unwrap());
+    assert!(env::var(EXPECT_VAR).is_err());
+
+    let cap = Capability::CAP_SYS_BOOT;
+    if !state.effective.remove(&cap) {
+        state.effective.insert(cap);
+    }
+    env::set_var(EXPECT_VAR, inherit::encode(&state));
+    match *inherit::verify_inherited().unwrap_err().kind() {
+        ErrorKind::StateMismatch(ref m) => assert_eq!(m.sets[0].set, CapSet::Effective),
+        ref e => panic!("unexpected error {:?}", e),
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "isolate")]
+
+#[macro_use]
+extern crate caps;
+use caps::isolate;
+use caps::{CapSet, CapState, Capability};
+
+#[test]
+fn test_run_in_child() {
+    let cur = CapState::current().unwrap();
+    let state = CapState {
+        effective: caps![],
+        ..cur.clone()
+    };
+    let res = isolate::run_in_child(&state, || CapState::current().unwrap()).unwrap();
+    assert_eq!(res, state);
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_run_in_child_irreversible() {
+    let cur = CapState::current().unwrap();
+    if !cur.bounding.contains(&Capability::CAP_KILL)
+        || !cur.effective.contains(&Capability::CAP_SETPCAP)
+    {
+        return;
+    }
+    let mut state = cur.clone();
+    state.bounding.remove(&Capability::CAP_KILL);
+    let res = isolate::run_in_child(&state, || {
+        caps::has_cap(None, CapSet::Bounding, Capability::CAP_KILL).unwrap()
+    });
+    assert!(!res.unwrap());
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_run_in_child_errors() {
+    let err = isolate::run_in_child(&CapState::current().unwrap(), || -> u8 { panic!("boom") })
+        .unwrap_err();
+    assert_eq!(err.to_string(), "child panicked: boom");
+
+    let mut state = CapState::current().unwrap();
+    state.effective = caps::all();
+    state.permitted = caps![];
+    let err = isolate::run_in_child(&state, || ()).unwrap_err();
+    assert!(err.errno().is_some());
+}
@@ -0,0 +1,28 @@
+extern crate caps;
+use caps::knowledge;
+
+#[test]
+fn test_knowledge_coverage() {
+    for c in caps::all() {
+        assert!(
+            !knowledge::operations(c).is_empty(),
+            "no operations for {}",
+            c
+        );
+    }
+}
+
+#[test]
+fn test_knowledge_operations() {
+    let ops = knowledge::operations(caps::Capability::CAP_NET_BIND_SERVICE);
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].syscalls, &["bind"]);
+}
+
+#[test]
+fn test_knowledge_by_syscall() {
+    let ops = knowledge::by_syscall("setuid");
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].capability, caps::Capability::CAP_SETUID);
+    assert!(knowledge::by_syscall("getpid").is_empty());
+}
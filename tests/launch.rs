@@ -0,0 +1,80 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::launch;
+use caps::{CapSet, Capability};
+
+fn wait(pid: libc::pid_t) -> i32 {
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    libc::WEXITSTATUS(status)
+}
+
+#[test]
+fn test_spawn_with_caps() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETUID).unwrap() {
+        return;
+    }
+    // CAP_NET_RAW is bit 13.
+    let script = "[ $(id -u) = 65534 ] && [ $(id -g) = 65534 ] \
+                  && grep -q '^CapAmb:.0000000000002000$' /proc/self/status \
+                  && grep -q '^CapEff:.0000000000002000$' /proc/self/status \
+                  && grep -q '^CapBnd:.0000000000002400$' /proc/self/status";
+    let pid = launch::spawn_with_caps(
+        "/bin/sh",
+        &["-c", script],
+        &caps::caps![CAP_NET_RAW],
+        &caps::caps![CAP_NET_RAW, CAP_NET_BIND_SERVICE],
+        None,
+        Some((65534, 65534)),
+    )
+    .unwrap();
+    assert_eq!(wait(pid), 0);
+}
+
+#[test]
+fn test_spawn_with_caps_error() {
+    let err = launch::spawn_with_caps(
+        "/nonexistent",
+        &[] as &[&str],
+        &caps::CapsHashSet::new(),
+        &caps::all(),
+        None,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(libc::ENOENT));
+}
+
+#[test]
+fn test_launcher() {
+    let dir = std::env::temp_dir();
+    let (gid, groups) = (0, [42]);
+    // `id -G` lists the gid first, then other supplementary groups.
+    let mut expected = vec![gid];
+    expected.extend(groups.iter().filter(|&&g| g != gid));
+    let expected: Vec<_> = expected.iter().map(|g| g.to_string()).collect();
+    let script = format!(
+        "[ \"$(pwd)\" = {:?} ] && [ \"$FOO\" = bar ] && [ -z \"$HOME\" ] \
+         && [ \"$(id -G)\" = {:?} ] && ! grep -q '^CapBnd:.0*$' /proc/self/status",
+        dir,
+        expected.join(" ")
+    );
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETGID).unwrap() {
+        return;
+    }
+    let pid = launch::Launcher::new("/bin/sh")
+        .arg("-c")
+        .arg(script)
+        .groups(&groups)
+        .group(gid)
+        .chdir(&dir)
+        .clear_env()
+        .env("FOO", "bar")
+        .env("PATH", "/usr/bin:/bin")
+        .launch()
+        .unwrap();
+    assert_eq!(wait(pid), 0);
+}
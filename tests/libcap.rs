@@ -0,0 +1,52 @@
+#![cfg(feature = "libcap")]
+
+#[macro_use]
+extern crate caps;
+use caps::libcap::CapT;
+use caps::{CapState, Capability};
+
+#[test]
+fn test_state_roundtrip() {
+    let state = CapState {
+        effective: caps![CAP_CHOWN],
+        permitted: caps![CAP_CHOWN, CAP_KILL],
+        inheritable: caps![CAP_NET_RAW],
+        ..Default::default()
+    };
+    let cap = CapT::from_state(&state).unwrap();
+    assert_eq!(cap.to_state().unwrap(), state);
+    assert_eq!(
+        CapT::new().unwrap().to_state().unwrap(),
+        CapState::default()
+    );
+}
+
+#[test]
+fn test_text() {
+    let cap = CapT::from_text("cap_kill+ip").unwrap();
+    let state = cap.to_state().unwrap();
+    assert!(state.effective.is_empty());
+    assert_eq!(state.inheritable, caps![CAP_KILL]);
+    assert_eq!(state.permitted, caps![CAP_KILL]);
+    let state = CapState {
+        effective: caps![CAP_NET_BIND_SERVICE],
+        permitted: caps![CAP_NET_BIND_SERVICE],
+        ..Default::default()
+    };
+    let text = CapT::from_state(&state).unwrap().to_text().unwrap();
+    assert_eq!(text, "cap_net_bind_service=ep");
+    assert!(CapT::from_text("cap_foo+p").is_err());
+}
+
+#[test]
+fn test_raw() {
+    let cap = CapT::from_text("cap_chown=p").unwrap();
+    let raw = cap.into_raw();
+    let cap = unsafe { CapT::from_raw(raw) };
+    assert_eq!(cap.as_raw(), raw);
+    assert!(cap
+        .to_state()
+        .unwrap()
+        .permitted
+        .contains(&Capability::CAP_CHOWN));
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "lockdown")]
+
+extern crate caps;
+extern crate libc;
+
+use caps::{CapSet, Capability};
+
+// The filter applies to the whole process, hence the single test.
+#[test]
+fn test_deny_future_capset() {
+    let effective = caps::read(None, CapSet::Effective).unwrap();
+    caps::lockdown::deny_future_capset().unwrap();
+    let err = caps::set(None, CapSet::Effective, effective.clone()).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(libc::EPERM));
+    let err = caps::raise(None, CapSet::Ambient, Capability::CAP_CHOWN).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(libc::EPERM));
+    let err = caps::securebits::set_keepcaps(true).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(libc::EPERM));
+    assert_eq!(caps::read(None, CapSet::Effective).unwrap(), effective);
+    assert!(caps::clear(None, CapSet::Ambient).is_ok());
+    // Threads spawned afterwards inherit the filter.
+    std::thread::spawn(move || {
+        assert!(caps::set(None, CapSet::Effective, effective).is_err());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,52 @@
+extern crate caps;
+use caps::lowlevel::{self, Masks};
+use caps::{this_thread, Capability};
+use std::thread;
+
+#[test]
+fn test_masks_current() {
+    // Capabilities unknown to this crate are only reported as raw bits.
+    let known = caps::all().iter().fold(0, |m, c| m | c.bitmask());
+    let masks = Masks::current().unwrap();
+    let state = Masks::from(&this_thread::state().unwrap());
+    assert_eq!(masks.effective & known, state.effective);
+    assert_eq!(masks.bounding & known, state.bounding);
+    let (effective, permitted, inheritable) = lowlevel::capget().unwrap();
+    assert_eq!(effective, masks.effective);
+    assert_eq!(permitted, masks.permitted);
+    assert_eq!(inheritable, masks.inheritable);
+}
+
+#[test]
+fn test_ambient_raise_lower() {
+    thread::spawn(|| {
+        let cap = Capability::CAP_NET_RAW;
+        let (e, p, i) = lowlevel::capget().unwrap();
+        if (p & cap.bitmask()) == 0 {
+            return;
+        }
+        lowlevel::capset(e, p, i | cap.bitmask()).unwrap();
+        lowlevel::ambient_raise(cap.index()).unwrap();
+        assert!(lowlevel::ambient_read(cap.index()));
+        lowlevel::ambient_lower(cap.index()).unwrap();
+        assert!(!lowlevel::ambient_read(cap.index()));
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_masks_apply() {
+    thread::spawn(|| {
+        let cap = Capability::CAP_SYS_BOOT;
+        let mut masks = Masks::current().unwrap();
+        if (masks.effective & cap.bitmask()) == 0 {
+            return;
+        }
+        masks.effective &= !cap.bitmask();
+        masks.apply().unwrap();
+        assert_eq!(Masks::current().unwrap(), masks);
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,27 @@
+#[macro_use]
+extern crate caps;
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_assert_macros() {
+    thread::spawn(|| {
+        caps::drop(None, CapSet::Effective, Capability::CAP_SYS_BOOT).unwrap();
+        assert_no_cap!(CAP_SYS_BOOT);
+        debug_assert_no_cap!(Effective, CAP_SYS_BOOT);
+        assert_no_cap!(Ambient, CAP_SYS_BOOT);
+        if caps::has_cap(None, CapSet::Permitted, Capability::CAP_SYS_BOOT).unwrap() {
+            assert_has_cap!(Permitted, CAP_SYS_BOOT);
+            caps::raise(None, CapSet::Effective, Capability::CAP_SYS_BOOT).unwrap();
+            debug_assert_has_cap!(CAP_SYS_BOOT);
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+#[should_panic(expected = "CAP_SYS_BOOT is not held in Ambient set")]
+fn test_assert_has_cap_panics() {
+    assert_has_cap!(Ambient, CAP_SYS_BOOT);
+}
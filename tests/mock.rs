@@ -0,0 +1,44 @@
+#![cfg(feature = "mock")]
+
+extern crate caps;
+
+use caps::backend::PrivilegeBackend;
+use caps::mock::{MockBackend, Op};
+
+// Code under test: serve with the single privilege it needs.
+fn serve(backend: &dyn PrivilegeBackend) -> caps::errors::Result<()> {
+    backend.restrict(&["CAP_NET_BIND_SERVICE"])?;
+    backend.raise("CAP_NET_BIND_SERVICE")?;
+    // bind()...
+    backend.lower("CAP_NET_BIND_SERVICE")
+}
+
+#[test]
+fn test_mock_records_ops() {
+    let backend = MockBackend::with_sets(&[], &["CAP_NET_BIND_SERVICE", "CAP_SYS_ADMIN"]);
+    serve(&backend).unwrap();
+    assert_eq!(
+        backend.ops(),
+        vec![
+            Op::Restrict(vec!["CAP_NET_BIND_SERVICE".to_string()]),
+            Op::Raise("CAP_NET_BIND_SERVICE".to_string()),
+            Op::Lower("CAP_NET_BIND_SERVICE".to_string()),
+        ]
+    );
+    assert_eq!(backend.permitted().unwrap(), vec!["CAP_NET_BIND_SERVICE"]);
+    assert!(backend.effective().unwrap().is_empty());
+    backend.clear_ops();
+    assert!(backend.ops().is_empty());
+}
+
+#[test]
+fn test_mock_denials() {
+    let backend = MockBackend::new(&["CAP_CHOWN"]);
+    assert!(backend.has("cap_chown").unwrap());
+    backend.drop("CAP_CHOWN").unwrap();
+    assert!(!backend.has("CAP_CHOWN").unwrap());
+    let err = backend.raise("CAP_CHOWN").unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(1));
+    assert!(serve(&backend).is_err());
+    assert_eq!(backend.ops().len(), 4);
+}
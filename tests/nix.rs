@@ -0,0 +1,23 @@
+#![cfg(feature = "nix")]
+
+extern crate caps;
+extern crate nix;
+
+use caps::CapSet;
+use nix::errno::Errno;
+
+#[test]
+fn test_nix_pid() {
+    let pid = nix::unistd::getpid();
+    caps::read(pid, CapSet::Effective).unwrap();
+    assert!(caps::read(pid, CapSet::Ambient).is_err());
+}
+
+#[test]
+fn test_nix_errno() {
+    let e: caps::errors::Error = Errno::EPERM.into();
+    assert_eq!(e.errno().map(|e| e.0), Some(Errno::EPERM as i32));
+    assert_eq!(Errno::from(e), Errno::EPERM);
+    let e: caps::errors::Error = "not a syscall".into();
+    assert_eq!(Errno::from(e), Errno::EINVAL);
+}
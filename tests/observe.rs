@@ -0,0 +1,75 @@
+#![cfg(feature = "libc")]
+
+// Observers are process-wide, thus they are tested in their own binary.
+extern crate caps;
+extern crate libc;
+use caps::{lowlevel, observe};
+use caps::{CapSet, CapState, Capability, CapsDiff, Target};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_observe() {
+    let diffs = Arc::new(Mutex::new(Vec::<CapsDiff>::new()));
+    let d = diffs.clone();
+    let id = observe::register(move |diff| d.lock().unwrap().push(diff.clone()));
+
+    thread::spawn(move || {
+        let cap = Capability::CAP_CHOWN;
+        if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+            return;
+        }
+        caps::drop(None, CapSet::Effective, cap).unwrap();
+        caps::raise(None, CapSet::Effective, cap).unwrap();
+        // No-op changes are not reported.
+        caps::raise(None, CapSet::Effective, cap).unwrap();
+        let mut state = CapState::current().unwrap();
+        state.effective.remove(&cap);
+        state.apply().unwrap();
+
+        let diffs = diffs.lock().unwrap();
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].target, Target::CurrentThread);
+        assert_eq!(diffs[0].set, CapSet::Effective);
+        assert_eq!(diffs[0].removed, caps::caps![CAP_CHOWN]);
+        assert!(diffs[0].added.is_empty());
+        assert_eq!(diffs[1].added, caps::caps![CAP_CHOWN]);
+        assert_eq!(diffs[2].removed, caps::caps![CAP_CHOWN]);
+    })
+    .join()
+    .unwrap();
+
+    assert!(observe::unregister(id));
+    assert!(!observe::unregister(id));
+}
+
+#[test]
+fn test_watch() {
+    let cap = Capability::CAP_CHOWN;
+    if !caps::has_cap(None, CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        // Only async-signal-safe operations are allowed in the child.
+        unsafe { libc::usleep(100_000) };
+        let (e, p, i) = lowlevel::capget().unwrap();
+        lowlevel::capset(e & !cap.bitmask(), p, i).unwrap();
+        unsafe {
+            libc::usleep(1_000_000);
+            libc::_exit(0)
+        };
+    }
+    let mut watch = observe::watch(pid, Duration::from_millis(5)).unwrap();
+    let diff = watch.next().unwrap();
+    assert_eq!(diff.target, Target::Process(pid));
+    assert_eq!(diff.set, CapSet::Effective);
+    assert_eq!(diff.removed, caps::caps![CAP_CHOWN]);
+    unsafe {
+        libc::kill(pid, libc::SIGKILL);
+        libc::waitpid(pid, std::ptr::null_mut(), 0);
+    }
+    assert!(watch.next().is_none());
+}
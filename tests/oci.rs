@@ -0,0 +1,57 @@
+#[macro_use]
+extern crate caps;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+use caps::oci::OciCapabilities;
+use caps::CapState;
+
+fn state() -> CapState {
+    CapState {
+        bounding: caps![CAP_NET_RAW, CAP_CHOWN],
+        effective: caps![CAP_CHOWN],
+        permitted: caps![CAP_CHOWN],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_oci_roundtrip() {
+    let oci = OciCapabilities::from_state(&state());
+    assert_eq!(
+        oci.bounding,
+        vec![caps::Capability::CAP_CHOWN, caps::Capability::CAP_NET_RAW]
+    );
+    assert_eq!(oci.to_state(), state());
+}
+
+#[test]
+fn test_oci_to_json() {
+    let json = OciCapabilities::from_state(&state()).to_json();
+    assert_eq!(
+        json,
+        r#"{
+  "bounding": [
+    "CAP_CHOWN",
+    "CAP_NET_RAW"
+  ],
+  "effective": [
+    "CAP_CHOWN"
+  ],
+  "inheritable": [],
+  "permitted": [
+    "CAP_CHOWN"
+  ],
+  "ambient": []
+}"#
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_oci_serde() {
+    let oci = OciCapabilities::from_state(&state());
+    let parsed: OciCapabilities = serde_json::from_str(&oci.to_json()).unwrap();
+    assert_eq!(parsed, oci);
+    let partial: OciCapabilities = serde_json::from_str(r#"{"bounding": ["CAP_KILL"]}"#).unwrap();
+    assert_eq!(partial.to_state().bounding, caps![CAP_KILL]);
+}
@@ -0,0 +1,31 @@
+// The panic hook is process-wide, thus tested in its own binary, which
+// re-executes itself to panic in a child process.
+#![cfg(feature = "diagnostics")]
+
+extern crate caps;
+use std::env;
+use std::process::Command;
+
+const CHILD_VAR: &str = "CAPS_TEST_PANIC_HOOK_CHILD";
+
+#[test]
+fn test_panic_hook() {
+    if env::var_os(CHILD_VAR).is_some() {
+        caps::diagnostics::install_panic_hook();
+        panic!("expected panic");
+    }
+    let output = Command::new(env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "test_panic_hook",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(CHILD_VAR, "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("expected panic"), "{}", stderr);
+    assert!(stderr.contains("capability state at panic"), "{}", stderr);
+}
@@ -0,0 +1,45 @@
+extern crate caps;
+use caps::{pidfd, CapSet, Target};
+use std::os::unix::io::AsRawFd;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_pidfd_self() {
+    let pid = std::process::id() as i32;
+    let fd = pidfd::open(pid).unwrap();
+    assert_eq!(pidfd::pid(fd.as_raw_fd()).unwrap(), pid);
+    assert!(pidfd::is_alive(fd.as_raw_fd()).unwrap());
+    let by_fd = caps::read(&fd, CapSet::Permitted).unwrap();
+    let by_pid = caps::read(Target::Process(pid), CapSet::Permitted).unwrap();
+    assert_eq!(by_fd, by_pid);
+}
+
+#[test]
+fn test_pidfd_exited() {
+    let mut child = Command::new("true").spawn().unwrap();
+    let fd = pidfd::open(child.id() as i32).unwrap();
+    child.wait().unwrap();
+    assert!(!pidfd::is_alive(fd.as_raw_fd()).unwrap());
+    assert!(caps::read(&fd, CapSet::Permitted).is_err());
+}
+
+#[test]
+fn test_pidfd_zombie() {
+    let mut child = Command::new("true").spawn().unwrap();
+    let fd = pidfd::open(child.id() as i32).unwrap();
+    // Exited but not reaped yet.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pidfd::is_alive(fd.as_raw_fd()).unwrap() {
+        assert!(Instant::now() < deadline);
+        thread::sleep(Duration::from_millis(10));
+    }
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_pidfd_invalid() {
+    let f = std::fs::File::open("/dev/null").unwrap();
+    assert!(caps::read(Target::PidFd(f.as_raw_fd()), CapSet::Permitted).is_err());
+}
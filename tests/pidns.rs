@@ -0,0 +1,56 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::pidns::{self, NsInfo};
+use caps::CapState;
+use std::fs;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_own_namespace() {
+    let pid = unsafe { libc::getpid() };
+    let info = NsInfo::read(pid).unwrap();
+    assert_eq!(info.pid, pid);
+    assert_eq!(info.ns_pids[0], pid);
+    assert_eq!(info.ns, pidns::ns_inode(pid).unwrap());
+    assert_eq!(pidns::to_host(info.ns, info.local_pid()), Some(pid));
+    let (_, state) = pidns::read_state(info.ns, info.local_pid()).unwrap();
+    assert_eq!(state, CapState::from_proc(pid, pid).unwrap());
+}
+
+#[test]
+fn test_nested_namespace() {
+    let mut unshare = match Command::new("unshare")
+        .args(["--pid", "--fork", "sleep", "5"])
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    // Wait for the forked payload, child of `unshare`.
+    let mut payload = None;
+    for _ in 0..200 {
+        let children = format!("/proc/{0}/task/{0}/children", unshare.id());
+        payload = fs::read_to_string(children)
+            .ok()
+            .and_then(|c| c.split_whitespace().next().and_then(|p| p.parse().ok()));
+        if payload.is_some() || unshare.try_wait().unwrap().is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = unshare.kill();
+    let payload = match payload {
+        Some(p) => p,
+        None => return,
+    };
+    let info = NsInfo::read(payload).unwrap();
+    assert!(info.is_nested());
+    assert_eq!(info.local_pid(), 1);
+    assert_eq!(pidns::to_host(info.ns, 1), Some(payload));
+    unsafe { libc::kill(payload, libc::SIGKILL) };
+    let _ = unshare.wait();
+}
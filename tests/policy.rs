@@ -0,0 +1,91 @@
+extern crate caps;
+use caps::policy::{self, Policy, Violation};
+use caps::securebits::SecureBits;
+use caps::{CapSet, CapState, Capability};
+
+#[test]
+fn test_policy_check() {
+    let state = CapState {
+        effective: caps::caps![CAP_NET_RAW, CAP_KILL],
+        ..CapState::default()
+    };
+    let policy = Policy {
+        no_new_privs: true,
+        securebits: SecureBits::NOROOT | SecureBits::NOROOT_LOCKED,
+        ..Policy::default()
+    }
+    .max(CapSet::Effective, &caps::caps![CAP_KILL])
+    .max(CapSet::Ambient, &caps::CapsHashSet::new());
+    let violations = policy.check(&state, SecureBits::NOROOT, false);
+    assert_eq!(
+        violations,
+        vec![
+            Violation::Excess {
+                set: CapSet::Effective,
+                caps: caps::caps![CAP_NET_RAW],
+            },
+            Violation::NoNewPrivs,
+            Violation::SecureBits {
+                missing: SecureBits::NOROOT_LOCKED,
+            },
+        ]
+    );
+    assert_eq!(
+        violations[0].to_string(),
+        "Effective set holds disallowed CAP_NET_RAW"
+    );
+    assert!(policy
+        .check(&state, SecureBits::from_bits(0x3), true)
+        .iter()
+        .all(|v| matches!(*v, Violation::Excess { .. })));
+}
+
+#[test]
+fn test_verify() {
+    let policy = Policy::default().max(CapSet::Effective, &caps::all());
+    assert!(policy::verify(&policy).unwrap().is_empty());
+    if caps::has_cap(None, CapSet::Effective, Capability::CAP_KILL).unwrap() {
+        let policy = Policy::default().max(CapSet::Effective, &caps::CapsHashSet::new());
+        assert!(!policy::verify(&policy).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_policy_denied() {
+    let state = CapState {
+        permitted: caps::caps![CAP_NET_RAW, CAP_KILL, CAP_CHOWN],
+        ..CapState::default()
+    };
+    let policy = Policy::default()
+        .allow(&caps::caps![CAP_KILL, CAP_CHOWN])
+        .deny(&caps::caps![CAP_CHOWN]);
+    assert!(policy.allows(CapSet::Permitted, Capability::CAP_KILL));
+    assert!(!policy.allows(CapSet::Permitted, Capability::CAP_CHOWN));
+    assert_eq!(
+        policy.check(&state, SecureBits::default(), false),
+        vec![
+            Violation::Excess {
+                set: CapSet::Permitted,
+                caps: caps::caps![CAP_NET_RAW],
+            },
+            Violation::Denied {
+                set: CapSet::Permitted,
+                caps: caps::caps![CAP_CHOWN],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_policy_enforce() {
+    std::thread::spawn(|| {
+        let policy = Policy::default()
+            .deny(&caps::caps![CAP_SYS_BOOT])
+            .max(CapSet::Effective, &caps::caps![CAP_KILL]);
+        policy.enforce().unwrap();
+        assert!(policy::verify(&policy).unwrap().is_empty());
+        assert!(!caps::has_cap(None, CapSet::Permitted, Capability::CAP_SYS_BOOT).unwrap());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,31 @@
+// Environment variables are process-wide, thus tested in their own binary.
+extern crate caps;
+use caps::policy;
+use caps::{CapSet, Capability};
+use std::env;
+use std::thread;
+
+#[test]
+fn test_apply_from_env() {
+    assert_eq!(
+        policy::parse_list(" cap_net_raw, KILL,").unwrap(),
+        caps::caps![CAP_NET_RAW, CAP_KILL]
+    );
+    assert!(policy::parse_list("cap_nope").is_err());
+
+    env::remove_var(policy::KEEP_VAR);
+    env::remove_var(policy::DENY_VAR);
+    assert!(!policy::apply_from_env().unwrap());
+
+    env::set_var(policy::DENY_VAR, "sys_boot");
+    assert!(policy::from_env().unwrap().unwrap().allowed.is_none());
+    thread::spawn(|| {
+        assert!(policy::apply_from_env().unwrap());
+        assert!(!caps::has_cap(None, CapSet::Permitted, Capability::CAP_SYS_BOOT).unwrap());
+    })
+    .join()
+    .unwrap();
+
+    env::set_var(policy::KEEP_VAR, "bogus");
+    assert!(policy::from_env().is_err());
+}
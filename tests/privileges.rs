@@ -0,0 +1,50 @@
+#![cfg(any(target_os = "illumos", target_os = "solaris"))]
+
+#[macro_use]
+extern crate caps;
+
+use caps::privileges::{self, PrivSet, Privileges};
+use caps::{CapSet, Capability};
+
+#[test]
+fn test_privileges_set() {
+    let mut set = Privileges::empty().unwrap();
+    assert!(set.names().is_empty());
+    set.add("net_privaddr").unwrap();
+    assert!(set.contains("net_privaddr").unwrap());
+    assert_eq!(set.names(), vec!["net_privaddr".to_string()]);
+    set.remove("net_privaddr").unwrap();
+    assert_eq!(set, Privileges::empty().unwrap());
+    assert!(set.add("not_a_privilege").is_err());
+    assert!(Privileges::basic().unwrap().contains("proc_fork").unwrap());
+}
+
+#[test]
+fn test_privileges_mapping() {
+    assert_eq!(
+        PrivSet::from_cap_set(CapSet::Bounding),
+        Some(PrivSet::Limit)
+    );
+    assert_eq!(PrivSet::from_cap_set(CapSet::Ambient), None);
+    let set = Privileges::from_capabilities(&caps![CAP_NET_BIND_SERVICE]).unwrap();
+    assert_eq!(set.names(), vec!["net_privaddr".to_string()]);
+    assert!(privileges::for_capability(Capability::CAP_WAKE_ALARM).is_empty());
+}
+
+#[test]
+fn test_privileges_process() {
+    let effective = privileges::get(PrivSet::Effective).unwrap();
+    let permitted = privileges::get(PrivSet::Permitted).unwrap();
+    for name in effective.names() {
+        assert!(permitted.contains(&name).unwrap());
+    }
+    // Dropping a privilege from the Effective set only is reversible.
+    let mut fork = Privileges::empty().unwrap();
+    fork.add("proc_fork").unwrap();
+    if effective.contains("proc_fork").unwrap() {
+        privileges::drop(PrivSet::Effective, &fork).unwrap();
+        assert!(!privileges::has_priv(PrivSet::Effective, "proc_fork").unwrap());
+        privileges::raise(PrivSet::Effective, &fork).unwrap();
+        assert!(privileges::has_priv(PrivSet::Effective, "proc_fork").unwrap());
+    }
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "procfs")]
+
+extern crate caps;
+extern crate libc;
+extern crate procfs;
+use caps::CapState;
+use procfs::process::Process;
+use std::convert::TryFrom;
+
+#[test]
+fn test_procfs_status() {
+    let pid = unsafe { libc::getpid() };
+    let expected = CapState::from_proc(pid, pid).unwrap();
+    let process = Process::myself().unwrap();
+    let mut status = process.status().unwrap();
+    assert_eq!(CapState::try_from(&status).unwrap(), expected);
+    assert_eq!(CapState::try_from(&process).unwrap(), expected);
+    status.capamb = None;
+    assert!(CapState::try_from(&status).unwrap().ambient.is_empty());
+    status.capbnd = None;
+    assert!(CapState::try_from(&status).is_err());
+}
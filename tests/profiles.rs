@@ -0,0 +1,43 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::lowlevel::Masks;
+use caps::profiles;
+use caps::securebits;
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_profiles_registry() {
+    let names = profiles::names();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+    for name in &["web-server", "dns-resolver", "ping", "none"] {
+        assert!(names.contains(name));
+    }
+    let ping = profiles::get("ping").unwrap();
+    assert_eq!(ping.caps, &[Capability::CAP_NET_RAW]);
+    assert!(profiles::get("none").unwrap().to_set().is_empty());
+    assert!(profiles::get("nonexistent").is_none());
+    assert!(profiles::apply_profile("nonexistent").is_err());
+}
+
+#[test]
+fn test_apply_profile() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap()
+        || !caps::has_cap(None, CapSet::Permitted, Capability::CAP_NET_RAW).unwrap()
+    {
+        return;
+    }
+    // Everything is per-thread, thus in a dedicated one.
+    thread::spawn(|| {
+        profiles::apply_profile("ping").unwrap();
+        let masks = Masks::current().unwrap();
+        assert_eq!(masks.permitted, Capability::CAP_NET_RAW.bitmask());
+        assert_eq!(masks.bounding, Capability::CAP_NET_RAW.bitmask());
+        assert!(securebits::has_no_new_privs().unwrap());
+    })
+    .join()
+    .unwrap();
+}
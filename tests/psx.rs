@@ -0,0 +1,43 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::lowlevel::Masks;
+use caps::{psx, this_thread, CapSet, Capability};
+use std::sync::{mpsc, Arc, Barrier};
+use std::thread;
+
+// This is the only test in this binary, as it affects all threads.
+#[test]
+fn test_psx_set_all_threads() {
+    let cap = Capability::CAP_SYS_NICE;
+    let barrier = Arc::new(Barrier::new(5));
+    let (tx, rx) = mpsc::channel();
+    let mut workers = vec![];
+    for _ in 0..4 {
+        let (barrier, tx) = (barrier.clone(), tx.clone());
+        workers.push(thread::spawn(move || {
+            barrier.wait();
+            barrier.wait();
+            let eff = this_thread::read(CapSet::Effective).unwrap();
+            tx.send((eff, Masks::current().unwrap())).unwrap();
+        }));
+    }
+    barrier.wait();
+
+    let mut state = this_thread::state().unwrap();
+    state.effective.remove(&cap);
+    psx::set_all_threads(&state).unwrap();
+
+    barrier.wait();
+    for w in workers {
+        w.join().unwrap();
+    }
+    // All threads end up with the same raw masks, including capabilities
+    // unknown to the crate.
+    let masks = Masks::current().unwrap();
+    for (eff, other) in rx.try_iter() {
+        assert_eq!(eff, state.effective);
+        assert_eq!(other, masks);
+    }
+    assert!(!this_thread::has_cap(CapSet::Effective, cap).unwrap());
+}
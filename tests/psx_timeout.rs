@@ -0,0 +1,39 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+
+use caps::{psx, this_thread};
+use std::sync::{mpsc, Arc, Barrier};
+use std::thread;
+
+// This is the only test in this binary, as it affects all threads.
+#[test]
+fn test_psx_timeout_late_signal() {
+    let barrier = Arc::new(Barrier::new(2));
+    let (tx, rx) = mpsc::channel();
+    let worker = {
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            let mut set: libc::sigset_t = unsafe { std::mem::zeroed() };
+            unsafe {
+                libc::sigemptyset(&mut set);
+                libc::sigaddset(&mut set, libc::SIGRTMAX());
+                libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut());
+            }
+            barrier.wait();
+            barrier.wait();
+            // The signal is still pending, and delivered once unblocked.
+            unsafe { libc::pthread_sigmask(libc::SIG_UNBLOCK, &set, std::ptr::null_mut()) };
+            tx.send(()).unwrap();
+        })
+    };
+    barrier.wait();
+
+    let state = this_thread::state().unwrap();
+    assert!(psx::set_all_threads(&state).is_err());
+
+    barrier.wait();
+    rx.recv().unwrap();
+    worker.join().unwrap();
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::raw::{self, CapUserData, CapUserHeader};
+use caps::{CapSet, Capability};
+use std::thread;
+
+#[test]
+fn test_raw_capget() {
+    let mut hdr = CapUserHeader {
+        version: raw::CAPS_V3,
+        pid: 0,
+    };
+    let mut data = CapUserData::default();
+    raw::capget(&mut hdr, &mut data).unwrap();
+    let chown = caps::has_cap(None, CapSet::Permitted, Capability::CAP_CHOWN).unwrap();
+    assert_eq!(data.permitted_s0 & 1 != 0, chown);
+}
+
+#[test]
+fn test_raw_capset() {
+    thread::spawn(|| {
+        let mut hdr = CapUserHeader {
+            version: raw::CAPS_V3,
+            pid: 0,
+        };
+        let mut data = CapUserData::default();
+        raw::capget(&mut hdr, &mut data).unwrap();
+        data.effective_s0 = 0;
+        data.effective_s1 = 0;
+        raw::capset(&mut hdr, &data).unwrap();
+        assert!(caps::read(None, CapSet::Effective).unwrap().is_empty());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_raw_bad_version() {
+    let mut hdr = CapUserHeader { version: 1, pid: 0 };
+    let mut data = CapUserData::default();
+    let err = raw::capget(&mut hdr, &mut data).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(libc::EINVAL));
+    assert_eq!(hdr.version, raw::probe_version().unwrap());
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "rayon")]
+
+extern crate caps;
+extern crate rayon;
+use caps::{this_thread, threads, CapSet, Capability};
+
+#[test]
+fn test_rayon_pool_builder() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let mut state = this_thread::state().unwrap();
+    state.effective.remove(&cap);
+    let pool = threads::rayon_pool_builder(state)
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let held = pool.install(|| this_thread::has_cap(CapSet::Effective, cap).unwrap());
+    assert!(!held);
+    assert!(this_thread::has_cap(CapSet::Effective, cap).unwrap());
+}
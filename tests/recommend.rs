@@ -0,0 +1,48 @@
+#![cfg(feature = "libc")]
+
+#[macro_use]
+extern crate caps;
+use caps::recommend::Recommendation;
+use caps::usage::Usage;
+use caps::{CapSet, CapState, Capability};
+
+fn usage(capability: Capability, denied: bool) -> Usage {
+    Usage {
+        pid: 1,
+        comm: None,
+        capability,
+        denied,
+    }
+}
+
+#[test]
+fn test_recommendation() {
+    let grant = CapState {
+        bounding: caps![CAP_CHOWN, CAP_KILL, CAP_NET_BIND_SERVICE, CAP_NET_RAW],
+        effective: caps![CAP_NET_BIND_SERVICE, CAP_NET_RAW],
+        permitted: caps![CAP_NET_BIND_SERVICE, CAP_NET_RAW],
+        ..Default::default()
+    };
+    let uses = [
+        usage(Capability::CAP_NET_BIND_SERVICE, false),
+        usage(Capability::CAP_NET_BIND_SERVICE, false),
+        usage(Capability::CAP_SYS_NICE, true),
+    ];
+    let rec = Recommendation::new(&grant, &uses);
+    assert_eq!(rec.used, caps![CAP_NET_BIND_SERVICE, CAP_SYS_NICE]);
+    assert_eq!(rec.drop, caps![CAP_CHOWN, CAP_KILL, CAP_NET_RAW]);
+    assert_eq!(rec.missing, caps![CAP_SYS_NICE]);
+    assert_eq!(
+        rec.to_string(),
+        "Used: cap_net_bind_service,cap_sys_nice\n\
+         Drop: cap_chown,cap_kill,cap_net_raw\n\
+         Missing: cap_sys_nice"
+    );
+
+    let policy = rec.policy();
+    assert!(policy.no_new_privs);
+    assert!(policy.allows(CapSet::Permitted, Capability::CAP_NET_BIND_SERVICE));
+    assert!(!policy.allows(CapSet::Bounding, Capability::CAP_NET_RAW));
+    assert_eq!(rec.options().keep, rec.used);
+    assert_eq!(rec.hardening().bounding, rec.used);
+}
@@ -10,3 +10,20 @@ fn test_ambient_supported() {
 fn test_all_supported() {
     assert_eq!(runtime::all_supported(), caps::all());
 }
+
+#[test]
+fn test_userns_knobs() {
+    let knobs = runtime::UsernsKnobs::read();
+    assert!(knobs.max_user_namespaces.is_some());
+
+    let mut knobs = runtime::UsernsKnobs {
+        unprivileged_userns_clone: Some(false),
+        ..Default::default()
+    };
+    assert!(!knobs.allow_creation(false));
+    assert!(knobs.allow_creation(true));
+    knobs.max_user_namespaces = Some(0);
+    assert!(!knobs.allow_creation(true));
+    assert!(runtime::UsernsKnobs::default().allow_creation(false));
+    runtime::userns_creation_likely();
+}
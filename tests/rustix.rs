@@ -0,0 +1,30 @@
+#![cfg(feature = "rustix")]
+
+extern crate caps;
+extern crate rustix;
+
+use caps::{CapSet, Capability};
+use rustix::io::Errno;
+
+#[test]
+fn test_rustix_errno() {
+    let kill = Capability::CAP_KILL;
+    caps::drop(None, CapSet::Effective, kill).unwrap();
+    caps::drop(None, CapSet::Permitted, kill).unwrap();
+    let err = caps::raise(None, CapSet::Effective, kill).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(Errno::PERM.raw_os_error()));
+    let err = caps::raise(None, CapSet::Ambient, kill).unwrap_err();
+    assert_eq!(err.errno().map(|e| e.0), Some(Errno::PERM.raw_os_error()));
+}
+
+#[test]
+fn test_rustix_prctls() {
+    caps::securebits::get_securebits().unwrap();
+    caps::securebits::has_keepcaps().unwrap();
+    caps::securebits::has_no_new_privs().unwrap();
+    let bounding = caps::read(None, CapSet::Bounding).unwrap();
+    assert_eq!(
+        caps::has_cap(None, CapSet::Bounding, Capability::CAP_CHOWN).unwrap(),
+        bounding.contains(&Capability::CAP_CHOWN)
+    );
+}
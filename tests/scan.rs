@@ -0,0 +1,107 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::command::CommandCapsExt;
+use caps::scan::{self, Concern, Gain};
+use caps::{CapSet, CapState, Capability};
+use std::process::Command;
+
+#[test]
+fn test_all_processes() {
+    let pid = unsafe { libc::getpid() };
+    let all: Vec<_> = scan::all_processes().collect();
+    assert!(all.iter().all(|&(p, _)| p > 0));
+    let own = all.iter().find(|&&(p, _)| p == pid).unwrap();
+    assert_eq!(own.1, CapState::from_proc(pid, pid).unwrap());
+}
+
+#[test]
+fn test_holders() {
+    let pid = unsafe { libc::getpid() };
+    let state = CapState::from_proc(pid, pid).unwrap();
+    let cap = match state.effective.iter().next() {
+        Some(c) => *c,
+        None => return,
+    };
+    let holders = scan::holders(cap, CapSet::Effective);
+    let own = holders.iter().find(|h| h.pid == pid).unwrap();
+    assert!(!own.comm.is_empty());
+    assert!(!own.cmdline.is_empty());
+}
+
+#[test]
+fn test_concerns() {
+    let state = CapState {
+        bounding: caps::all(),
+        permitted: caps::caps![CAP_NET_BIND_SERVICE, CAP_SYS_ADMIN],
+        ..Default::default()
+    };
+    assert_eq!(
+        Concern::check(0, &state),
+        vec![
+            Concern::RootEquivalent(vec![Capability::CAP_SYS_ADMIN]),
+            Concern::FullBounding,
+        ]
+    );
+    let state = CapState {
+        permitted: caps::caps![CAP_NET_BIND_SERVICE],
+        ..Default::default()
+    };
+    assert_eq!(
+        Concern::check(1000, &state),
+        vec![Concern::NonRootCaps(vec![Capability::CAP_NET_BIND_SERVICE])]
+    );
+    assert!(Concern::check(1000, &CapState::default()).is_empty());
+}
+
+#[test]
+fn test_host_report() {
+    let report = scan::host_report();
+    assert!(report.scanned > 0);
+    assert!(report.flagged.iter().all(|f| !f.concerns.is_empty()));
+}
+
+#[test]
+fn test_gains() {
+    let ancestor = CapState {
+        permitted: caps::caps![CAP_CHOWN],
+        ..Default::default()
+    };
+    let state = CapState {
+        ambient: caps::caps![CAP_CHOWN],
+        permitted: caps::caps![CAP_CHOWN],
+        ..Default::default()
+    };
+    assert_eq!(
+        scan::gains(&ancestor, &state),
+        vec![Gain {
+            set: CapSet::Ambient,
+            caps: vec![Capability::CAP_CHOWN],
+        }]
+    );
+    assert!(scan::gains(&state, &ancestor).is_empty());
+}
+
+#[test]
+fn test_leaks() {
+    let cap = Capability::CAP_NET_BIND_SERVICE;
+    if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+        return;
+    }
+    let mut child = Command::new("sleep")
+        .arg("5")
+        .ambient_caps(&caps::caps![CAP_NET_BIND_SERVICE])
+        .spawn()
+        .unwrap();
+    let pid = child.id() as i32;
+    let leaks = scan::leaks(unsafe { libc::getpid() }).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let leak = leaks.iter().find(|l| l.process.pid == pid).unwrap();
+    assert_eq!(leak.ppid, unsafe { libc::getpid() });
+    assert!(leak
+        .gains
+        .iter()
+        .any(|g| g.set == CapSet::Ambient && g.caps == vec![cap]));
+}
@@ -12,3 +12,15 @@ fn test_keepcaps() {
     let f2 = securebits::has_keepcaps().unwrap();
     assert_eq!(f0, f2);
 }
+
+#[test]
+fn test_securebits() {
+    let keep = securebits::has_keepcaps().unwrap();
+    let bits = securebits::get_securebits().unwrap();
+    assert_eq!(bits.contains(securebits::SecureBits::KEEP_CAPS), keep);
+}
+
+#[test]
+fn test_no_new_privs() {
+    securebits::has_no_new_privs().unwrap();
+}
@@ -0,0 +1,26 @@
+extern crate caps;
+use caps::selinux;
+use caps::Capability;
+
+#[test]
+fn test_selinux_roundtrip() {
+    for cap in caps::all() {
+        let perm = selinux::permission(cap);
+        assert_eq!(selinux::from_permission(perm.class, &perm.name), Some(cap));
+        let perm = selinux::userns_permission(cap);
+        assert_eq!(selinux::from_permission(perm.class, &perm.name), Some(cap));
+    }
+}
+
+#[test]
+fn test_selinux_classes() {
+    let perm = selinux::permission(Capability::CAP_SYSLOG);
+    assert_eq!(perm.class, selinux::CAPABILITY2);
+    assert_eq!(perm.name, "syslog");
+    let perm = selinux::userns_permission(Capability::CAP_SYS_ADMIN);
+    assert_eq!(perm.class, selinux::CAP_USERNS);
+    assert_eq!(selinux::from_permission("capability", "syslog"), None);
+    assert_eq!(selinux::from_permission("capability", "NET_RAW"), None);
+    assert_eq!(selinux::from_permission("file", "net_raw"), None);
+    assert_eq!(selinux::from_permission("capability", "bogus"), None);
+}
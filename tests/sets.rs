@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate caps;
+use caps::sets;
+
+#[test]
+fn test_sets_root_equivalent() {
+    for c in caps::all() {
+        let is_root = c.risk() == caps::Risk::RootEquivalent;
+        assert_eq!(sets::ROOT_EQUIVALENT.contains(c), is_root);
+    }
+}
+
+#[test]
+fn test_sets_union() {
+    let s = sets::NETWORK | sets::AUDIT;
+    assert_eq!(
+        s.len(),
+        sets::NETWORK.as_slice().len() + sets::AUDIT.as_slice().len()
+    );
+    let s = sets::MAC | caps![CAP_KILL];
+    assert!(s.contains(&caps::Capability::CAP_MAC_ADMIN));
+    assert!(s.contains(&caps::Capability::CAP_KILL));
+    let s = caps![] | sets::MAC;
+    assert_eq!(s, sets::MAC.to_set());
+}
+
+#[test]
+fn test_drop_all_except() {
+    let keep = sets::NETWORK | caps![CAP_KILL];
+    let perm = caps::read(None, caps::CapSet::Permitted).unwrap();
+    let setpcap = caps::has_cap(None, caps::CapSet::Effective, caps::Capability::CAP_SETPCAP);
+    let ret = caps::drop_all_except(&keep);
+    if setpcap.unwrap() {
+        ret.unwrap();
+        let cur = caps::read(None, caps::CapSet::Permitted).unwrap();
+        assert!(cur.is_subset(&keep));
+        assert!(cur.is_subset(&perm));
+        let bset = caps::read(None, caps::CapSet::Bounding).unwrap();
+        assert!(bset.is_subset(&keep));
+        // Capabilities unknown to the crate are dropped too.
+        let kept = keep.iter().fold(0, |m, c| m | c.bitmask());
+        let masks = caps::lowlevel::Masks::current().unwrap();
+        assert_eq!(masks.bounding & !kept, 0);
+    }
+}
@@ -0,0 +1,59 @@
+extern crate caps;
+use caps::simple::{
+    self, Summary, ADD, ALL, BOUNDING, CAPS, DROP, EFFECTIVE, INHERITABLE, PERMITTED,
+};
+use caps::{CapSet, CapState, Capability};
+
+#[test]
+fn test_simple_working_copy() {
+    let cur = CapState::current().unwrap();
+    simple::get_caps_process().unwrap();
+    let chown = Capability::CAP_CHOWN;
+    assert_eq!(
+        simple::have_capability(BOUNDING, chown).unwrap(),
+        cur.bounding.contains(&chown)
+    );
+    simple::clear(ALL).unwrap();
+    assert!(!simple::have_capability(EFFECTIVE, chown).unwrap());
+    simple::update(ADD, EFFECTIVE | PERMITTED, chown).unwrap();
+    assert!(simple::have_capability(EFFECTIVE | PERMITTED, chown).unwrap());
+    assert!(!simple::have_capability(EFFECTIVE | INHERITABLE, chown).unwrap());
+    simple::fill(INHERITABLE).unwrap();
+    assert!(simple::have_capability(INHERITABLE, Capability::CAP_KILL).unwrap());
+    simple::updatev(DROP, CAPS, &[chown, Capability::CAP_KILL]).unwrap();
+    assert!(!simple::have_capability(EFFECTIVE, chown).unwrap());
+    assert!(!simple::have_capability(INHERITABLE, Capability::CAP_KILL).unwrap());
+    // Nothing was applied.
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_simple_apply() {
+    let chown = Capability::CAP_CHOWN;
+    let perm = caps::has_cap(None, CapSet::Permitted, chown).unwrap();
+    simple::get_caps_process().unwrap();
+    simple::clear(CAPS).unwrap();
+    if perm {
+        simple::update(ADD, EFFECTIVE | PERMITTED, chown).unwrap();
+    }
+    let bounding = caps::read(None, CapSet::Bounding).unwrap();
+    simple::apply(CAPS).unwrap();
+    let cur = CapState::current().unwrap();
+    assert_eq!(cur.permitted.len(), perm as usize);
+    assert_eq!(cur.effective, cur.permitted);
+    assert!(cur.inheritable.is_empty());
+    assert_eq!(cur.bounding, bounding);
+}
+
+#[test]
+fn test_simple_have_capabilities() {
+    simple::get_caps_process().unwrap();
+    simple::clear(ALL).unwrap();
+    assert_eq!(simple::have_capabilities(CAPS).unwrap(), Summary::None);
+    simple::update(ADD, EFFECTIVE, Capability::CAP_CHOWN).unwrap();
+    assert_eq!(simple::have_capabilities(CAPS).unwrap(), Summary::Partial);
+    assert_eq!(simple::have_capabilities(PERMITTED).unwrap(), Summary::None);
+    simple::fill(CAPS).unwrap();
+    assert_eq!(simple::have_capabilities(CAPS).unwrap(), Summary::Full);
+    assert_eq!(simple::have_capabilities(ALL).unwrap(), Summary::Partial);
+}
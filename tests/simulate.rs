@@ -0,0 +1,77 @@
+#![cfg(feature = "libc")]
+
+#[macro_use]
+extern crate caps;
+use caps::file::FileCaps;
+use caps::simulate::{Chain, ExecFile, Process};
+use caps::CapState;
+
+fn root() -> Process {
+    Process {
+        state: CapState {
+            bounding: caps::all(),
+            effective: caps::all(),
+            permitted: caps::all(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_simulate_fork_exec() {
+    let ping = ExecFile {
+        file_caps: Some(FileCaps {
+            effective: true,
+            permitted: caps![CAP_NET_RAW],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let steps = Chain::new(root())
+        .fork()
+        .setuid(1000)
+        .exec(ExecFile::default())
+        .exec(ping)
+        .simulate()
+        .unwrap();
+    assert_eq!(steps.len(), 4);
+    assert_eq!(steps[0], root());
+    assert_eq!(steps[1].uid, 1000);
+    assert!(steps[1].state.permitted.is_empty());
+    assert!(steps[2].state.permitted.is_empty());
+    assert_eq!(steps[3].state.permitted, caps![CAP_NET_RAW]);
+    assert_eq!(steps[3].state.effective, caps![CAP_NET_RAW]);
+}
+
+#[test]
+fn test_simulate_keep_caps() {
+    let mut start = root();
+    start.securebits = caps::securebits::SecureBits::KEEP_CAPS;
+    let steps = Chain::new(start)
+        .setuid(1000)
+        .exec(ExecFile::default())
+        .simulate()
+        .unwrap();
+    assert_eq!(steps[0].state.permitted, caps::all());
+    assert!(steps[0].state.effective.is_empty());
+    assert!(steps[1].state.permitted.is_empty());
+}
+
+#[test]
+fn test_simulate_setuid_denied() {
+    let start = Process {
+        uid: 1000,
+        euid: 1000,
+        suid: 1000,
+        ..Default::default()
+    };
+    assert!(Chain::new(start).setuid(0).simulate().is_err());
+}
+
+#[test]
+fn test_simulate_current() {
+    let start = Process::current().unwrap();
+    let steps = Chain::new(start.clone()).fork().simulate().unwrap();
+    assert_eq!(steps[0], start);
+}
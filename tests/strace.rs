@@ -0,0 +1,63 @@
+extern crate caps;
+use caps::strace::{self, Call};
+use caps::Capability;
+
+const LOG: &str = r#"execve("/usr/bin/daemon", ["daemon"], 0x7ffc /* 20 vars */) = 0
+[pid  1234] 12:00:00.000001 setuid(0) = -1 EPERM (Operation not permitted)
+1235  chown32("/run/daemon.pid", 0, 0 <unfinished ...>
+1235  <... chown32 resumed>) = -1 EPERM (Operation not permitted)
+1236  bind(3, {sa_family=AF_INET, sin_port=htons(80)}, 16) = -1 EACCES (Permission denied)
+1236  bind(4, {sa_family=AF_INET, sin_port=htons(8080)}, 16) = 0
+openat(AT_FDCWD, "/missing", O_RDONLY) = -1 ENOENT (No such file or directory)
+--- SIGCHLD {si_signo=SIGCHLD, si_code=CLD_EXITED} ---
++++ exited with 1 +++
+"#;
+
+#[test]
+fn test_parse_line() {
+    assert_eq!(
+        strace::parse_line(
+            "[pid  1234] 12:00:00.000001 setuid(0) = -1 EPERM (Operation not permitted)"
+        ),
+        Some(Call {
+            pid: Some(1234),
+            syscall: "setuid".to_string(),
+            errno: Some("EPERM".to_string()),
+        })
+    );
+    let call =
+        strace::parse_line("1235  <... chown32 resumed>) = -1 EPERM (Operation not permitted)")
+            .unwrap();
+    assert_eq!(call.pid, Some(1235));
+    assert_eq!(call.syscall, "chown32");
+    assert!(call.denied());
+    let call = strace::parse_line("write(1, \"a = b\", 5) = 5").unwrap();
+    assert_eq!(call.pid, None);
+    assert_eq!(call.errno, None);
+    assert_eq!(
+        strace::parse_line("1235  chown(\"/x\", 0, 0 <unfinished ...>"),
+        None
+    );
+    assert_eq!(strace::parse_line("<... resumed>) = 0"), None);
+    assert_eq!(strace::parse_line("1235  <...  resumed>) = 0"), None);
+    assert_eq!(strace::parse_line("+++ exited with 1 +++"), None);
+    assert_eq!(strace::parse(LOG).len(), 6);
+}
+
+#[test]
+fn test_infer() {
+    let suggestions = strace::infer(LOG);
+    let caps: Vec<Capability> = suggestions.iter().map(|s| s.capability).collect();
+    assert_eq!(
+        caps,
+        [
+            Capability::CAP_CHOWN,
+            Capability::CAP_SETUID,
+            Capability::CAP_NET_BIND_SERVICE,
+            // Binding netlink audit sockets also goes through bind.
+            Capability::CAP_AUDIT_READ,
+        ]
+    );
+    assert_eq!(suggestions[0].syscalls, ["chown32"]);
+    assert_eq!(suggestions[2].failures, 1);
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "proptest")]
+
+#[macro_use]
+extern crate proptest;
+extern crate caps;
+use caps::strategy;
+
+proptest! {
+    #[test]
+    fn test_capability_known(cap in strategy::capability()) {
+        prop_assert!(caps::all().contains(&cap));
+    }
+
+    #[test]
+    fn test_caps_set_known(set in strategy::caps_set()) {
+        prop_assert!(set.is_subset(&caps::all()));
+    }
+
+    #[test]
+    fn test_consistent_cap_state(state in strategy::consistent_cap_state()) {
+        prop_assert!(state.effective.is_subset(&state.permitted));
+        prop_assert!(state.ambient.is_subset(&state.permitted));
+        prop_assert!(state.ambient.is_subset(&state.inheritable));
+    }
+}
@@ -0,0 +1,47 @@
+#[macro_use]
+extern crate caps;
+use caps::policy::Policy;
+use caps::securebits::{self, SecureBits};
+use caps::systemd::Hardening;
+use caps::CapSet;
+
+#[test]
+fn test_hardening_to_unit() {
+    let hardening = Hardening {
+        bounding: caps![CAP_NET_RAW, CAP_CHOWN],
+        securebits: SecureBits::NOROOT | SecureBits::NOROOT_LOCKED,
+        ..Default::default()
+    };
+    assert_eq!(
+        hardening.to_unit(),
+        "CapabilityBoundingSet=CAP_CHOWN CAP_NET_RAW\n\
+         AmbientCapabilities=\n\
+         NoNewPrivileges=no\n\
+         SecureBits=noroot noroot-locked\n"
+    );
+}
+
+#[test]
+fn test_hardening_from_policy() {
+    let policy = Policy::default()
+        .allow(&caps![CAP_NET_BIND_SERVICE, CAP_NET_RAW, CAP_KILL])
+        .deny(&caps![CAP_KILL])
+        .max(CapSet::Ambient, &caps![CAP_NET_BIND_SERVICE, CAP_KILL]);
+    let hardening = Hardening::from_policy(&policy);
+    assert_eq!(hardening.bounding, caps![CAP_NET_BIND_SERVICE, CAP_NET_RAW]);
+    assert_eq!(hardening.ambient, caps![CAP_NET_BIND_SERVICE]);
+    assert!(Hardening::from_policy(&Policy::default())
+        .ambient
+        .is_empty());
+}
+
+#[test]
+fn test_hardening_current() {
+    let hardening = Hardening::current().unwrap();
+    let state = caps::CapState::current().unwrap();
+    assert_eq!(hardening.bounding, state.bounding);
+    assert_eq!(
+        hardening.no_new_privs,
+        securebits::has_no_new_privs().unwrap()
+    );
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "test-util")]
+
+#[macro_use]
+extern crate caps;
+use caps::test_util;
+use caps::{CapSet, CapState, Capability};
+use std::panic;
+
+#[test]
+fn test_with_caps_restores() {
+    let cur = CapState::current().unwrap();
+    let state = CapState {
+        effective: caps![],
+        ..cur.clone()
+    };
+    let res = test_util::with_caps(&state, || CapState::current().unwrap());
+    assert_eq!(res, Some(state));
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_with_caps_helper_thread() {
+    let cur = CapState::current().unwrap();
+    if !test_util::require(CapSet::Permitted, &caps![CAP_CHOWN]) {
+        return;
+    }
+    let state = CapState {
+        effective: caps![CAP_CHOWN],
+        permitted: caps![CAP_CHOWN],
+        inheritable: caps![],
+        ..cur.clone()
+    };
+    let res = test_util::with_caps(&state, || {
+        caps::has_cap(None, CapSet::Permitted, Capability::CAP_KILL).unwrap()
+    });
+    assert_eq!(res, Some(false));
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_with_caps_panic() {
+    let cur = CapState::current().unwrap();
+    let state = CapState {
+        effective: caps![],
+        ..cur.clone()
+    };
+    let res = panic::catch_unwind(|| test_util::with_caps(&state, || panic!("boom")));
+    assert!(res.is_err());
+    assert_eq!(CapState::current().unwrap(), cur);
+}
+
+#[test]
+fn test_with_caps_skipped() {
+    let cur = CapState::current().unwrap();
+    let mut state = cur.clone();
+    state.permitted = caps::all();
+    state.bounding = caps::all();
+    let ran = test_util::with_caps(&state, || ());
+    assert_eq!(
+        ran.is_some(),
+        cur.permitted == caps::all() && cur.bounding == caps::all()
+    );
+}
@@ -0,0 +1,36 @@
+extern crate caps;
+use caps::{this_thread, CapSet, Capability, Target};
+use std::thread;
+
+#[test]
+fn test_this_thread_gettid() {
+    let tid = this_thread::gettid();
+    assert!(tid > 0);
+    let other = thread::spawn(this_thread::gettid).join().unwrap();
+    assert_ne!(tid, other);
+    let by_tid = caps::read(Target::Thread(tid), CapSet::Effective).unwrap();
+    assert_eq!(by_tid, this_thread::read(CapSet::Effective).unwrap());
+}
+
+#[test]
+fn test_this_thread_per_thread() {
+    let cap = Capability::CAP_SYS_NICE;
+    let before = this_thread::has_cap(CapSet::Effective, cap).unwrap();
+    let tid = this_thread::gettid();
+    thread::spawn(move || {
+        this_thread::drop(CapSet::Effective, cap).unwrap();
+        assert!(!this_thread::has_cap(CapSet::Effective, cap).unwrap());
+        let parent = caps::has_cap(Target::Thread(tid), CapSet::Effective, cap).unwrap();
+        assert_eq!(parent, before);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        this_thread::has_cap(CapSet::Effective, cap).unwrap(),
+        before
+    );
+    assert_eq!(
+        this_thread::state().unwrap().effective.contains(&cap),
+        before
+    );
+}
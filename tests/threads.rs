@@ -0,0 +1,76 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::{this_thread, threads, CapSet, Capability};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+#[test]
+fn test_from_proc() {
+    let pid = std::process::id() as i32;
+    let state = caps::CapState::from_proc(pid, this_thread::gettid()).unwrap();
+    assert_eq!(state, this_thread::state().unwrap());
+}
+
+#[test]
+fn test_threads_consistent() {
+    let pid = std::process::id() as i32;
+    let cap = Capability::CAP_SYS_PACCT;
+    let barrier = Arc::new(Barrier::new(2));
+    let b = barrier.clone();
+    let worker = thread::spawn(move || {
+        this_thread::drop(CapSet::Effective, cap).unwrap();
+        b.wait();
+        b.wait();
+        this_thread::gettid()
+    });
+    barrier.wait();
+    let report = threads::threads_consistent(pid).unwrap();
+    barrier.wait();
+    let tid = worker.join().unwrap();
+    assert_eq!(report.pid, pid);
+    if this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        assert!(!report.is_consistent());
+        assert!(report.divergent.contains(&tid));
+    }
+}
+
+#[test]
+fn test_proc_status_invalid() {
+    assert!(caps::CapState::from_proc_status("Name: foo\n").is_err());
+    assert!(caps::CapState::from_proc_status("CapInh: zz\n").is_err());
+}
+
+#[test]
+fn test_spawn_with_caps() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let mut state = this_thread::state().unwrap();
+    state.effective.remove(&cap);
+    let handle = threads::spawn_with_caps(thread::Builder::new(), state, move || {
+        this_thread::has_cap(CapSet::Effective, cap).unwrap()
+    })
+    .unwrap();
+    assert!(!handle.join().unwrap().unwrap());
+    assert!(this_thread::has_cap(CapSet::Effective, cap).unwrap());
+}
+
+#[test]
+fn test_start_handler() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let mut state = this_thread::state().unwrap();
+    state.effective.remove(&cap);
+    let handler = threads::start_handler(state);
+    let held = thread::spawn(move || {
+        handler(0);
+        this_thread::has_cap(CapSet::Effective, cap).unwrap()
+    })
+    .join()
+    .unwrap();
+    assert!(!held);
+}
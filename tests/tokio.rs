@@ -0,0 +1,24 @@
+#![cfg(feature = "tokio")]
+
+extern crate caps;
+extern crate tokio;
+use caps::{this_thread, threads, CapSet, Capability};
+
+#[test]
+fn test_tokio_runtime_builder() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !this_thread::has_cap(CapSet::Effective, cap).unwrap() {
+        return;
+    }
+    let mut state = this_thread::state().unwrap();
+    state.effective.remove(&cap);
+    let rt = threads::tokio_runtime_builder(state)
+        .worker_threads(2)
+        .build()
+        .unwrap();
+    let held = rt
+        .block_on(rt.spawn_blocking(move || this_thread::has_cap(CapSet::Effective, cap).unwrap()))
+        .unwrap();
+    assert!(!held);
+    assert!(this_thread::has_cap(CapSet::Effective, cap).unwrap());
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::tool::{self, Sets};
+use caps::{CapSet, CapState, Capability};
+
+#[test]
+fn test_inspect() {
+    let pid = unsafe { libc::getpid() };
+    let i = tool::inspect(pid).unwrap();
+    assert_eq!(i.pid, pid);
+    assert_eq!(
+        i.sets,
+        Sets::from_state(&CapState::from_proc(pid, pid).unwrap())
+    );
+    assert!(i.securebits.is_some());
+}
+
+#[test]
+fn test_diff() {
+    let mut a = CapState::default();
+    let mut b = CapState::default();
+    assert!(tool::diff(&a, &b).is_empty());
+    a.permitted.insert(Capability::CAP_CHOWN);
+    b.permitted.insert(Capability::CAP_KILL);
+    b.effective.insert(Capability::CAP_KILL);
+    let diffs = tool::diff(&a, &b);
+    assert_eq!(diffs.len(), 2);
+    let prm = diffs.iter().find(|d| d.set == CapSet::Permitted).unwrap();
+    assert_eq!(prm.added, vec![Capability::CAP_KILL]);
+    assert_eq!(prm.removed, vec![Capability::CAP_CHOWN]);
+    let eff = diffs.iter().find(|d| d.set == CapSet::Effective).unwrap();
+    assert_eq!(eff.added, vec![Capability::CAP_KILL]);
+    assert!(eff.removed.is_empty());
+}
+
+#[test]
+fn test_predict() {
+    let outcome = tool::predict("true").unwrap();
+    assert!(outcome.program.is_absolute());
+    assert!(outcome.state.is_some());
+    assert!(tool::predict("caps-no-such-program").is_err());
+}
+
+#[test]
+fn test_lookup() {
+    assert_eq!(tool::lookup_user("root").unwrap(), (0, 0));
+    assert_eq!(tool::lookup_user("0").unwrap().0, 0);
+    assert_eq!(tool::lookup_group("0").unwrap(), 0);
+    assert!(tool::lookup_user("caps-no-such-user").is_err());
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "tracing")]
+
+extern crate caps;
+extern crate tracing;
+use caps::{CapSet, Capability};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// Subscriber recording the fields of all events, as "name=value" strings.
+#[derive(Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+    spans: AtomicUsize,
+}
+
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "caps"
+    }
+    fn new_span(&self, _: &Attributes) -> Id {
+        Id::from_u64(self.spans.fetch_add(1, Ordering::SeqCst) as u64 + 1)
+    }
+    fn record(&self, _: &Id, _: &Record) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event) {
+        let mut fields = Fields(vec![]);
+        event.record(&mut fields);
+        self.events.lock().unwrap().push(fields.0.join(" "));
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_tracing() {
+    thread::spawn(|| {
+        let cap = Capability::CAP_CHOWN;
+        if !caps::has_cap(None, CapSet::Permitted, cap).unwrap() {
+            return;
+        }
+        let recorder = Recorder::default();
+        let events = recorder.events.clone();
+        tracing::subscriber::with_default(recorder, || {
+            caps::drop(None, CapSet::Effective, cap).unwrap();
+        });
+        let events = events.lock().unwrap();
+        assert!(events.iter().any(|e| e.starts_with("syscall=capset")));
+        assert!(events
+            .iter()
+            .any(|e| e.contains("set=Effective") && e.contains("removed=CAP_CHOWN")));
+        caps::raise(None, CapSet::Effective, cap).unwrap();
+    })
+    .join()
+    .unwrap();
+}
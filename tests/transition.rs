@@ -0,0 +1,153 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::errors::ErrorKind;
+use caps::securebits::{self, SecureBits};
+use caps::transition::{self, Action, Transition};
+use caps::{CapSet, Capability};
+
+#[test]
+fn test_transition_invalid() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap() {
+        return;
+    }
+    let before = securebits::get_securebits().unwrap();
+    let err = Transition::new()
+        .securebits(SecureBits::NO_CAP_AMBIENT_RAISE)
+        .inheritable(&caps::caps![CAP_NET_RAW])
+        .ambient(&caps::caps![CAP_NET_RAW])
+        .apply()
+        .unwrap_err();
+    match *err.kind() {
+        ErrorKind::InvalidTransition(_) => {}
+        ref e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(securebits::get_securebits().unwrap(), before);
+}
+
+// Ids are switched for the whole process, thus in a child.
+fn in_child<F: FnOnce() -> bool>(f: F) {
+    match unsafe { libc::fork() } {
+        0 => {
+            let ok = f();
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+        }
+        pid => {
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            assert_eq!(libc::WEXITSTATUS(status), 0);
+        }
+    }
+}
+
+#[test]
+fn test_transition_apply() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETUID).unwrap() {
+        return;
+    }
+    in_child(|| {
+        let report = Transition::new()
+            .bounding(&caps::caps![CAP_NET_RAW, CAP_SETPCAP])
+            .groups(&[])
+            .gid(65534)
+            .uid(65534)
+            .inheritable(&caps::caps![CAP_NET_RAW])
+            .ambient(&caps::caps![CAP_NET_RAW])
+            .no_new_privs()
+            .apply()
+            .unwrap();
+        report.actions.len() == 7
+            && report.actions[3] == Action::Uid(65534)
+            && unsafe { libc::getuid() } == 65534
+            && caps::read(None, CapSet::Ambient).unwrap() == caps::caps![CAP_NET_RAW]
+            && caps::has_cap(None, CapSet::Effective, Capability::CAP_NET_RAW).unwrap()
+            && !caps::has_cap(None, CapSet::Bounding, Capability::CAP_SYS_ADMIN).unwrap()
+            && securebits::has_no_new_privs().unwrap()
+    });
+}
+
+#[test]
+fn test_transition_lockdown() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETUID).unwrap() {
+        return;
+    }
+    in_child(|| {
+        // Keepcaps is locked off, but NO_SETUID_FIXUP retains capabilities.
+        let permitted = caps::read(None, CapSet::Permitted).unwrap();
+        Transition::new()
+            .securebits(SecureBits::LOCKDOWN)
+            .groups(&[])
+            .gid(65534)
+            .uid(65534)
+            .apply()
+            .unwrap();
+        let uid = unsafe { libc::getuid() };
+        uid == 65534
+            && securebits::get_securebits().unwrap() == SecureBits::LOCKDOWN
+            && caps::read(None, CapSet::Permitted).unwrap() == permitted
+    });
+    in_child(|| {
+        // Capabilities cannot be retained: nothing is changed.
+        let err = Transition::new()
+            .securebits(SecureBits::KEEP_CAPS_LOCKED)
+            .groups(&[])
+            .gid(65534)
+            .uid(65534)
+            .apply()
+            .unwrap_err();
+        matches!(*err.kind(), ErrorKind::InvalidTransition(_))
+            && unsafe { (libc::getuid(), libc::getgid()) } == (0, 0)
+            && securebits::get_securebits().unwrap() == SecureBits::default()
+    });
+}
+
+#[test]
+fn test_transition_requires_groups() {
+    let err = Transition::new().uid(65534).validate().unwrap_err();
+    match *err.kind() {
+        ErrorKind::InvalidTransition(_) => {}
+        ref e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_change_id() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETUID).unwrap() {
+        return;
+    }
+    in_child(|| {
+        let groups = [42];
+        assert_eq!(unsafe { libc::setgroups(1, groups.as_ptr()) }, 0);
+        transition::change_id(65534, 65534, &caps::caps![CAP_NET_BIND_SERVICE]).unwrap();
+        let kept = caps::caps![CAP_NET_BIND_SERVICE];
+        let ids = unsafe { (libc::getuid(), libc::getgid()) };
+        ids == (65534, 65534)
+            && unsafe { libc::getgroups(0, std::ptr::null_mut()) } == 0
+            && caps::read(None, CapSet::Permitted).unwrap() == kept
+            && caps::read(None, CapSet::Effective).unwrap() == kept
+            && caps::read(None, CapSet::Inheritable).unwrap().is_empty()
+            && caps::read(None, CapSet::Ambient).unwrap().is_empty()
+    });
+}
+
+#[test]
+fn test_change_id_keepcaps_locked() {
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETUID).unwrap() {
+        return;
+    }
+    in_child(|| {
+        let groups = [42];
+        assert_eq!(unsafe { libc::setgroups(1, groups.as_ptr()) }, 0);
+        securebits::set_securebits(SecureBits::KEEP_CAPS_LOCKED).unwrap();
+        let err = transition::change_id(65534, 65534, &caps::caps![]).unwrap_err();
+        // Checked before groups and ids are changed.
+        let mut current = [0; 2];
+        let n = unsafe { libc::getgroups(2, current.as_mut_ptr()) };
+        let ids = unsafe { (libc::getuid(), libc::getgid()) };
+        matches!(*err.kind(), ErrorKind::InvalidTransition(_))
+            && current[..n as usize] == groups
+            && ids == (0, 0)
+    });
+}
@@ -0,0 +1,61 @@
+extern crate caps;
+use caps::usage::{self, Recorder};
+use caps::Capability;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::Command;
+
+const DENIED: &str = "type=AVC msg=audit(1700000000.123:456): avc:  denied  { net_raw } \
+                      for  pid=1234 comm=\"ping\" capability=13  scontext=u:r:ping_t:s0 \
+                      tcontext=u:r:ping_t:s0 tclass=capability permissive=1";
+
+#[test]
+fn test_parse_line() {
+    let uses = usage::parse_line(DENIED);
+    assert_eq!(uses.len(), 1);
+    assert_eq!(uses[0].pid, 1234);
+    assert_eq!(uses[0].comm.as_deref(), Some("ping"));
+    assert_eq!(uses[0].capability, Capability::CAP_NET_RAW);
+    assert!(uses[0].denied);
+
+    let granted = "type=AVC msg=audit(1.2:3): avc:  granted  { setuid setgid } for  pid=7 \
+                   comm=\"su\" tclass=capability";
+    let caps: Vec<_> = usage::parse_line(granted)
+        .into_iter()
+        .map(|u| u.capability)
+        .collect();
+    assert_eq!(caps, [Capability::CAP_SETUID, Capability::CAP_SETGID]);
+
+    let file = "type=AVC msg=audit(1.2:3): avc:  denied  { read } for  pid=7 tclass=file";
+    assert!(usage::parse_line(file).is_empty());
+    assert!(usage::parse_line("type=SYSCALL msg=audit(1.2:3): arch=c000003e").is_empty());
+}
+
+#[test]
+fn test_recorder() {
+    let path = std::env::temp_dir().join(format!("caps-usage-{}.log", std::process::id()));
+    fs::write(&path, format!("{}\n", DENIED)).unwrap();
+    let recorder = Recorder::start(&path).unwrap();
+    assert!(recorder.collect().unwrap().is_empty());
+    let mut log = OpenOptions::new().append(true).open(&path).unwrap();
+    writeln!(log, "{}", DENIED.replace("1234", "42")).unwrap();
+    let uses = recorder.collect().unwrap();
+    assert_eq!(uses.len(), 1);
+    assert_eq!(uses[0].pid, 42);
+
+    // Records written shortly after the workload are waited for.
+    let recorder = Recorder::start(&path).unwrap();
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        writeln!(log, "{}", DENIED).unwrap();
+    });
+    recorder.settle().unwrap();
+    assert_eq!(recorder.collect().unwrap().len(), 1);
+    writer.join().unwrap();
+
+    let discovery = usage::record(&mut Command::new("true"), &path).unwrap();
+    assert!(discovery.status.success());
+    assert!(discovery.records.is_empty());
+    assert!(discovery.used.is_empty());
+    fs::remove_file(&path).unwrap();
+}
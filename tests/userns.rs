@@ -0,0 +1,119 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+extern crate libc;
+use caps::userns::{self, UidMap, UserNamespace};
+use caps::{CapSet, Capability};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+fn ns(inode: u64, owner: u32, parent: Option<UserNamespace>) -> UserNamespace {
+    UserNamespace {
+        inode,
+        owner,
+        parent: parent.map(Box::new),
+    }
+}
+
+#[test]
+fn test_capable_in() {
+    let init = ns(1, 0, None);
+    let child = ns(2, 1000, Some(init.clone()));
+    let grandchild = ns(3, 2000, Some(child.clone()));
+    let eff = caps::caps![CAP_CHOWN];
+    let cap = Capability::CAP_CHOWN;
+
+    assert!(userns::capable_in(&init, 0, &eff, cap, &init));
+    assert!(userns::capable_in(&init, 0, &eff, cap, &grandchild));
+    assert!(!userns::capable_in(
+        &init,
+        0,
+        &eff,
+        Capability::CAP_KILL,
+        &init
+    ));
+    // Owners have all capabilities in their child namespaces.
+    assert!(userns::capable_in(
+        &init,
+        1000,
+        &caps::CapsHashSet::new(),
+        Capability::CAP_SYS_ADMIN,
+        &grandchild
+    ));
+    assert!(!userns::capable_in(
+        &init,
+        2000,
+        &caps::CapsHashSet::new(),
+        cap,
+        &grandchild
+    ));
+    // No privileges over parent namespaces.
+    assert!(!userns::capable_in(&child, 0, &eff, cap, &init));
+    assert!(init.contains(&grandchild));
+    assert!(!grandchild.contains(&init));
+}
+
+#[test]
+fn test_uid_map() {
+    let map = UidMap::parse("0 1000 1\n1 100000 65536\n").unwrap();
+    assert_eq!(map.to_outside(0), Some(1000));
+    assert_eq!(map.to_outside(2), Some(100_001));
+    assert_eq!(map.to_outside(65537), None);
+    assert_eq!(map.to_inside(1000), Some(0));
+    assert_eq!(map.to_inside(165_535), Some(65536));
+    assert_eq!(map.to_inside(999), None);
+    assert!(UidMap::parse("0 1000").is_err());
+    assert!(UidMap::parse("0 1000 x").is_err());
+
+    let overflow = UidMap::parse("0 4294967295 2\n4294967295 0 2\n").unwrap();
+    assert_eq!(overflow.to_outside(0), Some(u32::MAX));
+    assert_eq!(overflow.to_outside(1), None);
+    assert_eq!(overflow.to_inside(0), Some(u32::MAX));
+    assert_eq!(overflow.to_inside(1), None);
+
+    let own = UidMap::read(unsafe { libc::getpid() }).unwrap();
+    let euid = unsafe { libc::geteuid() };
+    assert_eq!(own.to_inside(own.to_outside(euid).unwrap()), Some(euid));
+}
+
+#[test]
+fn test_ns_capable() {
+    let pid = unsafe { libc::getpid() };
+    let own = UserNamespace::current().unwrap();
+    let cap = Capability::CAP_CHOWN;
+    let has = caps::read(caps::Target::Process(pid), CapSet::Effective)
+        .unwrap()
+        .contains(&cap);
+    assert_eq!(userns::ns_capable(pid, cap, &own).unwrap(), has);
+
+    let mut child = match Command::new("unshare")
+        .args(["--user", "sleep", "5"])
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let cpid = child.id() as i32;
+    let mut nested = None;
+    for _ in 0..200 {
+        match UserNamespace::of(cpid) {
+            Ok(ref n) if n.inode != own.inode => {
+                nested = Some(n.clone());
+                break;
+            }
+            _ => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    let nested = match nested {
+        Some(n) => n,
+        None => return,
+    };
+    assert_eq!(nested.parent.as_ref().unwrap().inode, own.inode);
+    let euid = unsafe { libc::geteuid() };
+    assert_eq!(nested.owner, euid);
+    // As the owner, this process has all capabilities in the child namespace.
+    assert!(userns::ns_capable(pid, Capability::CAP_SYS_ADMIN, &nested).unwrap());
+}
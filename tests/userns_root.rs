@@ -0,0 +1,41 @@
+#![cfg(feature = "libc")]
+
+// Entering a user namespace requires a single-threaded process, thus it is
+// tested in a forked child, in its own binary.
+extern crate caps;
+extern crate libc;
+use caps::{runtime, userns, CapSet, Capability};
+
+#[test]
+fn test_unshare_and_map_root() {
+    // Unprivileged user namespaces may be disabled, or denied by an LSM.
+    if !runtime::userns_creation_likely() {
+        return;
+    }
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        let code = match userns::unshare_and_map_root() {
+            Err(ref e) if e.errno().map(|e| e.0) == Some(libc::EPERM) => 2,
+            Err(_) => 1,
+            Ok(()) => {
+                let root = unsafe { libc::geteuid() } == 0;
+                let admin = caps::has_cap(None, CapSet::Effective, Capability::CAP_SYS_ADMIN);
+                if root && admin.unwrap_or(false) {
+                    0
+                } else {
+                    1
+                }
+            }
+        };
+        unsafe { libc::_exit(code) };
+    }
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    // Creation was denied anyway, e.g. in a restricted container.
+    if libc::WEXITSTATUS(status) == 2 {
+        return;
+    }
+    assert_eq!(libc::WEXITSTATUS(status), 0);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "libc")]
+
+// The watchdog restricts all threads of the process, thus it is tested in
+// its own binary.
+extern crate caps;
+use caps::policy::Policy;
+use caps::watchdog::{self, Event, Watchdog};
+use caps::{CapSet, Capability};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[test]
+fn test_watchdog() {
+    let cap = Capability::CAP_SYS_BOOT;
+    if !caps::has_cap(None, CapSet::Effective, Capability::CAP_SETPCAP).unwrap()
+        || !caps::has_cap(None, CapSet::Permitted, cap).unwrap()
+    {
+        return;
+    }
+    let policy = Policy::default().deny(&caps::caps![CAP_SYS_BOOT]);
+    assert!(!watchdog::check(&policy).unwrap().is_empty());
+
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let dog = Watchdog::start_with(policy.clone(), Duration::from_millis(10), move |e| {
+        let corrected = matches!(*e, Event::Corrected(_));
+        let _ = tx.lock().unwrap().send(corrected);
+    })
+    .unwrap();
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+    dog.stop();
+
+    assert!(watchdog::check(&policy).unwrap().is_empty());
+    assert!(!caps::has_cap(None, CapSet::Permitted, cap).unwrap());
+    assert!(!caps::has_cap(None, CapSet::Bounding, cap).unwrap());
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "libc")]
+
+extern crate caps;
+use caps::{whatif, CapSet, Capability};
+
+#[test]
+fn test_whatif_drop_impact() {
+    let exe = std::env::current_exe().unwrap();
+    let cap = Capability::CAP_SYS_CHROOT;
+    let impact = whatif::drop_impact(cap, &[exe]).unwrap();
+    assert_eq!(impact.capability, cap);
+    assert!(!impact.operations.is_empty());
+    assert!(impact.files.is_empty());
+    assert!(impact.profiles.is_empty());
+    let eff = caps::has_cap(None, CapSet::Effective, cap).unwrap();
+    assert_eq!(impact.held.contains(&CapSet::Effective), eff);
+}
+
+#[test]
+fn test_whatif_drop_impact_profiles() {
+    let impact = whatif::drop_impact::<&str>(Capability::CAP_NET_BIND_SERVICE, &[]).unwrap();
+    let names: Vec<&str> = impact.profiles.iter().map(|p| p.name).collect();
+    assert_eq!(names, ["dhcp-client", "dns-resolver", "ntp", "web-server"]);
+}